@@ -0,0 +1,5 @@
+-- Key/value store for per-database preferences such as the date filter timezone
+CREATE TABLE settings (
+    key TEXT PRIMARY KEY NOT NULL,
+    value TEXT NOT NULL
+);
@@ -0,0 +1,33 @@
+-- Split the due timestamp into an optional calendar day and an optional
+-- wall-clock time, so a task can be due on a day, at a time on a day, or never
+
+-- Step 1: Create new table with nullable due_date and a due_time column
+CREATE TABLE tasks_new (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    parent_id TEXT,
+    completed BOOLEAN NOT NULL DEFAULT FALSE,
+    completed_at TEXT,
+    task_order INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    due_date TEXT,
+    due_time TEXT,
+    FOREIGN KEY (parent_id) REFERENCES tasks_new (id) ON DELETE CASCADE,
+    CHECK (due_time IS NULL OR due_date IS NOT NULL)
+);
+
+-- Step 2: Existing due dates were always meant as days, keep only the
+-- calendar day they fall on in the system timezone
+INSERT INTO tasks_new (id, name, parent_id, completed, completed_at, task_order, created_at, due_date, due_time)
+SELECT id, name, parent_id, completed, completed_at, task_order, created_at, DATE(due_date, 'localtime'), NULL FROM tasks;
+
+-- Step 3: Drop old table and rename new table
+DROP TABLE tasks;
+ALTER TABLE tasks_new RENAME TO tasks;
+
+-- Step 4: Recreate indexes
+CREATE INDEX idx_tasks_parent_id ON tasks (parent_id);
+CREATE INDEX idx_tasks_completed ON tasks (completed);
+CREATE INDEX idx_tasks_parent_order ON tasks (parent_id, task_order);
+CREATE INDEX idx_tasks_created_at ON tasks (created_at);
+CREATE INDEX idx_tasks_due_date ON tasks (due_date);
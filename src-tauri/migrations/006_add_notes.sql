@@ -0,0 +1,2 @@
+-- Add Markdown notes column to tasks table
+ALTER TABLE tasks ADD COLUMN notes TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,2 @@
+-- Add priority column to tasks table: 0 none, 1 low, 2 medium, 3 high, 4 urgent
+ALTER TABLE tasks ADD COLUMN priority INTEGER NOT NULL DEFAULT 0 CHECK (priority BETWEEN 0 AND 4);
@@ -0,0 +1,7 @@
+-- Add recurrence columns to tasks table
+-- recurrence holds an RFC 5545 RRULE, repeat_from whether the next occurrence
+-- is scheduled from the due date or the completion date, and occurrence the
+-- 1-based position in the series for COUNT
+ALTER TABLE tasks ADD COLUMN recurrence TEXT;
+ALTER TABLE tasks ADD COLUMN repeat_from TEXT NOT NULL DEFAULT 'due' CHECK (repeat_from IN ('due', 'completion'));
+ALTER TABLE tasks ADD COLUMN occurrence INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,17 @@
+-- Create reminders table
+-- A reminder fires either at an absolute instant (remind_at) or at an offset
+-- in minutes from the task's due time (offset_minutes, negative for before)
+CREATE TABLE reminders (
+    id TEXT PRIMARY KEY NOT NULL,
+    task_id TEXT NOT NULL,
+    remind_at TEXT,
+    offset_minutes INTEGER,
+    delivered_at TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE,
+    CHECK ((remind_at IS NULL) != (offset_minutes IS NULL))
+);
+
+-- Create indexes for looking up reminders by task and pending reminders
+CREATE INDEX idx_reminders_task_id ON reminders (task_id);
+CREATE INDEX idx_reminders_delivered_at ON reminders (delivered_at);
@@ -0,0 +1,25 @@
+-- Full-text index over task names and notes
+-- Standalone rather than external-content: tasks has no INTEGER PRIMARY KEY,
+-- so its rowids are not stable across VACUUM
+CREATE VIRTUAL TABLE tasks_fts USING fts5(
+    task_id UNINDEXED,
+    name,
+    notes,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO tasks_fts (task_id, name, notes) SELECT id, name, notes FROM tasks;
+
+-- Keep the index in sync with tasks
+CREATE TRIGGER tasks_fts_insert AFTER INSERT ON tasks BEGIN
+    INSERT INTO tasks_fts (task_id, name, notes) VALUES (new.id, new.name, new.notes);
+END;
+
+CREATE TRIGGER tasks_fts_delete AFTER DELETE ON tasks BEGIN
+    DELETE FROM tasks_fts WHERE task_id = old.id;
+END;
+
+CREATE TRIGGER tasks_fts_update AFTER UPDATE OF name, notes ON tasks BEGIN
+    DELETE FROM tasks_fts WHERE task_id = old.id;
+    INSERT INTO tasks_fts (task_id, name, notes) VALUES (new.id, new.name, new.notes);
+END;
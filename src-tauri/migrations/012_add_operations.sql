@@ -0,0 +1,15 @@
+-- Undo history: every change to the task tree with the rows it touched,
+-- before and after, as JSON. Undone operations stay until the next change
+-- so they can be redone
+CREATE TABLE operations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    label TEXT NOT NULL,
+    task_count INTEGER NOT NULL,
+    before TEXT NOT NULL,
+    after TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    undone BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX idx_operations_undone ON operations (undone, id);
@@ -0,0 +1,5 @@
+-- Deleted tasks go to the trash first: the time they were deleted, shared by
+-- a task and the subtasks deleted along with it. NULL for live tasks
+ALTER TABLE tasks ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX idx_tasks_deleted_at ON tasks (deleted_at);
@@ -0,0 +1,23 @@
+-- Audit trail: one row per changed field of a task, with its values before
+-- and after as JSON. Rows outlive their task, so there is no foreign key
+CREATE TABLE task_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT NOT NULL,
+    field TEXT NOT NULL,
+    old_value TEXT NOT NULL,
+    new_value TEXT NOT NULL,
+    origin TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_task_events_task_id ON task_events (task_id, id);
+CREATE INDEX idx_task_events_field ON task_events (field, task_id);
+
+-- Events are only ever appended
+CREATE TRIGGER task_events_no_update BEFORE UPDATE ON task_events BEGIN
+    SELECT RAISE(ABORT, 'task_events is append-only');
+END;
+
+CREATE TRIGGER task_events_no_delete BEFORE DELETE ON task_events BEGIN
+    SELECT RAISE(ABORT, 'task_events is append-only');
+END;
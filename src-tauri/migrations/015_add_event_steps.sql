@@ -0,0 +1,4 @@
+-- Marks the events written by undo and redo, which reports leave out as they
+-- repeat or revert earlier edits. Earlier events cannot be told apart and
+-- stay unmarked
+ALTER TABLE task_events ADD COLUMN step TEXT;
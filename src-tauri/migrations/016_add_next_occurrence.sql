@@ -0,0 +1,4 @@
+-- The next occurrence completing a recurring task spawned, so completing it
+-- again after a reopen does not spawn another. NULL until then, and again
+-- once that occurrence is purged
+ALTER TABLE tasks ADD COLUMN next_occurrence_id TEXT REFERENCES tasks (id) ON DELETE SET NULL;
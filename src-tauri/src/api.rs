@@ -0,0 +1,747 @@
+use std::fs;
+use std::io::Write;
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter};
+use tokio::net::TcpListener;
+use tokio::sync::{oneshot, Mutex};
+use uuid::Uuid;
+
+use crate::audit::Origin;
+use crate::date_filter::{DateFilter, DateFilterEntry};
+use crate::profile::DataDir;
+use crate::repository::TaskRepository;
+use crate::tag::{TagFilter, TagMatch};
+use crate::task::{Priority, Task, TaskSort, TaskUpdate};
+use crate::{Error, Result};
+
+/// Port the API listens on until another one is configured
+pub const DEFAULT_PORT: u16 = 7419;
+
+/// Event the window receives after a request changed tasks, with
+/// [`Changes`] as its payload
+pub const TASKS_CHANGED: &str = "tasks-changed";
+
+/// Kept next to the profiles rather than in a database, as the API serves
+/// whichever profile is open
+const SETTINGS_FILE: &str = "api.json";
+
+/// Bearer token clients authenticate with, readable by its owner only
+const TOKEN_FILE: &str = "api-token";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiSettings {
+    /// Off until turned on, since it lets any local program holding the
+    /// token edit tasks
+    pub enabled: bool,
+    /// Listened on at 127.0.0.1 only
+    pub port: u16,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        ApiSettings {
+            enabled: false,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+impl ApiSettings {
+    /// Settings saved in the data directory, ignoring a missing or mangled
+    /// file
+    pub fn load(data_dir: &DataDir) -> Self {
+        fs::read_to_string(data_dir.path().join(SETTINGS_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &DataDir) -> Result<()> {
+        fs::create_dir_all(data_dir.path())?;
+        let text = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        fs::write(data_dir.path().join(SETTINGS_FILE), format!("{text}\n"))?;
+        Ok(())
+    }
+}
+
+pub fn token_path(data_dir: &DataDir) -> PathBuf {
+    data_dir.path().join(TOKEN_FILE)
+}
+
+/// The token clients send as `Authorization: Bearer <token>`, generated on
+/// first use
+pub fn token(data_dir: &DataDir) -> Result<String> {
+    match fs::read_to_string(token_path(data_dir)) {
+        Ok(token) if !token.trim().is_empty() => Ok(token.trim().to_string()),
+        Ok(_) => rotate_token(data_dir),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => rotate_token(data_dir),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Replace the token with a new random one, locking out clients that hold
+/// the old one
+pub fn rotate_token(data_dir: &DataDir) -> Result<String> {
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    fs::create_dir_all(data_dir.path())?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(token_path(data_dir))?
+        .write_all(format!("{token}\n").as_bytes())?;
+    Ok(token)
+}
+
+/// What a request changed, sent to the window along with [`TASKS_CHANGED`]
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Changes {
+    /// Tasks created or changed, as they are now
+    pub tasks: Vec<Task>,
+    /// Tasks moved to the trash, their subtasks going along
+    pub trashed: Vec<String>,
+}
+
+/// A server listening, and how to stop it
+struct Running {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+/// The API of the running app. Clones share the server, which is
+/// restarted whenever its settings or token change
+#[derive(Clone)]
+pub struct ApiServer {
+    app: AppHandle,
+    repo: TaskRepository,
+    data_dir: DataDir,
+    running: Arc<Mutex<Option<Running>>>,
+}
+
+impl ApiServer {
+    pub fn new(app: AppHandle, repo: &TaskRepository, data_dir: DataDir) -> Self {
+        Self {
+            app,
+            repo: repo.with_origin(Origin::Api),
+            data_dir,
+            running: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn data_dir(&self) -> &DataDir {
+        &self.data_dir
+    }
+
+    /// Stop the server, then start it again if the saved settings enable
+    /// it. Requests in flight are finished first, so the port is free to
+    /// bind again
+    pub async fn restart(&self) -> Result<()> {
+        let mut running = self.running.lock().await;
+        if let Some(Running { stop, task }) = running.take() {
+            let _ = stop.send(());
+            let _ = task.await;
+        }
+
+        let settings = ApiSettings::load(&self.data_dir);
+        if !settings.enabled {
+            return Ok(());
+        }
+        let app = self.app.clone();
+        let state = ApiState {
+            repo: self.repo.clone(),
+            token: token(&self.data_dir)?.into(),
+            notify: Arc::new(move |changes| {
+                if let Err(e) = app.emit(TASKS_CHANGED, changes) {
+                    eprintln!("failed to notify the window of API changes: {e}");
+                }
+            }),
+        };
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, settings.port)).await?;
+        let (stop, stopped) = oneshot::channel::<()>();
+        let task = tauri::async_runtime::spawn(async move {
+            let server = axum::serve(listener, router(state)).with_graceful_shutdown(async {
+                let _ = stopped.await;
+            });
+            if let Err(e) = server.await {
+                eprintln!("API server failed: {e}");
+            }
+        });
+        *running = Some(Running { stop, task });
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    repo: TaskRepository,
+    token: Arc<str>,
+    /// Pushes what a request changed to the window, which reloads
+    notify: Arc<dyn Fn(&Changes) + Send + Sync>,
+}
+
+impl ApiState {
+    fn notify(&self, changes: &Changes) {
+        (self.notify)(changes);
+    }
+}
+
+fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/date-filters", get(date_filters))
+        .route("/tasks", get(list_tasks).post(create_task))
+        .route("/tasks/bulk", post(bulk))
+        .route(
+            "/tasks/{id}",
+            get(get_task).patch(update_task).delete(delete_task),
+        )
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+async fn authorize(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match token {
+        Some(token) if same_token(token.trim(), &state.token) => next.run(request).await,
+        _ => ApiError::Unauthorized.into_response(),
+    }
+}
+
+/// Compare without returning early, so response times do not reveal how
+/// much of a guess was right
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Errors respond with `{ kind, message }`, like the commands return them
+enum ApiError {
+    Task(Error),
+    /// A body or query that could not be read
+    Rejected(StatusCode, String),
+    Unauthorized,
+}
+
+impl From<Error> for ApiError {
+    fn from(e: Error) -> Self {
+        ApiError::Task(e)
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::Rejected(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::Rejected(rejection.status(), rejection.body_text())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::Task(e) => {
+                // No catch-all, so a new variant has to pick its status
+                let status = match e {
+                    Error::TaskNotFound(_)
+                    | Error::TagNotFound(_)
+                    | Error::ReminderNotFound(_)
+                    | Error::BackupNotFound(_) => StatusCode::NOT_FOUND,
+                    Error::AmbiguousId(_)
+                    | Error::Cycle { .. }
+                    | Error::DueTimeWithoutDate
+                    | Error::EmptyTagName
+                    | Error::InvalidRecurrence(_)
+                    | Error::InvalidReminderOffset(_)
+                    | Error::InvalidExport(_)
+                    | Error::UnsupportedExportVersion(_)
+                    | Error::InvalidProfile(_) => StatusCode::BAD_REQUEST,
+                    Error::DuplicateTag(_) | Error::HistoryConflict { .. } => StatusCode::CONFLICT,
+                    Error::Database(_)
+                    | Error::Io(_)
+                    | Error::Migrate(_)
+                    | Error::Pragma { .. }
+                    | Error::InvalidTimezone(_)
+                    | Error::Backup(_)
+                    | Error::CorruptBackup { .. }
+                    | Error::NoDataDir => StatusCode::INTERNAL_SERVER_ERROR,
+                };
+                (status, Json(e)).into_response()
+            }
+            ApiError::Rejected(status, message) => (
+                status,
+                Json(json!({ "kind": "invalidRequest", "message": message })),
+            )
+                .into_response(),
+            ApiError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                Json(json!({
+                    "kind": "unauthorized",
+                    "message": "missing or wrong bearer token",
+                })),
+            )
+                .into_response(),
+        }
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+/// `GET /tasks` takes a date filter shaped like the window sends it, e.g.
+/// `?type=range&startDay=2025-06-01&endDay=2025-06-07`, and lists every
+/// task without one
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListQuery {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    day: Option<NaiveDate>,
+    start_day: Option<NaiveDate>,
+    end_day: Option<NaiveDate>,
+    #[serde(default)]
+    sort: TaskSort,
+    /// Comma-separated tag ids
+    tags: Option<String>,
+    #[serde(default)]
+    tag_mode: TagMatch,
+}
+
+impl ListQuery {
+    fn filter(&self) -> ApiResult<DateFilter> {
+        let filter = json!({
+            "type": self.kind.as_deref().unwrap_or("all"),
+            "day": self.day,
+            "startDay": self.start_day,
+            "endDay": self.end_day,
+        });
+        serde_json::from_value(filter)
+            .map_err(|e| ApiError::Rejected(StatusCode::BAD_REQUEST, e.to_string()))
+    }
+
+    fn tag_filter(&self) -> Option<TagFilter> {
+        let tag_ids: Vec<String> = self
+            .tags
+            .as_deref()?
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .collect();
+        (!tag_ids.is_empty()).then_some(TagFilter {
+            tag_ids,
+            mode: self.tag_mode,
+        })
+    }
+}
+
+async fn list_tasks(
+    State(state): State<ApiState>,
+    query: std::result::Result<Query<ListQuery>, QueryRejection>,
+) -> ApiResult<Json<Vec<Task>>> {
+    let Query(query) = query?;
+    let tasks = state
+        .repo
+        .load_tasks(&query.filter()?, query.tag_filter().as_ref(), query.sort)
+        .await?;
+    Ok(Json(tasks))
+}
+
+async fn date_filters(State(state): State<ApiState>) -> ApiResult<Json<Vec<DateFilterEntry>>> {
+    Ok(Json(state.repo.date_filters().await?))
+}
+
+async fn get_task(State(state): State<ApiState>, Path(id): Path<String>) -> ApiResult<Json<Task>> {
+    let id = state.repo.resolve_id(&id).await?;
+    Ok(Json(state.repo.task(&id).await?))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NewTask {
+    name: String,
+    parent_id: Option<String>,
+    /// Someday when omitted
+    due_date: Option<NaiveDate>,
+    due_time: Option<NaiveTime>,
+}
+
+async fn create_task(
+    State(state): State<ApiState>,
+    body: std::result::Result<Json<NewTask>, JsonRejection>,
+) -> ApiResult<(StatusCode, Json<Task>)> {
+    let Json(new) = body?;
+    let parent_id = match new.parent_id {
+        Some(parent_id) => Some(state.repo.resolve_id(&parent_id).await?),
+        None => None,
+    };
+    let task = state
+        .repo
+        .create_task(&new.name, parent_id.as_deref(), new.due_date, new.due_time)
+        .await?;
+    state.notify(&Changes {
+        tasks: vec![task.clone()],
+        ..Changes::default()
+    });
+    Ok((StatusCode::CREATED, Json(task)))
+}
+
+/// `PATCH /tasks/{id}`, changing only the fields present, all of them or
+/// none
+async fn update_task(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    body: std::result::Result<Json<TaskUpdate>, JsonRejection>,
+) -> ApiResult<Json<Task>> {
+    let Json(update) = body?;
+    let id = state.repo.resolve_id(&id).await?;
+    let tasks = state.repo.update_task(&id, &update).await?;
+    let task = tasks[0].clone();
+    state.notify(&Changes {
+        tasks,
+        ..Changes::default()
+    });
+    Ok(Json(task))
+}
+
+async fn delete_task(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<Changes>> {
+    let id = state.repo.resolve_id(&id).await?;
+    state.repo.delete_subtree(std::slice::from_ref(&id)).await?;
+    let changes = Changes {
+        trashed: vec![id],
+        ..Changes::default()
+    };
+    state.notify(&changes);
+    Ok(Json(changes))
+}
+
+/// `POST /tasks/bulk` body: tasks and what to do with all of them, e.g.
+/// `{ "ids": [...], "action": "reschedule", "dueDate": "2025-06-01" }`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BulkRequest {
+    ids: Vec<String>,
+    #[serde(flatten)]
+    action: BulkAction,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(
+    tag = "action",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum BulkAction {
+    /// Skipping tasks already done
+    Complete,
+    /// Skipping tasks still open
+    Reopen,
+    /// Move to the trash along with their subtasks
+    Trash,
+    /// Along with their incomplete subtasks; without a date they become
+    /// someday tasks
+    Reschedule {
+        due_date: Option<NaiveDate>,
+        due_time: Option<NaiveTime>,
+    },
+    /// Under another task, or to the top level without one
+    Move {
+        parent_id: Option<String>,
+        /// Place among the new siblings, from 0; last when omitted
+        index: Option<usize>,
+    },
+    Prioritize {
+        priority: Priority,
+    },
+    Tag {
+        tag_ids: Vec<String>,
+    },
+    Untag {
+        tag_ids: Vec<String>,
+    },
+}
+
+/// Apply one action to several tasks. Completing and reopening toggle each
+/// task on its own, trashing, rescheduling and moving are a single undo step
+/// and priority and tag changes are not kept in the undo history
+async fn bulk(
+    State(state): State<ApiState>,
+    body: std::result::Result<Json<BulkRequest>, JsonRejection>,
+) -> ApiResult<Json<Changes>> {
+    let Json(request) = body?;
+    let repo = &state.repo;
+    let mut ids = Vec::with_capacity(request.ids.len());
+    for id in &request.ids {
+        ids.push(repo.resolve_id(id).await?);
+    }
+
+    let mut changes = Changes::default();
+    match request.action {
+        action @ (BulkAction::Complete | BulkAction::Reopen) => {
+            let completed = matches!(action, BulkAction::Complete);
+            for id in &ids {
+                // An earlier toggle may have closed or reopened it already
+                if repo.task(id).await?.completed != completed {
+                    for task in repo.toggle_task(id).await? {
+                        changes.tasks.retain(|changed| changed.id != task.id);
+                        changes.tasks.push(task);
+                    }
+                }
+            }
+        }
+        BulkAction::Trash => {
+            repo.delete_subtree(&ids).await?;
+            changes.trashed = ids;
+            state.notify(&changes);
+            return Ok(Json(changes));
+        }
+        BulkAction::Reschedule { due_date, due_time } => {
+            repo.set_due_date(&ids, due_date, due_time).await?;
+        }
+        BulkAction::Move { parent_id, index } => {
+            let parent_id = match parent_id {
+                Some(parent_id) => Some(repo.resolve_id(&parent_id).await?),
+                None => None,
+            };
+            repo.move_tasks(&ids, parent_id.as_deref(), index).await?;
+        }
+        BulkAction::Prioritize { priority } => repo.set_priority(&ids, priority).await?,
+        BulkAction::Tag { tag_ids } => repo.attach_tags(&ids, &tag_ids).await?,
+        BulkAction::Untag { tag_ids } => repo.detach_tags(&ids, &tag_ids).await?,
+    }
+
+    for id in &ids {
+        if !changes.tasks.iter().any(|task| &task.id == id) {
+            changes.tasks.push(repo.task(id).await?);
+        }
+    }
+    state.notify(&changes);
+    Ok(Json(changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use axum::body::{to_bytes, Body};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::history::OperationKind;
+
+    const TOKEN: &str = "secret";
+
+    /// A router over an empty database, and the changes it notified
+    async fn app() -> (Router, TaskRepository, Arc<StdMutex<Vec<Changes>>>) {
+        let repo = TaskRepository::new(crate::db::memory().await).with_origin(Origin::Api);
+        let notified = Arc::new(StdMutex::new(Vec::new()));
+        let sink = notified.clone();
+        let state = ApiState {
+            repo: repo.clone(),
+            token: TOKEN.into(),
+            notify: Arc::new(move |changes| sink.lock().unwrap().push(changes.clone())),
+        };
+        (router(state), repo, notified)
+    }
+
+    async fn send(
+        router: &Router,
+        method: &str,
+        uri: &str,
+        authorization: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        };
+        let response = router.clone().oneshot(request.unwrap()).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn requires_the_bearer_token() {
+        let (router, _, _) = app().await;
+        for authorization in [
+            None,
+            Some("Bearer wrong"),
+            Some("Bearer secret2"),
+            Some("secret"),
+        ] {
+            let (status, body) = send(&router, "GET", "/tasks", authorization, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{authorization:?}");
+            assert_eq!(body["kind"], "unauthorized");
+        }
+        let (status, body) = send(&router, "GET", "/tasks", Some("Bearer secret"), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!([]));
+    }
+
+    #[tokio::test]
+    async fn maps_errors_to_statuses() {
+        let (router, repo, notified) = app().await;
+        let auth = Some("Bearer secret");
+        let (status, body) = send(&router, "GET", "/tasks/missing", auth, None).await;
+        assert_eq!(
+            (status, &body["kind"]),
+            (StatusCode::NOT_FOUND, &json!("taskNotFound"))
+        );
+
+        let (status, task) = send(
+            &router,
+            "POST",
+            "/tasks",
+            auth,
+            Some(json!({ "name": "Call" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let uri = format!("/tasks/{}", task["id"].as_str().unwrap());
+        let update = json!({ "name": "Call back", "dueTime": "09:00" });
+        let (status, body) = send(&router, "PATCH", &uri, auth, Some(update)).await;
+        assert_eq!(
+            (status, &body["kind"]),
+            (StatusCode::BAD_REQUEST, &json!("dueTimeWithoutDate"))
+        );
+        assert_eq!(
+            repo.task(task["id"].as_str().unwrap()).await.unwrap().name,
+            "Call"
+        );
+        assert_eq!(notified.lock().unwrap().len(), 1);
+
+        let (status, body) = send(&router, "POST", "/tasks", auth, Some(json!({}))).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["kind"], "invalidRequest");
+
+        let status = |e: Error| ApiError::Task(e).into_response().status();
+        let conflict = Error::HistoryConflict {
+            step: "undo",
+            operation: "rename".to_string(),
+            reason: "purged".to_string(),
+        };
+        assert_eq!(status(conflict), StatusCode::CONFLICT);
+        assert_eq!(
+            status(Error::InvalidReminderOffset(-1)),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(status(Error::NoDataDir), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn patches_as_one_undo_step() {
+        let (router, repo, notified) = app().await;
+        let auth = Some("Bearer secret");
+        let (_, task) = send(
+            &router,
+            "POST",
+            "/tasks",
+            auth,
+            Some(json!({ "name": "Call" })),
+        )
+        .await;
+        let id = task["id"].as_str().unwrap();
+
+        let update = json!({ "name": "Call back", "dueDate": "2026-11-02", "completed": true });
+        let (status, body) = send(
+            &router,
+            "PATCH",
+            &format!("/tasks/{id}"),
+            auth,
+            Some(update),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            (&body["name"], &body["dueDate"], &body["completed"]),
+            (&json!("Call back"), &json!("2026-11-02"), &json!(true))
+        );
+        assert_eq!(notified.lock().unwrap()[1].tasks[0].name, "Call back");
+
+        let undone = repo.undo().await.unwrap().unwrap();
+        assert_eq!(undone.kind, OperationKind::Edit);
+        let task = repo.task(id).await.unwrap();
+        assert_eq!(
+            (task.name.as_str(), task.due_date, task.completed),
+            ("Call", None, false)
+        );
+    }
+
+    #[test]
+    fn off_until_enabled() {
+        let dir = crate::db::scratch_dir();
+        let data_dir = DataDir::resolve(Some(dir.clone())).unwrap();
+        assert_eq!(
+            ApiSettings::load(&data_dir),
+            ApiSettings {
+                enabled: false,
+                port: DEFAULT_PORT
+            }
+        );
+        let settings = ApiSettings {
+            enabled: true,
+            port: 9000,
+        };
+        settings.save(&data_dir).unwrap();
+        assert_eq!(ApiSettings::load(&data_dir), settings);
+        fs::write(dir.join(SETTINGS_FILE), "{ enabled").unwrap();
+        assert!(!ApiSettings::load(&data_dir).enabled);
+
+        let first = token(&data_dir).unwrap();
+        assert_eq!(token(&data_dir).unwrap(), first);
+        let rotated = rotate_token(&data_dir).unwrap();
+        assert_ne!(rotated, first);
+        assert_eq!(token(&data_dir).unwrap(), rotated);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(token_path(&data_dir))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::history::{Snapshot, TaskRow};
+use crate::task::{Priority, Task};
+
+/// Where a change to the tasks was made
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum Origin {
+    /// The window
+    #[default]
+    Gui,
+    /// `act` subcommands, the terminal UI included
+    Cli,
+    /// The local HTTP API
+    Api,
+    /// Changes pulled from another device
+    Sync,
+}
+
+/// The direction of an undo-log step, for the events it wrote
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum HistoryStep {
+    Undo,
+    Redo,
+}
+
+/// What part of a task an event is about
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(rename_all = "camelCase")]
+pub enum TaskField {
+    /// The task came into existence, its name being the new value
+    Created,
+    /// The task was removed for good by undoing its creation
+    Removed,
+    Name,
+    /// Id of the parent task, null at the top level
+    Parent,
+    Completed,
+    /// `YYYY-MM-DD`, null for someday
+    DueDate,
+    DueTime,
+    Notes,
+    Priority,
+    Recurrence,
+    RepeatFrom,
+    /// Tag names, sorted
+    Tags,
+    /// Whether the task is in the trash
+    Trashed,
+}
+
+/// One changed field of a task. Values are JSON, null where there was none
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskEvent {
+    pub id: i64,
+    pub task_id: String,
+    pub field: TaskField,
+    #[sqlx(json)]
+    pub old_value: Value,
+    #[sqlx(json)]
+    pub new_value: Value,
+    pub origin: Origin,
+    /// Set when an undo or redo made the change rather than an edit
+    pub step: Option<HistoryStep>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskEvent {
+    /// Whether an edit pushed a due date the task already had to a later
+    /// day. Dates are `YYYY-MM-DD`, so they compare as strings
+    pub fn is_reschedule(&self) -> bool {
+        self.field == TaskField::DueDate
+            && self.step.is_none()
+            && matches!(
+                (self.old_value.as_str(), self.new_value.as_str()),
+                (Some(old), Some(new)) if new > old
+            )
+    }
+}
+
+/// The events of a task, oldest first
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskHistory {
+    pub events: Vec<TaskEvent>,
+    /// How often an edit pushed a due date it already had to a later day
+    pub times_rescheduled: i64,
+}
+
+/// A task whose due date slipped, for reports
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RescheduledTask {
+    pub task: Task,
+    pub times_rescheduled: i64,
+    /// The first due date it was pushed back from
+    pub original_due_date: Option<NaiveDate>,
+}
+
+/// A change about to be appended to `task_events`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub task_id: String,
+    pub field: TaskField,
+    pub old_value: Value,
+    pub new_value: Value,
+}
+
+/// Every field that differs between the rows of `before` and `after`.
+/// Order and timestamps are left out as bookkeeping, and tags are named by
+/// `tag_names`, falling back to their ids
+pub fn changes(
+    before: &Snapshot,
+    after: &Snapshot,
+    tag_names: &HashMap<String, String>,
+) -> Vec<Change> {
+    let old_rows: HashMap<&str, &TaskRow> = before
+        .tasks
+        .iter()
+        .map(|row| (row.id.as_str(), row))
+        .collect();
+    let tags = |snapshot: &Snapshot, task_id: &str| {
+        let mut names: Vec<&str> = snapshot
+            .task_tags
+            .iter()
+            .filter(|(id, _)| id == task_id)
+            .map(|(_, tag_id)| tag_names.get(tag_id).unwrap_or(tag_id).as_str())
+            .collect();
+        names.sort_unstable();
+        json!(names)
+    };
+
+    let mut changes = Vec::new();
+    for new in &after.tasks {
+        let mut push = |field, old_value: Value, new_value: Value| {
+            if old_value != new_value {
+                changes.push(Change {
+                    task_id: new.id.clone(),
+                    field,
+                    old_value,
+                    new_value,
+                });
+            }
+        };
+        let Some(old) = old_rows.get(new.id.as_str()) else {
+            push(TaskField::Created, Value::Null, json!(new.name));
+            continue;
+        };
+        push(TaskField::Name, json!(old.name), json!(new.name));
+        push(
+            TaskField::Parent,
+            json!(old.parent_id),
+            json!(new.parent_id),
+        );
+        push(
+            TaskField::Completed,
+            json!(old.completed),
+            json!(new.completed),
+        );
+        push(TaskField::DueDate, json!(old.due_date), json!(new.due_date));
+        push(TaskField::DueTime, json!(old.due_time), json!(new.due_time));
+        push(TaskField::Notes, json!(old.notes), json!(new.notes));
+        push(
+            TaskField::Priority,
+            priority(old.priority),
+            priority(new.priority),
+        );
+        push(
+            TaskField::Recurrence,
+            json!(old.recurrence),
+            json!(new.recurrence),
+        );
+        push(
+            TaskField::RepeatFrom,
+            json!(old.repeat_from),
+            json!(new.repeat_from),
+        );
+        push(TaskField::Tags, tags(before, &new.id), tags(after, &new.id));
+        push(
+            TaskField::Trashed,
+            json!(old.deleted_at.is_some()),
+            json!(new.deleted_at.is_some()),
+        );
+    }
+
+    for old in &before.tasks {
+        if !after.tasks.iter().any(|row| row.id == old.id) {
+            changes.push(Change {
+                task_id: old.id.clone(),
+                field: TaskField::Removed,
+                old_value: json!(old.name),
+                new_value: Value::Null,
+            });
+        }
+    }
+    changes
+}
+
+/// A stored priority by its name, as the frontend knows it
+fn priority(value: i64) -> Value {
+    let priority = match value {
+        1 => Priority::Low,
+        2 => Priority::Medium,
+        3 => Priority::High,
+        4 => Priority::Urgent,
+        _ => Priority::None,
+    };
+    json!(priority)
+}
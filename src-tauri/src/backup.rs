@@ -0,0 +1,453 @@
+use std::collections::HashSet;
+use std::ffi::{c_int, CStr, CString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use libsqlite3_sys as ffi;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+use sqlx::{ConnectOptions, Connection};
+
+use crate::{Error, Result};
+
+/// Timestamp in backup file names, e.g. `act-20250101T120000.000Z-startup.db`
+const STAMP: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// How often a busy backup step is retried before giving up
+const BUSY_RETRIES: u32 = 100;
+const BUSY_SLEEP_MS: c_int = 50;
+
+/// What a backup was taken for
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupReason {
+    /// The app was launched
+    Startup,
+    /// Pending migrations were about to run
+    Migration,
+    /// The backup interval elapsed
+    Scheduled,
+    /// Another backup was about to be restored over the database
+    Restore,
+}
+
+impl BackupReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            BackupReason::Startup => "startup",
+            BackupReason::Migration => "migration",
+            BackupReason::Scheduled => "scheduled",
+            BackupReason::Restore => "restore",
+        }
+    }
+
+    fn parse(reason: &str) -> Option<Self> {
+        [
+            BackupReason::Startup,
+            BackupReason::Migration,
+            BackupReason::Scheduled,
+            BackupReason::Restore,
+        ]
+        .into_iter()
+        .find(|r| r.as_str() == reason)
+    }
+}
+
+/// A snapshot file in a profile's backup directory
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backup {
+    /// The file name, which is what `restore_backup` takes
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub reason: BackupReason,
+    pub size: u64,
+}
+
+/// How often to back up and how many backups to keep: the newest one in
+/// each of the last `keep_hourly` hours that have backups, and likewise for
+/// days and weeks
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSettings {
+    /// Minutes between scheduled backups, 0 to only back up at startup and
+    /// before migrations
+    pub interval_minutes: u32,
+    pub keep_hourly: u32,
+    pub keep_daily: u32,
+    pub keep_weekly: u32,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        BackupSettings {
+            interval_minutes: 60,
+            keep_hourly: 24,
+            keep_daily: 7,
+            keep_weekly: 8,
+        }
+    }
+}
+
+/// The backups of one profile
+#[derive(Debug, Clone)]
+pub struct BackupStore {
+    dir: PathBuf,
+}
+
+impl BackupStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Snapshot the database behind `pool` with the SQLite online backup
+    /// API, which stays consistent while other connections keep writing
+    pub async fn create(&self, pool: &SqlitePool, reason: BackupReason) -> Result<Backup> {
+        fs::create_dir_all(&self.dir)?;
+        let created_at = Utc::now();
+        let id = format!("act-{}-{}.db", created_at.format(STAMP), reason.as_str());
+        let path = self.dir.join(&id);
+        // Copy under another name first so a failed backup is never listed
+        let partial = path.with_extension("db.partial");
+
+        let mut conn = pool.acquire().await?;
+        let copied = {
+            let mut handle = conn.lock_handle().await?;
+            RawDb::open(
+                &partial,
+                ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE,
+            )
+            .and_then(|dest| {
+                // SAFETY: the locked handle keeps sqlx off the source
+                // connection until the copy is done
+                unsafe { copy_database(handle.as_raw_handle().as_ptr(), dest.0)? };
+                // A rollback journal lets the snapshot be opened read-only
+                dest.exec(c"PRAGMA journal_mode = DELETE")
+            })
+        };
+        if let Err(e) = copied {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+        fs::rename(&partial, &path)?;
+
+        Ok(Backup {
+            id,
+            created_at,
+            reason,
+            size: fs::metadata(&path)?.len(),
+        })
+    }
+
+    /// Backups newest first, ignoring files that are not ours
+    pub fn list(&self) -> Result<Vec<Backup>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut backups = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let id = entry.file_name().to_string_lossy().into_owned();
+            let Some((created_at, reason)) = parse_file_name(&id) else {
+                continue;
+            };
+            backups.push(Backup {
+                id,
+                created_at,
+                reason,
+                size: entry.metadata()?.len(),
+            });
+        }
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+        Ok(backups)
+    }
+
+    /// Delete the backups the retention policy no longer covers. The newest
+    /// backup is always kept
+    pub fn prune(&self, settings: &BackupSettings) -> Result<Vec<Backup>> {
+        let backups = self.list()?;
+        let local = |backup: &Backup, format: &str| {
+            backup
+                .created_at
+                .with_timezone(&Local)
+                .format(format)
+                .to_string()
+        };
+
+        let mut keep = HashSet::from([0]);
+        keep_newest_per(&backups, settings.keep_hourly, &mut keep, |b| {
+            local(b, "%Y%m%d%H")
+        });
+        keep_newest_per(&backups, settings.keep_daily, &mut keep, |b| {
+            local(b, "%Y%m%d")
+        });
+        keep_newest_per(&backups, settings.keep_weekly, &mut keep, |b| {
+            local(b, "%G%V")
+        });
+
+        let mut pruned = Vec::new();
+        for (i, backup) in backups.into_iter().enumerate() {
+            if !keep.contains(&i) {
+                fs::remove_file(self.dir.join(&backup.id))?;
+                pruned.push(backup);
+            }
+        }
+        Ok(pruned)
+    }
+
+    /// Replace the database behind `pool` with backup `id`, after checking
+    /// the backup's integrity and snapshotting the current state. Returns
+    /// that snapshot, so a restore can itself be undone
+    pub async fn restore(&self, pool: &SqlitePool, id: &str) -> Result<Backup> {
+        if !self.list()?.iter().any(|backup| backup.id == id) {
+            return Err(Error::BackupNotFound(id.to_string()));
+        }
+        let path = self.dir.join(id);
+        check_integrity(id, &path).await?;
+
+        let current = self.create(pool, BackupReason::Restore).await?;
+
+        let mut conn = pool.acquire().await?;
+        {
+            let mut handle = conn.lock_handle().await?;
+            let src = RawDb::open(&path, ffi::SQLITE_OPEN_READONLY)?;
+            // SAFETY: as in `create`, with the live connection as destination
+            unsafe { copy_database(src.0, handle.as_raw_handle().as_ptr())? };
+        }
+        drop(conn);
+
+        // Older backups are brought up to the current schema
+        crate::db::migrate(pool).await?;
+        Ok(current)
+    }
+}
+
+/// Mark the newest backup of each of the first `count` buckets, `backups`
+/// being sorted newest first so every bucket is contiguous
+fn keep_newest_per(
+    backups: &[Backup],
+    count: u32,
+    keep: &mut HashSet<usize>,
+    bucket: impl Fn(&Backup) -> String,
+) {
+    let mut last = None;
+    let mut kept = 0;
+    for (i, backup) in backups.iter().enumerate() {
+        if kept == count {
+            break;
+        }
+        let key = bucket(backup);
+        if last.as_ref() != Some(&key) {
+            keep.insert(i);
+            kept += 1;
+            last = Some(key);
+        }
+    }
+}
+
+fn parse_file_name(name: &str) -> Option<(DateTime<Utc>, BackupReason)> {
+    let (stamp, reason) = name
+        .strip_prefix("act-")?
+        .strip_suffix(".db")?
+        .split_once('-')?;
+    let created_at = NaiveDateTime::parse_from_str(stamp, STAMP).ok()?.and_utc();
+    Some((created_at, BackupReason::parse(reason)?))
+}
+
+/// Refuse backups SQLite finds damaged or that are not Act databases
+async fn check_integrity(id: &str, path: &Path) -> Result<()> {
+    let corrupt = |reason: String| Error::CorruptBackup {
+        id: id.to_string(),
+        reason,
+    };
+
+    let checked: sqlx::Result<(Vec<String>, bool)> = async {
+        let mut conn = SqliteConnectOptions::new()
+            .filename(path)
+            .read_only(true)
+            .connect()
+            .await?;
+        let problems = sqlx::query_scalar("PRAGMA integrity_check")
+            .fetch_all(&mut conn)
+            .await?;
+        let has_tasks = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'tasks')",
+        )
+        .fetch_one(&mut conn)
+        .await?;
+        conn.close().await?;
+        Ok((problems, has_tasks))
+    }
+    .await;
+
+    // Files SQLite cannot even read count as damaged too
+    let (problems, has_tasks) = checked.map_err(|e| corrupt(e.to_string()))?;
+    if problems != ["ok"] {
+        return Err(corrupt(problems.join("; ")));
+    }
+    if !has_tasks {
+        return Err(corrupt("no tasks table".to_string()));
+    }
+    Ok(())
+}
+
+/// A connection opened outside sqlx, for the other end of a backup
+struct RawDb(*mut ffi::sqlite3);
+
+impl RawDb {
+    fn open(path: &Path, flags: c_int) -> Result<Self> {
+        let path = path
+            .to_str()
+            .and_then(|path| CString::new(path).ok())
+            .ok_or_else(|| Error::Backup(format!("unsupported path {}", path.display())))?;
+
+        let mut db = ptr::null_mut();
+        // SAFETY: `db` is closed by `Drop` even when opening fails
+        let rc = unsafe { ffi::sqlite3_open_v2(path.as_ptr(), &mut db, flags, ptr::null()) };
+        let db = RawDb(db);
+        if rc != ffi::SQLITE_OK {
+            return Err(Error::Backup(unsafe { error_message(db.0) }));
+        }
+        Ok(db)
+    }
+
+    fn exec(&self, sql: &CStr) -> Result<()> {
+        // SAFETY: `self.0` is an open connection
+        let rc = unsafe {
+            ffi::sqlite3_exec(self.0, sql.as_ptr(), None, ptr::null_mut(), ptr::null_mut())
+        };
+        if rc != ffi::SQLITE_OK {
+            return Err(Error::Backup(unsafe { error_message(self.0) }));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RawDb {
+    fn drop(&mut self) {
+        // SAFETY: closing a null handle is a no-op
+        unsafe { ffi::sqlite3_close(self.0) };
+    }
+}
+
+/// Copy every page of `src` into `dest`, retrying while either is locked
+///
+/// # Safety
+///
+/// Both handles must be open and not used by anything else meanwhile
+unsafe fn copy_database(src: *mut ffi::sqlite3, dest: *mut ffi::sqlite3) -> Result<()> {
+    let backup = ffi::sqlite3_backup_init(dest, c"main".as_ptr(), src, c"main".as_ptr());
+    if backup.is_null() {
+        return Err(Error::Backup(error_message(dest)));
+    }
+
+    let mut retries = 0;
+    let step = loop {
+        match ffi::sqlite3_backup_step(backup, -1) {
+            ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED if retries < BUSY_RETRIES => {
+                retries += 1;
+                ffi::sqlite3_sleep(BUSY_SLEEP_MS);
+            }
+            rc => break rc,
+        }
+    };
+    let finish = ffi::sqlite3_backup_finish(backup);
+
+    if step != ffi::SQLITE_DONE || finish != ffi::SQLITE_OK {
+        return Err(Error::Backup(error_message(dest)));
+    }
+    Ok(())
+}
+
+unsafe fn error_message(db: *mut ffi::sqlite3) -> String {
+    if db.is_null() {
+        return "out of memory".to_string();
+    }
+    CStr::from_ptr(ffi::sqlite3_errmsg(db))
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::TaskRepository;
+
+    async fn names(pool: &SqlitePool) -> Vec<String> {
+        sqlx::query_scalar("SELECT name FROM tasks ORDER BY name")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn backs_up_prunes_and_restores() {
+        let dir = crate::db::scratch_dir();
+        let store = BackupStore::new(dir.clone());
+        let repo = TaskRepository::new(crate::db::memory().await);
+        repo.create_task("Before", None, None, None).await.unwrap();
+        let backup = store
+            .create(&repo.pool(), BackupReason::Startup)
+            .await
+            .unwrap();
+        repo.create_task("After", None, None, None).await.unwrap();
+
+        // Older copies, two of them within the same hour
+        let older = |stamp: &str| {
+            let id = format!("act-{stamp}-scheduled.db");
+            fs::copy(dir.join(&backup.id), dir.join(&id)).unwrap();
+            id
+        };
+        let newer_same_hour = older("20260105T100510.000Z");
+        let same_hour = older("20260105T100500.000Z");
+        let day_before = older("20260104T100500.000Z");
+        let two_days_before = older("20260103T100500.000Z");
+        fs::write(dir.join("notes.txt"), "not a backup").unwrap();
+
+        let settings = BackupSettings {
+            interval_minutes: 60,
+            keep_hourly: 3,
+            keep_daily: 0,
+            keep_weekly: 0,
+        };
+        let pruned: Vec<String> = store
+            .prune(&settings)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.id)
+            .collect();
+        assert_eq!(pruned, [same_hour, two_days_before]);
+        let kept: Vec<String> = store.list().unwrap().into_iter().map(|b| b.id).collect();
+        assert_eq!(kept, [backup.id.clone(), newer_same_hour, day_before]);
+
+        let current = store.restore(&repo.pool(), &backup.id).await.unwrap();
+        assert_eq!(current.reason, BackupReason::Restore);
+        assert_eq!(names(&repo.pool()).await, ["Before"]);
+        // The snapshot taken first undoes the restore
+        store.restore(&repo.pool(), &current.id).await.unwrap();
+        assert_eq!(names(&repo.pool()).await, ["After", "Before"]);
+
+        let corrupt = "act-20260101T000000.000Z-scheduled.db";
+        let mut bytes = fs::read(dir.join(&backup.id)).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        bytes[100..200].fill(0xff);
+        fs::write(dir.join(corrupt), bytes).unwrap();
+        assert!(matches!(
+            store.restore(&repo.pool(), corrupt).await,
+            Err(Error::CorruptBackup { .. })
+        ));
+        assert!(matches!(
+            store.restore(&repo.pool(), "notes.txt").await,
+            Err(Error::BackupNotFound(_))
+        ));
+        assert_eq!(names(&repo.pool()).await, ["After", "Before"]);
+
+        repo.pool().close().await;
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
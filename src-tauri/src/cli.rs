@@ -0,0 +1,1104 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::api::{self, ApiSettings};
+use crate::audit::{HistoryStep, Origin, TaskEvent, TaskField};
+use crate::date_filter::{day_label, DateFilter, Zone};
+use crate::export::{Document, ExportFilter, ExportedTask, IdMode, ImportOptions};
+use crate::history::Operation;
+use crate::profile::DataDir;
+use crate::recurrence::RepeatFrom;
+use crate::reminder::ReminderTrigger;
+use crate::repository::TaskRepository;
+use crate::search::PathEntry;
+use crate::task::{Priority, Task, TaskSort};
+use crate::trash::TrashEntry;
+use crate::{ical, markdown, todotxt, tui, Result};
+
+/// Characters of a task id shown in listings, enough to type it back
+const SHORT_ID: usize = 8;
+
+/// Take Action on your Tasks
+#[derive(Debug, Parser)]
+#[command(name = "act", version, about)]
+pub struct Cli {
+    /// Directory holding the databases
+    #[arg(long, global = true, env = "ACT_HOME", value_name = "DIR")]
+    pub data_dir: Option<PathBuf>,
+
+    /// Profile to open, instead of the one used last
+    #[arg(long, global = true, env = "ACT_PROFILE", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Print JSON instead of text, for scripts
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Run a command instead of opening the window
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// File formats tasks can be exported to and imported from
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// Act's own JSON document, keeping everything
+    Json,
+    /// One todo.txt line per task
+    Todotxt,
+    /// iCalendar VTODOs, for calendar apps
+    Ical,
+    /// Checklists grouped by due day; importing reads any pasted list
+    Markdown,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    #[command(flatten)]
+    Tasks(TaskCommand),
+    /// Configure the HTTP API the window serves to scripts and editor
+    /// plugins
+    Api {
+        #[command(subcommand)]
+        command: ApiCommand,
+    },
+}
+
+/// Commands run against a profile's database. Task ids can be abbreviated
+/// to any unique prefix, such as the eight characters `act ls` shows. Days
+/// are `today`, `tomorrow`, `yesterday`, a weekday (the next one after
+/// today), `+3d` / `+2w`, or `YYYY-MM-DD`
+#[derive(Debug, Subcommand)]
+pub enum TaskCommand {
+    /// Add a task
+    Add {
+        name: String,
+        /// Day the task is due
+        #[arg(long, value_name = "DAY", value_parser = parse_day)]
+        due: Option<Day>,
+        /// Time of day the task is due, needs --due
+        #[arg(long, value_name = "HH:MM", value_parser = parse_time, requires = "due")]
+        at: Option<NaiveTime>,
+        /// Add it as a subtask of this task
+        #[arg(long, value_name = "ID")]
+        parent: Option<String>,
+    },
+    /// List tasks with their parents, like the sidebar filters: `all`,
+    /// `today` and `tomorrow` (both with overdue tasks), `yesterday`,
+    /// `someday`, a day, or a range `DAY..DAY`
+    Ls {
+        #[arg(default_value = "all", value_parser = parse_listing)]
+        when: Listing,
+        /// Highest priority first instead of the manual order
+        #[arg(long)]
+        by_priority: bool,
+    },
+    /// Complete tasks. Parents whose subtasks are then all done complete
+    /// too, and recurring tasks get their next occurrence
+    Done {
+        #[arg(required = true, value_name = "ID")]
+        ids: Vec<String>,
+    },
+    /// Move tasks together with their subtasks to the trash
+    Rm {
+        #[arg(required = true, value_name = "ID")]
+        ids: Vec<String>,
+    },
+    /// List, restore or purge deleted tasks
+    Trash {
+        #[command(subcommand)]
+        command: TrashCommand,
+    },
+    /// Move tasks under another task, or to the top level
+    Mv {
+        #[arg(required = true, value_name = "ID")]
+        ids: Vec<String>,
+        /// New parent, the top level when omitted
+        #[arg(long, value_name = "ID")]
+        parent: Option<String>,
+        /// Place among the new siblings, from 1; last when omitted
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        position: Option<u32>,
+    },
+    /// Show a task with its notes, reminders and subtasks
+    Show {
+        #[arg(value_name = "ID")]
+        id: String,
+    },
+    /// Write tasks to a file
+    Export {
+        #[arg(short, long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// File to write, standard output when omitted
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Only tasks due on or after this day, with their parents
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        from: Option<NaiveDate>,
+        /// Only tasks due on or before this day, with their parents
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        to: Option<NaiveDate>,
+        /// Only this task and its subtasks
+        #[arg(long, value_name = "ID")]
+        subtree: Option<String>,
+    },
+    /// Read tasks from a file, e.g. one written by `act export`
+    Import {
+        file: PathBuf,
+        #[arg(short, long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// Give the tasks new ids instead of updating tasks with the same id
+        #[arg(long)]
+        remap: bool,
+        /// Add a Markdown checklist as subtasks of this task, ignored for
+        /// other formats
+        #[arg(long, value_name = "ID")]
+        parent: Option<String>,
+        /// Only report what would change
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show every recorded change to a task, oldest first
+    History {
+        #[arg(value_name = "ID")]
+        id: String,
+    },
+    /// List tasks whose due date was pushed back, the most rescheduled first
+    Rescheduled {
+        /// Only tasks rescheduled at least this many times
+        #[arg(long, value_name = "N", default_value_t = 1)]
+        min: u32,
+    },
+    /// Revert the last change to the tasks, made here or in the window
+    Undo,
+    /// Apply the change undone last again
+    Redo,
+    /// Browse and edit tasks in the terminal, in columns like the window
+    Tui,
+}
+
+/// Trash entries are a deleted task with the subtasks deleted along with
+/// it, and their ids can be abbreviated like those of live tasks
+#[derive(Debug, Subcommand)]
+pub enum TrashCommand {
+    /// List deleted tasks, most recent first
+    Ls,
+    /// Put deleted tasks back where they were, or at the top level when
+    /// their parent is gone
+    Restore {
+        #[arg(required = true, value_name = "ID")]
+        ids: Vec<String>,
+    },
+    /// Permanently delete what is in the trash
+    Purge {
+        /// Only tasks deleted more than this many days ago
+        #[arg(long, value_name = "DAYS")]
+        older_than: Option<u32>,
+    },
+}
+
+/// The API listens on 127.0.0.1 while the window is open. Changes to its
+/// settings apply the next time the window opens
+#[derive(Debug, Subcommand)]
+pub enum ApiCommand {
+    /// Show whether the API is enabled and where it listens
+    Status,
+    /// Serve the API from the window
+    Enable {
+        /// Port to listen on, the one configured before when omitted
+        #[arg(long)]
+        port: Option<u16>,
+    },
+    /// Stop serving the API
+    Disable,
+    /// Print the bearer token clients authenticate with
+    Token {
+        /// Replace it first, locking out clients holding the old one
+        #[arg(long)]
+        rotate: bool,
+    },
+}
+
+/// A day as typed on the command line, resolved once today is known in the
+/// configured timezone
+#[derive(Debug, Clone, Copy)]
+pub enum Day {
+    /// Days from today
+    Offset(i64),
+    /// The first such weekday after today
+    Next(Weekday),
+    On(NaiveDate),
+}
+
+impl Day {
+    pub(crate) fn resolve(self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Day::Offset(days) => today + Duration::days(days),
+            Day::Next(weekday) => {
+                let ahead = (weekday.num_days_from_monday() + 6
+                    - today.weekday().num_days_from_monday())
+                    % 7
+                    + 1;
+                today + Duration::days(ahead.into())
+            }
+            Day::On(day) => day,
+        }
+    }
+}
+
+pub(crate) fn parse_day(text: &str) -> std::result::Result<Day, String> {
+    let lower = text.to_ascii_lowercase();
+    match lower.as_str() {
+        "today" => return Ok(Day::Offset(0)),
+        "tomorrow" => return Ok(Day::Offset(1)),
+        "yesterday" => return Ok(Day::Offset(-1)),
+        _ => {}
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(Day::On(day));
+    }
+    if let Ok(weekday) = lower.parse::<Weekday>() {
+        return Ok(Day::Next(weekday));
+    }
+    if let Some(offset) = lower.strip_prefix('+') {
+        let invalid = || "expected e.g. +3d or +2w".to_string();
+        let (count, days) = if let Some(count) = offset.strip_suffix('d') {
+            (count, 1)
+        } else if let Some(count) = offset.strip_suffix('w') {
+            (count, 7)
+        } else {
+            return Err(invalid());
+        };
+        let count: u16 = count.parse().map_err(|_| invalid())?;
+        return Ok(Day::Offset(i64::from(count) * days));
+    }
+    Err("expected today, tomorrow, a weekday, +3d or YYYY-MM-DD".to_string())
+}
+
+pub(crate) fn parse_time(text: &str) -> std::result::Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(text, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(text, "%H:%M:%S"))
+        .map_err(|_| "expected HH:MM".to_string())
+}
+
+/// Which tasks `act ls` shows
+#[derive(Debug, Clone)]
+pub enum Listing {
+    Filter(DateFilter),
+    On(Day),
+    Between(Day, Day),
+}
+
+impl Listing {
+    fn filter(&self, today: NaiveDate) -> DateFilter {
+        match *self {
+            Listing::Filter(ref filter) => filter.clone(),
+            Listing::On(day) => DateFilter::Date {
+                day: day.resolve(today),
+            },
+            Listing::Between(start, end) => DateFilter::Range {
+                start_day: start.resolve(today),
+                end_day: end.resolve(today),
+            },
+        }
+    }
+}
+
+fn parse_listing(text: &str) -> std::result::Result<Listing, String> {
+    let filter = match text.to_ascii_lowercase().as_str() {
+        "all" => DateFilter::All,
+        "today" => DateFilter::Today,
+        "tomorrow" => DateFilter::Tomorrow,
+        "yesterday" => DateFilter::Yesterday,
+        "someday" => DateFilter::Someday,
+        _ => {
+            return match text.split_once("..") {
+                Some((start, end)) => Ok(Listing::Between(parse_day(start)?, parse_day(end)?)),
+                None => parse_day(text).map(Listing::On),
+            }
+        }
+    };
+    Ok(Listing::Filter(filter))
+}
+
+/// Run `command` against the profile's database, returning the exit code
+pub fn run(command: Command, data_dir: &DataDir, profile: &str, json: bool) -> i32 {
+    let result = match command {
+        // Not tied to a profile, so no database is opened
+        Command::Api { command } => configure_api(data_dir, command, json),
+        Command::Tasks(command) => tauri::async_runtime::block_on(async {
+            let repo = TaskRepository::new(data_dir.open(profile).await?).with_origin(Origin::Cli);
+            execute(&repo, command, json).await
+        }),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("act: {e}");
+            1
+        }
+    }
+}
+
+async fn execute(repo: &TaskRepository, command: TaskCommand, json: bool) -> Result<()> {
+    let zone = repo.timezone().await?;
+    let today = zone.local_time(Utc::now()).date();
+
+    match command {
+        TaskCommand::Add {
+            name,
+            due,
+            at,
+            parent,
+        } => {
+            let parent = match parent {
+                Some(parent) => Some(repo.resolve_id(&parent).await?),
+                None => None,
+            };
+            let due = due.map(|day| day.resolve(today));
+            let task = repo.create_task(&name, parent.as_deref(), due, at).await?;
+            if json {
+                print_json(&task);
+            } else {
+                println!("{}", task_line(&task, 0, today));
+            }
+        }
+        TaskCommand::Ls { when, by_priority } => {
+            let sort = if by_priority {
+                TaskSort::Priority
+            } else {
+                TaskSort::Manual
+            };
+            let tasks = repo.load_tasks(&when.filter(today), None, sort).await?;
+            if json {
+                print_json(&tasks);
+            } else {
+                print_tree(&tasks, today);
+            }
+        }
+        TaskCommand::Done { ids } => {
+            let mut changed = Vec::new();
+            for id in ids {
+                let task = repo.task(&repo.resolve_id(&id).await?).await?;
+                if task.completed {
+                    if !json {
+                        eprintln!("already done: {}", task.name);
+                    }
+                    continue;
+                }
+                changed.extend(repo.toggle_task(&task.id).await?);
+            }
+            if json {
+                print_json(&changed);
+            } else {
+                for task in &changed {
+                    println!("{}", task_line(task, 0, today));
+                }
+            }
+        }
+        TaskCommand::Rm { ids } => {
+            let ids = resolve_ids(repo, &ids).await?;
+            let deleted = repo.delete_subtree(&ids).await?;
+            if json {
+                print_json(&serde_json::json!({ "deleted": deleted }));
+            } else {
+                println!("moved {deleted} {} to the trash", plural(deleted, "task"));
+            }
+        }
+        TaskCommand::Trash { command } => match command {
+            TrashCommand::Ls => {
+                let entries = repo.trash().await?;
+                if json {
+                    print_json(&entries);
+                } else if entries.is_empty() {
+                    println!("the trash is empty");
+                } else {
+                    for entry in &entries {
+                        println!("{}", trash_line(entry, &zone, today));
+                    }
+                }
+            }
+            TrashCommand::Restore { ids } => {
+                let entries = repo.trash().await?;
+                let mut restored = Vec::new();
+                for id in ids {
+                    let id = resolve_trashed(&entries, &id)?;
+                    restored.push(repo.restore_from_trash(&id).await?);
+                }
+                if json {
+                    print_json(&restored);
+                } else {
+                    for task in &restored {
+                        println!("{}", task_line(task, 0, today));
+                    }
+                }
+            }
+            TrashCommand::Purge { older_than } => {
+                let cutoff = older_than.map(|days| Utc::now() - Duration::days(days.into()));
+                let purged = repo.purge_trash(cutoff).await?;
+                if json {
+                    print_json(&serde_json::json!({ "purged": purged }));
+                } else {
+                    println!("purged {purged} {}", plural(purged, "task"));
+                }
+            }
+        },
+        TaskCommand::Mv {
+            ids,
+            parent,
+            position,
+        } => {
+            let ids = resolve_ids(repo, &ids).await?;
+            let parent = match parent {
+                Some(parent) => Some(repo.task(&repo.resolve_id(&parent).await?).await?),
+                None => None,
+            };
+            let index = position.map(|position| position as usize - 1);
+            repo.move_tasks(&ids, parent.as_ref().map(|p| p.id.as_str()), index)
+                .await?;
+
+            if json {
+                let mut moved = Vec::new();
+                for id in &ids {
+                    moved.push(repo.task(id).await?);
+                }
+                print_json(&moved);
+            } else {
+                let target = match &parent {
+                    Some(parent) => format!("under {}", parent.name),
+                    None => "to the top level".to_string(),
+                };
+                let count = ids.len() as u64;
+                println!("moved {count} {} {target}", plural(count, "task"));
+            }
+        }
+        TaskCommand::Show { id } => {
+            let task = repo.task(&repo.resolve_id(&id).await?).await?;
+            let filter = ExportFilter {
+                root_id: Some(task.id.clone()),
+                ..ExportFilter::default()
+            };
+            let mut subtree = repo.export_tasks(&filter).await?.tasks;
+            let details = subtree.remove(0);
+
+            let mut path = Vec::new();
+            let mut parent_id = task.parent_id.clone();
+            while let Some(id) = parent_id {
+                let parent = repo.task(&id).await?;
+                parent_id = parent.parent_id;
+                path.insert(
+                    0,
+                    PathEntry {
+                        id: parent.id,
+                        name: parent.name,
+                    },
+                );
+            }
+
+            let shown = Shown {
+                notes: details.notes.unwrap_or_default(),
+                tags: details.tags,
+                reminders: details.reminders.unwrap_or_default(),
+                path,
+                subtasks: subtree,
+                task,
+            };
+            if json {
+                print_json(&shown);
+            } else {
+                print_shown(&shown, &zone, today);
+            }
+        }
+        TaskCommand::Export {
+            format,
+            output,
+            from,
+            to,
+            subtree,
+        } => {
+            let root_id = match subtree {
+                Some(id) => Some(repo.resolve_id(&id).await?),
+                None => None,
+            };
+            let filter = ExportFilter { from, to, root_id };
+            let text = match format {
+                Format::Json => {
+                    let document = repo.export_tasks(&filter).await?;
+                    serde_json::to_string_pretty(&document).expect("document serializes") + "\n"
+                }
+                Format::Todotxt => todotxt::export(repo, &filter).await?,
+                Format::Ical => ical::export(repo, &filter).await?,
+                Format::Markdown => markdown::export(repo, &filter).await?,
+            };
+            match output {
+                Some(path) => fs::write(path, text)?,
+                None => print!("{text}"),
+            }
+        }
+        TaskCommand::Import {
+            file,
+            format,
+            remap,
+            parent,
+            dry_run,
+        } => {
+            let text = fs::read_to_string(file)?;
+            let options = ImportOptions {
+                ids: if remap { IdMode::Remap } else { IdMode::Merge },
+                dry_run,
+            };
+            let report = match format {
+                Format::Json => {
+                    repo.import_tasks(&Document::from_json(&text)?, options)
+                        .await?
+                }
+                Format::Todotxt => todotxt::import(repo, &text, options).await?,
+                Format::Ical => ical::import(repo, &text, options).await?,
+                Format::Markdown => {
+                    let parent = match parent {
+                        Some(parent) => Some(repo.resolve_id(&parent).await?),
+                        None => None,
+                    };
+                    markdown::import(repo, &text, parent.as_deref(), options).await?
+                }
+            };
+
+            if json {
+                print_json(&report);
+                return Ok(());
+            }
+            for warning in &report.warnings {
+                eprintln!("warning: {warning}");
+            }
+            let verb = if report.dry_run {
+                "would import"
+            } else {
+                "imported"
+            };
+            println!(
+                "{verb} {} new and {} updated tasks, {} new tags",
+                report.created, report.updated, report.tags_created
+            );
+        }
+        TaskCommand::History { id } => {
+            let history = repo.task_history(&repo.resolve_id(&id).await?).await?;
+            if json {
+                print_json(&history);
+            } else {
+                println!(
+                    "rescheduled {} {}",
+                    history.times_rescheduled,
+                    plural(history.times_rescheduled as u64, "time")
+                );
+                for event in &history.events {
+                    println!("{}", event_line(event, &zone));
+                }
+            }
+        }
+        TaskCommand::Rescheduled { min } => {
+            let rescheduled = repo.rescheduled_tasks(min).await?;
+            if json {
+                print_json(&rescheduled);
+            } else {
+                for entry in &rescheduled {
+                    let mut line = format!(
+                        "{}  rescheduled {} {}",
+                        task_line(&entry.task, 0, today),
+                        entry.times_rescheduled,
+                        plural(entry.times_rescheduled as u64, "time")
+                    );
+                    if let Some(day) = entry.original_due_date {
+                        line.push_str(&format!(", first due {}", day_label(day, today)));
+                    }
+                    println!("{line}");
+                }
+            }
+        }
+        TaskCommand::Undo => print_step(repo.undo().await?, "undid", "nothing to undo", json),
+        TaskCommand::Redo => print_step(repo.redo().await?, "redid", "nothing to redo", json),
+        TaskCommand::Tui => tui::run(repo).await?,
+    }
+    Ok(())
+}
+
+fn configure_api(data_dir: &DataDir, command: ApiCommand, json: bool) -> Result<()> {
+    let mut settings = ApiSettings::load(data_dir);
+    match command {
+        ApiCommand::Status => {}
+        ApiCommand::Enable { port } => {
+            settings.enabled = true;
+            settings.port = port.unwrap_or(settings.port);
+            settings.save(data_dir)?;
+        }
+        ApiCommand::Disable => {
+            settings.enabled = false;
+            settings.save(data_dir)?;
+        }
+        ApiCommand::Token { rotate } => {
+            let token = if rotate {
+                api::rotate_token(data_dir)?
+            } else {
+                api::token(data_dir)?
+            };
+            if json {
+                print_json(&serde_json::json!({ "token": token }));
+            } else {
+                println!("{token}");
+            }
+            return Ok(());
+        }
+    }
+
+    if json {
+        print_json(&settings);
+    } else if settings.enabled {
+        println!("enabled on http://127.0.0.1:{}", settings.port);
+        println!("token in {}", api::token_path(data_dir).display());
+    } else {
+        println!("disabled");
+    }
+    Ok(())
+}
+
+async fn resolve_ids(repo: &TaskRepository, ids: &[String]) -> Result<Vec<String>> {
+    let mut resolved = Vec::with_capacity(ids.len());
+    for id in ids {
+        resolved.push(repo.resolve_id(id).await?);
+    }
+    Ok(resolved)
+}
+
+/// Full id of the one trash entry whose id starts with `prefix`, an exact
+/// match winning over longer ones
+fn resolve_trashed(entries: &[TrashEntry], prefix: &str) -> Result<String> {
+    if entries.iter().any(|entry| entry.task.id == prefix) {
+        return Ok(prefix.to_string());
+    }
+    let mut matching = entries
+        .iter()
+        .filter(|entry| entry.task.id.starts_with(prefix));
+    match (matching.next(), matching.next()) {
+        (Some(entry), None) => Ok(entry.task.id.clone()),
+        (None, _) => Err(crate::Error::TaskNotFound(prefix.to_string())),
+        (Some(_), Some(_)) => Err(crate::Error::AmbiguousId(prefix.to_string())),
+    }
+}
+
+/// What `act show` prints
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Shown {
+    #[serde(flatten)]
+    task: Task,
+    notes: String,
+    tags: Vec<String>,
+    reminders: Vec<ReminderTrigger>,
+    /// Ancestors from the root down
+    path: Vec<PathEntry>,
+    /// Every descendant, parents before their children
+    subtasks: Vec<ExportedTask>,
+}
+
+/// What undo or redo did, or `none` when there was nothing to do
+fn print_step(operation: Option<Operation>, verb: &str, none: &str, json: bool) {
+    if json {
+        print_json(&operation);
+        return;
+    }
+    match operation {
+        Some(operation) => println!("{verb} {}", operation.description()),
+        None => println!("{none}"),
+    }
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).expect("values serialize to JSON")
+    );
+}
+
+/// Tasks indented under their parents, which come first in the listing or
+/// are missing from it
+fn print_tree(tasks: &[Task], today: NaiveDate) {
+    let listed: HashSet<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+    let mut children: HashMap<&str, Vec<&Task>> = HashMap::new();
+    let mut roots = Vec::new();
+    for task in tasks {
+        match task.parent_id.as_deref() {
+            Some(parent_id) if listed.contains(parent_id) => {
+                children.entry(parent_id).or_default().push(task)
+            }
+            _ => roots.push(task),
+        }
+    }
+
+    let mut stack: Vec<(&Task, usize)> = roots.into_iter().rev().map(|t| (t, 0)).collect();
+    while let Some((task, depth)) = stack.pop() {
+        println!("{}", task_line(task, depth, today));
+        if let Some(children) = children.get(task.id.as_str()) {
+            stack.extend(children.iter().rev().map(|&child| (child, depth + 1)));
+        }
+    }
+}
+
+/// `3f2a1b9c  [ ] Name  (Tomorrow 14:30, high, 1/3)`
+fn task_line(task: &Task, depth: usize, today: NaiveDate) -> String {
+    let mut details = Vec::new();
+    if let Some(due) = due_label(task.due_date, task.due_time, today) {
+        details.push(due);
+    }
+    if task.overdue {
+        details.push("overdue".to_string());
+    }
+    if let Some(priority) = priority_label(task.priority) {
+        details.push(priority.to_string());
+    }
+    if task.recurrence.is_some() {
+        details.push("repeats".to_string());
+    }
+    if task.total_subtasks > 0 {
+        details.push(format!(
+            "{}/{}",
+            task.completed_subtasks, task.total_subtasks
+        ));
+    }
+    let details = if details.is_empty() {
+        String::new()
+    } else {
+        format!("  ({})", details.join(", "))
+    };
+    format!(
+        "{}  {}{}{details}",
+        short_id(&task.id),
+        "  ".repeat(depth),
+        checkbox(task.completed, &task.name)
+    )
+}
+
+/// `3f2a1b9c  [ ] Name  (deleted Today 09:12, 3 tasks, from Home › Garden)`
+fn trash_line(entry: &TrashEntry, zone: &Zone, today: NaiveDate) -> String {
+    let deleted_at = zone.local_time(entry.deleted_at);
+    let mut details = vec![format!(
+        "deleted {} {}",
+        day_label(deleted_at.date(), today),
+        deleted_at.format("%H:%M")
+    )];
+    if entry.task_count > 1 {
+        details.push(format!("{} tasks", entry.task_count));
+    }
+    if !entry.path.is_empty() {
+        let names: Vec<&str> = entry.path.iter().map(|entry| entry.name.as_str()).collect();
+        details.push(format!("from {}", names.join(" › ")));
+    }
+    format!(
+        "{}  {}  ({})",
+        short_id(&entry.task.id),
+        checkbox(entry.task.completed, &entry.task.name),
+        details.join(", ")
+    )
+}
+
+/// `2026-10-17 09:12  cli  due date: 2026-10-20 → 2026-10-24`
+fn event_line(event: &TaskEvent, zone: &Zone) -> String {
+    let field = match event.field {
+        TaskField::Created => "created",
+        TaskField::Removed => "removed",
+        TaskField::Name => "name",
+        TaskField::Parent => "parent",
+        TaskField::Completed => "done",
+        TaskField::DueDate => "due date",
+        TaskField::DueTime => "due time",
+        TaskField::Notes => "notes",
+        TaskField::Priority => "priority",
+        TaskField::Recurrence => "repeats",
+        TaskField::RepeatFrom => "repeats from",
+        TaskField::Tags => "tags",
+        TaskField::Trashed => "in trash",
+    };
+    let origin = match event.origin {
+        Origin::Gui => "gui",
+        Origin::Cli => "cli",
+        Origin::Api => "api",
+        Origin::Sync => "sync",
+    };
+    let change = match event.field {
+        TaskField::Created => format!("{field} as {}", value_label(&event.new_value)),
+        TaskField::Removed => field.to_string(),
+        TaskField::Parent => format!(
+            "{field}: {} → {}",
+            id_label(&event.old_value),
+            id_label(&event.new_value)
+        ),
+        _ => format!(
+            "{field}: {} → {}",
+            value_label(&event.old_value),
+            value_label(&event.new_value)
+        ),
+    };
+    let step = match event.step {
+        Some(HistoryStep::Undo) => " (undo)",
+        Some(HistoryStep::Redo) => " (redo)",
+        None => "",
+    };
+    format!(
+        "{}  {origin:<4} {change}{step}",
+        zone.local_time(event.created_at).format("%Y-%m-%d %H:%M")
+    )
+}
+
+/// A value of a task event on one short line, `-` for none or empty
+fn value_label(value: &Value) -> String {
+    const MAX: usize = 40;
+    let text = match value {
+        Value::Null => return "-".to_string(),
+        Value::Bool(true) => "yes".to_string(),
+        Value::Bool(false) => "no".to_string(),
+        Value::String(text) => text.split_whitespace().collect::<Vec<_>>().join(" "),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map_or_else(|| item.to_string(), str::to_string)
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        value => value.to_string(),
+    };
+    if text.is_empty() {
+        "-".to_string()
+    } else if text.chars().count() > MAX {
+        format!("{}…", text.chars().take(MAX).collect::<String>())
+    } else {
+        text
+    }
+}
+
+/// A task id of a task event shortened like in listings, `top level` for none
+fn id_label(value: &Value) -> String {
+    value.as_str().map_or_else(
+        || "top level".to_string(),
+        |id| short_id(id).trim_end().to_string(),
+    )
+}
+
+fn print_shown(shown: &Shown, zone: &Zone, today: NaiveDate) {
+    let task = &shown.task;
+    println!("{}", checkbox(task.completed, &task.name));
+
+    let mut fields: Vec<(&str, String)> = vec![("id", task.id.clone())];
+    if let Some(completed_at) = task.completed_at {
+        let day = zone.local_time(completed_at).date();
+        fields.push(("done", day_label(day, today)));
+    }
+    if let Some(mut due) = due_label(task.due_date, task.due_time, today) {
+        if task.overdue {
+            due.push_str(", overdue");
+        }
+        fields.push(("due", due));
+    }
+    if let Some(priority) = priority_label(task.priority) {
+        fields.push(("priority", priority.to_string()));
+    }
+    if let Some(rule) = &task.recurrence {
+        let from = match task.repeat_from {
+            RepeatFrom::Due => "due date",
+            RepeatFrom::Completion => "completion",
+        };
+        fields.push((
+            "repeats",
+            format!("{rule} from {from}, occurrence {}", task.occurrence),
+        ));
+    }
+    if !shown.tags.is_empty() {
+        fields.push(("tags", shown.tags.join(", ")));
+    }
+    if !shown.path.is_empty() {
+        let names: Vec<&str> = shown.path.iter().map(|entry| entry.name.as_str()).collect();
+        fields.push(("parent", names.join(" › ")));
+    }
+    for reminder in &shown.reminders {
+        let when = match reminder {
+            ReminderTrigger::Absolute { at } => {
+                let local = zone.local_time(*at);
+                format!(
+                    "{} {}",
+                    day_label(local.date(), today),
+                    local.format("%H:%M")
+                )
+            }
+            ReminderTrigger::Relative { offset_minutes: 0 } => "when due".to_string(),
+            ReminderTrigger::Relative { offset_minutes } => {
+                let side = if *offset_minutes < 0 {
+                    "before"
+                } else {
+                    "after"
+                };
+                format!("{} min {side} due", offset_minutes.abs())
+            }
+        };
+        fields.push(("reminder", when));
+    }
+    for (label, value) in fields {
+        println!("  {label:<9} {value}");
+    }
+
+    if !shown.notes.trim().is_empty() {
+        println!();
+        for line in shown.notes.trim_end().lines() {
+            println!("  {line}");
+        }
+    }
+
+    if !shown.subtasks.is_empty() {
+        println!();
+        let mut depths: HashMap<&str, usize> = HashMap::from([(task.id.as_str(), 0)]);
+        for subtask in &shown.subtasks {
+            let depth = subtask
+                .parent_id
+                .as_deref()
+                .and_then(|id| depths.get(id))
+                .map_or(1, |depth| depth + 1);
+            depths.insert(&subtask.id, depth);
+            let due = due_label(subtask.due_date, subtask.due_time, today)
+                .map(|due| format!("  ({due})"))
+                .unwrap_or_default();
+            println!(
+                "{}  {}{}{due}",
+                short_id(&subtask.id),
+                "  ".repeat(depth - 1),
+                checkbox(subtask.completed, &subtask.name)
+            );
+        }
+    }
+}
+
+fn checkbox(completed: bool, name: &str) -> String {
+    let mark = if completed { 'x' } else { ' ' };
+    format!("[{mark}] {name}")
+}
+
+pub(crate) fn due_label(
+    day: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    today: NaiveDate,
+) -> Option<String> {
+    let mut label = day_label(day?, today);
+    if let Some(time) = time {
+        label.push_str(&time.format(" %H:%M").to_string());
+    }
+    Some(label)
+}
+
+fn priority_label(priority: Priority) -> Option<&'static str> {
+    match priority {
+        Priority::None => None,
+        Priority::Low => Some("low"),
+        Priority::Medium => Some("medium"),
+        Priority::High => Some("high"),
+        Priority::Urgent => Some("urgent"),
+    }
+}
+
+/// The id cut to `SHORT_ID` characters, padded so names line up
+fn short_id(id: &str) -> String {
+    let short: String = id.chars().take(SHORT_ID).collect();
+    format!("{short:SHORT_ID$}")
+}
+
+fn plural(count: u64, noun: &str) -> String {
+    if count == 1 {
+        noun.to_string()
+    } else {
+        format!("{noun}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn api_commands_stay_apart_from_task_commands() {
+        Cli::command().debug_assert();
+        let parse = |args: &[&str]| Cli::try_parse_from(args).unwrap().command;
+        assert!(matches!(
+            parse(&["act", "api", "enable", "--port", "4000"]),
+            Some(Command::Api {
+                command: ApiCommand::Enable { port: Some(4000) }
+            })
+        ));
+        assert!(matches!(
+            parse(&["act", "--json", "undo"]),
+            Some(Command::Tasks(TaskCommand::Undo))
+        ));
+    }
+
+    #[test]
+    fn days_resolve_against_today() {
+        // A Saturday
+        let today = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        let day = |text: &str| parse_day(text).map(|day| day.resolve(today).to_string());
+        for (text, expected) in [
+            ("today", "2026-10-17"),
+            ("Tomorrow", "2026-10-18"),
+            ("YESTERDAY", "2026-10-16"),
+            ("2026-11-02", "2026-11-02"),
+            ("monday", "2026-10-19"),
+            ("Fri", "2026-10-23"),
+            // The next one, never today
+            ("sat", "2026-10-24"),
+            ("+0d", "2026-10-17"),
+            ("+3d", "2026-10-20"),
+            ("+2W", "2026-10-31"),
+        ] {
+            assert_eq!(day(text).as_deref(), Ok(expected), "{text}");
+        }
+        for text in [
+            "",
+            "+3",
+            "+d",
+            "+3x",
+            "+é",
+            "+-1d",
+            "+70000d",
+            "someday",
+            "2026-02-30",
+        ] {
+            assert!(day(text).is_err(), "{text}");
+        }
+
+        assert!(matches!(
+            parse_listing("mon..+1w"),
+            Ok(Listing::Between(Day::Next(Weekday::Mon), Day::Offset(7)))
+        ));
+        assert!(matches!(
+            parse_listing("Someday"),
+            Ok(Listing::Filter(DateFilter::Someday))
+        ));
+    }
+
+    #[test]
+    fn times_take_minutes_and_optional_seconds() {
+        assert_eq!(
+            parse_time("9:05"),
+            Ok(NaiveTime::from_hms_opt(9, 5, 0).unwrap())
+        );
+        assert_eq!(
+            parse_time("23:59:30"),
+            Ok(NaiveTime::from_hms_opt(23, 59, 30).unwrap())
+        );
+        for text in ["", "9", "24:00", "12:60", "noon", "9:05pm"] {
+            assert!(parse_time(text).is_err(), "{text}");
+        }
+    }
+}
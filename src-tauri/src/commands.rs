@@ -0,0 +1,445 @@
+use chrono::{Duration, NaiveDate, NaiveTime, Utc};
+use tauri::State;
+
+use crate::api::{self, ApiServer, ApiSettings};
+use crate::audit::{RescheduledTask, TaskHistory};
+use crate::backup::{Backup, BackupSettings};
+use crate::date_filter::{DateFilter, DateFilterEntry};
+use crate::export::{Document, ExportFilter, ImportOptions, ImportReport};
+use crate::history::Operation;
+use crate::ical;
+use crate::markdown;
+use crate::notes;
+use crate::profile::{ProfileInfo, Profiles};
+use crate::recurrence::RepeatFrom;
+use crate::reminder::{Reminder, ReminderTrigger};
+use crate::repository::TaskRepository;
+use crate::search::SearchHit;
+use crate::tag::{Tag, TagFilter};
+use crate::task::{Priority, Task, TaskSort};
+use crate::todotxt;
+use crate::trash::{TrashEntry, TrashSettings};
+use crate::Result;
+
+#[tauri::command]
+pub async fn list_profiles(profiles: State<'_, Profiles>) -> Result<Vec<ProfileInfo>> {
+    profiles.list().await
+}
+
+#[tauri::command]
+pub async fn active_profile(profiles: State<'_, Profiles>) -> Result<ProfileInfo> {
+    Ok(profiles.active().await)
+}
+
+/// Open another profile, creating it on first use. The frontend reloads
+/// everything afterwards
+#[tauri::command]
+pub async fn switch_profile(
+    repo: State<'_, TaskRepository>,
+    profiles: State<'_, Profiles>,
+    name: String,
+) -> Result<ProfileInfo> {
+    profiles.switch(&repo, &name).await
+}
+
+#[tauri::command]
+pub async fn list_backups(profiles: State<'_, Profiles>) -> Result<Vec<Backup>> {
+    profiles.backups().await
+}
+
+/// Replace the active profile's database with a backup, returning the
+/// backup taken of the state it replaced. The frontend reloads everything
+/// afterwards
+#[tauri::command]
+pub async fn restore_backup(
+    repo: State<'_, TaskRepository>,
+    profiles: State<'_, Profiles>,
+    id: String,
+) -> Result<Backup> {
+    profiles.restore(&repo, &id).await
+}
+
+#[tauri::command]
+pub async fn get_backup_settings(repo: State<'_, TaskRepository>) -> Result<BackupSettings> {
+    repo.backup_settings().await
+}
+
+#[tauri::command]
+pub async fn set_backup_settings(
+    repo: State<'_, TaskRepository>,
+    settings: BackupSettings,
+) -> Result<()> {
+    repo.set_backup_settings(&settings).await
+}
+
+#[tauri::command]
+pub async fn export_tasks(
+    repo: State<'_, TaskRepository>,
+    filter: Option<ExportFilter>,
+) -> Result<Document> {
+    repo.export_tasks(&filter.unwrap_or_default()).await
+}
+
+/// Import a document produced by `export_tasks`, taken as plain JSON so its
+/// version is checked before anything else
+#[tauri::command]
+pub async fn import_tasks(
+    repo: State<'_, TaskRepository>,
+    document: serde_json::Value,
+    options: Option<ImportOptions>,
+) -> Result<ImportReport> {
+    let document = Document::from_value(document)?;
+    repo.import_tasks(&document, options.unwrap_or_default())
+        .await
+}
+
+#[tauri::command]
+pub async fn export_todo_txt(
+    repo: State<'_, TaskRepository>,
+    filter: Option<ExportFilter>,
+) -> Result<String> {
+    todotxt::export(&repo, &filter.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn import_todo_txt(
+    repo: State<'_, TaskRepository>,
+    text: String,
+    options: Option<ImportOptions>,
+) -> Result<ImportReport> {
+    todotxt::import(&repo, &text, options.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn export_ical(
+    repo: State<'_, TaskRepository>,
+    filter: Option<ExportFilter>,
+) -> Result<String> {
+    ical::export(&repo, &filter.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn import_ical(
+    repo: State<'_, TaskRepository>,
+    text: String,
+    options: Option<ImportOptions>,
+) -> Result<ImportReport> {
+    ical::import(&repo, &text, options.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn export_markdown(
+    repo: State<'_, TaskRepository>,
+    filter: Option<ExportFilter>,
+) -> Result<String> {
+    markdown::export(&repo, &filter.unwrap_or_default()).await
+}
+
+/// Add the items of a pasted checklist as new tasks, top-level ones last
+/// under `parent_id`
+#[tauri::command]
+pub async fn import_markdown(
+    repo: State<'_, TaskRepository>,
+    text: String,
+    parent_id: Option<String>,
+    options: Option<ImportOptions>,
+) -> Result<ImportReport> {
+    markdown::import(
+        &repo,
+        &text,
+        parent_id.as_deref(),
+        options.unwrap_or_default(),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn load_tasks(
+    repo: State<'_, TaskRepository>,
+    filter: Option<DateFilter>,
+    tags: Option<TagFilter>,
+    sort: Option<TaskSort>,
+) -> Result<Vec<Task>> {
+    repo.load_tasks(
+        &filter.unwrap_or(DateFilter::All),
+        tags.as_ref(),
+        sort.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Most hits `search_tasks` returns
+const SEARCH_LIMIT: u32 = 50;
+
+#[tauri::command]
+pub async fn search_tasks(
+    repo: State<'_, TaskRepository>,
+    query: String,
+    filter: Option<DateFilter>,
+) -> Result<Vec<SearchHit>> {
+    repo.search_tasks(&query, &filter.unwrap_or(DateFilter::All), SEARCH_LIMIT)
+        .await
+}
+
+#[tauri::command]
+pub async fn date_filters(repo: State<'_, TaskRepository>) -> Result<Vec<DateFilterEntry>> {
+    repo.date_filters().await
+}
+
+#[tauri::command]
+pub async fn get_timezone(repo: State<'_, TaskRepository>) -> Result<Option<String>> {
+    Ok(repo.timezone().await?.name().map(str::to_string))
+}
+
+#[tauri::command]
+pub async fn set_timezone(
+    repo: State<'_, TaskRepository>,
+    timezone: Option<String>,
+) -> Result<Option<String>> {
+    let zone = repo.set_timezone(timezone.as_deref()).await?;
+    Ok(zone.name().map(str::to_string))
+}
+
+#[tauri::command]
+pub async fn create_task(
+    repo: State<'_, TaskRepository>,
+    name: String,
+    parent_id: Option<String>,
+    due_date: Option<NaiveDate>,
+    due_time: Option<NaiveTime>,
+) -> Result<Task> {
+    repo.create_task(&name, parent_id.as_deref(), due_date, due_time)
+        .await
+}
+
+#[tauri::command]
+pub async fn set_due_date(
+    repo: State<'_, TaskRepository>,
+    ids: Vec<String>,
+    due_date: Option<NaiveDate>,
+    due_time: Option<NaiveTime>,
+) -> Result<()> {
+    repo.set_due_date(&ids, due_date, due_time).await
+}
+
+#[tauri::command]
+pub async fn rename_task(repo: State<'_, TaskRepository>, id: String, name: String) -> Result<()> {
+    repo.rename_task(&id, &name).await
+}
+
+#[tauri::command]
+pub async fn set_priority(
+    repo: State<'_, TaskRepository>,
+    ids: Vec<String>,
+    priority: Priority,
+) -> Result<()> {
+    repo.set_priority(&ids, priority).await
+}
+
+#[tauri::command]
+pub async fn set_recurrence(
+    repo: State<'_, TaskRepository>,
+    id: String,
+    rule: Option<String>,
+    repeat_from: Option<RepeatFrom>,
+) -> Result<()> {
+    repo.set_recurrence(&id, rule.as_deref(), repeat_from.unwrap_or_default())
+        .await
+}
+
+#[tauri::command]
+pub async fn list_reminders(
+    repo: State<'_, TaskRepository>,
+    task_id: String,
+) -> Result<Vec<Reminder>> {
+    repo.reminders(&task_id).await
+}
+
+#[tauri::command]
+pub async fn add_reminder(
+    repo: State<'_, TaskRepository>,
+    task_id: String,
+    trigger: ReminderTrigger,
+) -> Result<Reminder> {
+    repo.add_reminder(&task_id, trigger).await
+}
+
+#[tauri::command]
+pub async fn delete_reminder(repo: State<'_, TaskRepository>, id: String) -> Result<()> {
+    repo.delete_reminder(&id).await
+}
+
+#[tauri::command]
+pub async fn get_notes(repo: State<'_, TaskRepository>, id: String) -> Result<String> {
+    repo.notes(&id).await
+}
+
+#[tauri::command]
+pub async fn set_notes(repo: State<'_, TaskRepository>, id: String, notes: String) -> Result<()> {
+    repo.set_notes(&id, &notes).await
+}
+
+/// Notes as sanitized HTML, links meant to be opened with the opener plugin
+#[tauri::command]
+pub async fn render_notes(repo: State<'_, TaskRepository>, id: String) -> Result<String> {
+    Ok(notes::render_html(&repo.notes(&id).await?))
+}
+
+#[tauri::command]
+pub async fn toggle_task(repo: State<'_, TaskRepository>, id: String) -> Result<Vec<Task>> {
+    repo.toggle_task(&id).await
+}
+
+#[tauri::command]
+pub async fn delete_subtree(repo: State<'_, TaskRepository>, ids: Vec<String>) -> Result<u64> {
+    repo.delete_subtree(&ids).await
+}
+
+#[tauri::command]
+pub async fn list_trash(repo: State<'_, TaskRepository>) -> Result<Vec<TrashEntry>> {
+    repo.trash().await
+}
+
+#[tauri::command]
+pub async fn restore_from_trash(repo: State<'_, TaskRepository>, id: String) -> Result<Task> {
+    repo.restore_from_trash(&id).await
+}
+
+/// Permanently delete trash entries deleted more than `older_than_days`
+/// ago, or the whole trash without it
+#[tauri::command]
+pub async fn purge_trash(
+    repo: State<'_, TaskRepository>,
+    older_than_days: Option<u32>,
+) -> Result<u64> {
+    let cutoff = older_than_days.map(|days| Utc::now() - Duration::days(days.into()));
+    repo.purge_trash(cutoff).await
+}
+
+#[tauri::command]
+pub async fn get_trash_settings(repo: State<'_, TaskRepository>) -> Result<TrashSettings> {
+    repo.trash_settings().await
+}
+
+#[tauri::command]
+pub async fn set_trash_settings(
+    repo: State<'_, TaskRepository>,
+    settings: TrashSettings,
+) -> Result<()> {
+    repo.set_trash_settings(&settings).await
+}
+
+#[tauri::command]
+pub async fn reorder_tasks(
+    repo: State<'_, TaskRepository>,
+    ids: Vec<String>,
+    parent_id: Option<String>,
+) -> Result<()> {
+    repo.reorder_tasks(&ids, parent_id.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn move_tasks(
+    repo: State<'_, TaskRepository>,
+    ids: Vec<String>,
+    new_parent_id: Option<String>,
+    index: Option<usize>,
+) -> Result<()> {
+    repo.move_tasks(&ids, new_parent_id.as_deref(), index).await
+}
+
+#[tauri::command]
+pub async fn undo(repo: State<'_, TaskRepository>) -> Result<Option<Operation>> {
+    repo.undo().await
+}
+
+#[tauri::command]
+pub async fn redo(repo: State<'_, TaskRepository>) -> Result<Option<Operation>> {
+    repo.redo().await
+}
+
+#[tauri::command]
+pub async fn task_history(repo: State<'_, TaskRepository>, id: String) -> Result<TaskHistory> {
+    repo.task_history(&id).await
+}
+
+/// Tasks whose due date slipped at least `min_times` times, once by default
+#[tauri::command]
+pub async fn rescheduled_tasks(
+    repo: State<'_, TaskRepository>,
+    min_times: Option<u32>,
+) -> Result<Vec<RescheduledTask>> {
+    repo.rescheduled_tasks(min_times.unwrap_or(1)).await
+}
+
+#[tauri::command]
+pub async fn get_api_settings(api: State<'_, ApiServer>) -> Result<ApiSettings> {
+    Ok(ApiSettings::load(api.data_dir()))
+}
+
+/// Save the settings and restart the API server to apply them
+#[tauri::command]
+pub async fn set_api_settings(api: State<'_, ApiServer>, settings: ApiSettings) -> Result<()> {
+    settings.save(api.data_dir())?;
+    api.restart().await
+}
+
+#[tauri::command]
+pub async fn api_token(api: State<'_, ApiServer>) -> Result<String> {
+    api::token(api.data_dir())
+}
+
+/// Replace the token; clients holding the old one are refused from now on
+#[tauri::command]
+pub async fn rotate_api_token(api: State<'_, ApiServer>) -> Result<String> {
+    let token = api::rotate_token(api.data_dir())?;
+    api.restart().await?;
+    Ok(token)
+}
+
+#[tauri::command]
+pub async fn list_tags(repo: State<'_, TaskRepository>) -> Result<Vec<Tag>> {
+    repo.list_tags().await
+}
+
+#[tauri::command]
+pub async fn create_tag(repo: State<'_, TaskRepository>, name: String) -> Result<Tag> {
+    repo.create_tag(&name).await
+}
+
+#[tauri::command]
+pub async fn rename_tag(repo: State<'_, TaskRepository>, id: String, name: String) -> Result<()> {
+    repo.rename_tag(&id, &name).await
+}
+
+#[tauri::command]
+pub async fn merge_tags(
+    repo: State<'_, TaskRepository>,
+    source_ids: Vec<String>,
+    target_id: String,
+) -> Result<()> {
+    repo.merge_tags(&source_ids, &target_id).await
+}
+
+#[tauri::command]
+pub async fn delete_tag(repo: State<'_, TaskRepository>, id: String) -> Result<()> {
+    repo.delete_tag(&id).await
+}
+
+#[tauri::command]
+pub async fn attach_tags(
+    repo: State<'_, TaskRepository>,
+    task_ids: Vec<String>,
+    tag_ids: Vec<String>,
+) -> Result<()> {
+    repo.attach_tags(&task_ids, &tag_ids).await
+}
+
+#[tauri::command]
+pub async fn detach_tags(
+    repo: State<'_, TaskRepository>,
+    task_ids: Vec<String>,
+    tag_ids: Vec<String>,
+) -> Result<()> {
+    repo.detach_tags(&task_ids, &tag_ids).await
+}
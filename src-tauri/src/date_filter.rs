@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Timezone whose calendar days the date filters follow
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Zone {
+    /// Whatever timezone the system is set to
+    #[default]
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    /// Parse an IANA name such as `Europe/Zurich`
+    pub fn parse(name: &str) -> crate::Result<Self> {
+        name.parse::<Tz>()
+            .map(Zone::Named)
+            .map_err(|_| crate::Error::InvalidTimezone(name.to_string()))
+    }
+
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Zone::Local => None,
+            Zone::Named(tz) => Some(tz.name()),
+        }
+    }
+
+    /// Instant the wall-clock time `local` denotes in this timezone. Times
+    /// skipped by a DST jump resolve to the first valid minute after them,
+    /// repeated ones to their earlier instant
+    pub fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        fn resolve<T: TimeZone>(tz: &T, mut local: NaiveDateTime) -> DateTime<Utc> {
+            loop {
+                match tz.from_local_datetime(&local) {
+                    LocalResult::Single(instant) | LocalResult::Ambiguous(instant, _) => {
+                        return instant.with_timezone(&Utc)
+                    }
+                    LocalResult::None => local += Duration::minutes(1),
+                }
+            }
+        }
+
+        match self {
+            Zone::Local => resolve(&chrono::Local, local),
+            Zone::Named(tz) => resolve(tz, local),
+        }
+    }
+
+    /// Wall-clock time `instant` corresponds to in this timezone
+    pub fn local_time(&self, instant: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Zone::Local => instant.with_timezone(&chrono::Local).naive_local(),
+            Zone::Named(tz) => instant.with_timezone(tz).naive_local(),
+        }
+    }
+}
+
+/// Which tasks `load_tasks` returns, matching the frontend `DateFilter`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "lowercase",
+    rename_all_fields = "camelCase"
+)]
+pub enum DateFilter {
+    All,
+    Today,
+    Tomorrow,
+    Yesterday,
+    Date {
+        day: NaiveDate,
+    },
+    Range {
+        start_day: NaiveDate,
+        end_day: NaiveDate,
+    },
+    /// Tasks without a due date
+    Someday,
+}
+
+/// Inclusive due days a filter selects; without bounds it selects the tasks
+/// that have no due date
+#[derive(Debug, Clone, Copy)]
+pub struct DueRange {
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    /// Also match incomplete tasks that were due before today
+    pub include_overdue: bool,
+}
+
+impl DueRange {
+    fn days(start: NaiveDate, end: NaiveDate) -> Self {
+        DueRange {
+            start: Some(start),
+            end: Some(end),
+            include_overdue: false,
+        }
+    }
+}
+
+impl DateFilter {
+    /// Resolve the filter to due day bounds, `None` meaning every task
+    pub fn due_range(&self, today: NaiveDate) -> Option<DueRange> {
+        let day = |offset: i64| {
+            let day = today + Duration::days(offset);
+            DueRange::days(day, day)
+        };
+
+        match self {
+            DateFilter::All => None,
+            DateFilter::Today => Some(DueRange {
+                include_overdue: true,
+                ..day(0)
+            }),
+            DateFilter::Tomorrow => Some(DueRange {
+                include_overdue: true,
+                ..day(1)
+            }),
+            DateFilter::Yesterday => Some(day(-1)),
+            DateFilter::Date { day } => Some(DueRange::days(*day, *day)),
+            DateFilter::Range { start_day, end_day } => Some(DueRange::days(*start_day, *end_day)),
+            DateFilter::Someday => Some(DueRange {
+                start: None,
+                end: None,
+                include_overdue: false,
+            }),
+        }
+    }
+}
+
+/// Task totals for one due day
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DayCount {
+    pub total: i64,
+    pub completed: i64,
+}
+
+/// A sidebar entry: the filter plus its label and task counts
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DateFilterEntry {
+    #[serde(flatten)]
+    pub filter: DateFilter,
+    /// The filter's day, used as the due date for new tasks
+    pub due_date: Option<NaiveDate>,
+    pub label: String,
+    pub total_task_count: i64,
+    pub completed_task_count: i64,
+}
+
+/// Build the sidebar: All, Today and Tomorrow always, Yesterday when it has
+/// tasks, then every other day with tasks, newest first, and finally Someday
+/// when some tasks have no due date
+pub fn sidebar_entries(
+    today: NaiveDate,
+    counts: &BTreeMap<NaiveDate, DayCount>,
+    someday: DayCount,
+) -> Vec<DateFilterEntry> {
+    let tomorrow = today + Duration::days(1);
+    let yesterday = today - Duration::days(1);
+
+    let entry = |filter: DateFilter, day: Option<NaiveDate>| {
+        let (label, count) = match (&filter, day) {
+            (_, Some(day)) => (
+                day_label(day, today),
+                counts.get(&day).copied().unwrap_or_default(),
+            ),
+            (DateFilter::Someday, None) => ("Someday".to_string(), someday),
+            _ => (
+                "All".to_string(),
+                counts.values().fold(someday, |acc, c| DayCount {
+                    total: acc.total + c.total,
+                    completed: acc.completed + c.completed,
+                }),
+            ),
+        };
+        DateFilterEntry {
+            filter,
+            due_date: day,
+            label,
+            total_task_count: count.total,
+            completed_task_count: count.completed,
+        }
+    };
+
+    let mut entries = vec![
+        entry(DateFilter::All, None),
+        entry(DateFilter::Today, Some(today)),
+        entry(DateFilter::Tomorrow, Some(tomorrow)),
+    ];
+    if counts.contains_key(&yesterday) {
+        entries.push(entry(DateFilter::Yesterday, Some(yesterday)));
+    }
+    for day in counts.keys().rev() {
+        if ![today, tomorrow, yesterday].contains(day) {
+            entries.push(entry(DateFilter::Date { day: *day }, Some(*day)));
+        }
+    }
+    if someday.total > 0 {
+        entries.push(entry(DateFilter::Someday, None));
+    }
+    entries
+}
+
+/// "Today", "Tomorrow", "Yesterday", otherwise e.g. "Dec 15", with the year
+/// appended outside the current one
+pub fn day_label(day: NaiveDate, today: NaiveDate) -> String {
+    match (day - today).num_days() {
+        0 => "Today".to_string(),
+        1 => "Tomorrow".to_string(),
+        -1 => "Yesterday".to_string(),
+        _ if day.year() == today.year() => day.format("%b %-d").to_string(),
+        _ => day.format("%b %-d, %Y").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn days_follow_the_zone() {
+        let instant: DateTime<Utc> = "2026-10-17T23:30:00Z".parse().unwrap();
+        let zurich = Zone::parse("Europe/Zurich").unwrap();
+        let new_york = Zone::parse("America/New_York").unwrap();
+        assert_eq!(zurich.local_time(instant).date(), day("2026-10-18"));
+        assert_eq!(new_york.local_time(instant).date(), day("2026-10-17"));
+
+        let midnight = day("2026-10-18").and_time(chrono::NaiveTime::MIN);
+        assert_eq!(
+            zurich.to_utc(midnight),
+            "2026-10-17T22:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[test]
+    fn dst_gaps_and_repeats_resolve() {
+        let zurich = Zone::parse("Europe/Zurich").unwrap();
+        let at = |s: &str| s.parse::<NaiveDateTime>().unwrap();
+        let utc = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        // 02:30 is skipped in spring, so it becomes 03:00 CEST
+        assert_eq!(
+            zurich.to_utc(at("2026-03-29T02:30:00")),
+            utc("2026-03-29T01:00:00Z")
+        );
+        // and happens twice in autumn, the earlier one winning
+        assert_eq!(
+            zurich.to_utc(at("2026-10-25T02:30:00")),
+            utc("2026-10-25T00:30:00Z")
+        );
+        assert!(Zone::parse("Nowhere/Special").is_err());
+    }
+
+    #[test]
+    fn filters_resolve_to_due_ranges() {
+        let today = day("2026-10-17");
+        assert!(DateFilter::All.due_range(today).is_none());
+
+        let range = DateFilter::Today.due_range(today).unwrap();
+        assert_eq!((range.start, range.end), (Some(today), Some(today)));
+        assert!(range.include_overdue);
+
+        let range = DateFilter::Yesterday.due_range(today).unwrap();
+        assert_eq!(range.start, Some(day("2026-10-16")));
+        assert!(!range.include_overdue);
+
+        let range = DateFilter::Range {
+            start_day: day("2026-10-01"),
+            end_day: day("2026-10-31"),
+        }
+        .due_range(today)
+        .unwrap();
+        assert_eq!(range.end, Some(day("2026-10-31")));
+
+        let range = DateFilter::Someday.due_range(today).unwrap();
+        assert_eq!((range.start, range.end), (None, None));
+    }
+
+    #[test]
+    fn sidebar_lists_days_newest_first() {
+        let today = day("2026-10-17");
+        let count = |total, completed| DayCount { total, completed };
+        let counts = BTreeMap::from([
+            (day("2026-10-16"), count(1, 1)),
+            (day("2026-10-20"), count(2, 0)),
+            (day("2025-12-31"), count(1, 0)),
+        ]);
+
+        let entries = sidebar_entries(today, &counts, DayCount::default());
+        let labels: Vec<&str> = entries.iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "All",
+                "Today",
+                "Tomorrow",
+                "Yesterday",
+                "Oct 20",
+                "Dec 31, 2025"
+            ]
+        );
+        assert_eq!(entries[0].total_task_count, 4);
+        assert_eq!(entries[0].due_date, None);
+        assert_eq!(entries[1].due_date, Some(today));
+
+        let entries = sidebar_entries(today, &BTreeMap::new(), count(2, 1));
+        let last = entries.last().unwrap();
+        assert_eq!(
+            (last.filter.clone(), last.total_task_count),
+            (DateFilter::Someday, 2)
+        );
+    }
+}
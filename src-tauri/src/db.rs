@@ -0,0 +1,308 @@
+use std::borrow::Cow;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::time::Duration;
+
+use sqlx::error::BoxDynError;
+use sqlx::migrate::{Migration, MigrationSource, MigrationType, Migrator};
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePool, SqliteSynchronous,
+};
+
+use crate::backup::{BackupReason, BackupStore};
+
+/// A schema migration, applied in version order by `migrate`
+pub struct MigrationDef {
+    pub version: i64,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+pub const MIGRATIONS: &[MigrationDef] = &[
+    MigrationDef {
+        version: 1,
+        description: "create_tasks_table",
+        sql: include_str!("../migrations/001_initial.sql"),
+    },
+    MigrationDef {
+        version: 2,
+        description: "add_order_column",
+        sql: include_str!("../migrations/002_add_order.sql"),
+    },
+    MigrationDef {
+        version: 3,
+        description: "rename_date_created_add_due_date",
+        sql: include_str!("../migrations/003_rename_date_created_add_due_date.sql"),
+    },
+    MigrationDef {
+        version: 4,
+        description: "add_settings",
+        sql: include_str!("../migrations/004_add_settings.sql"),
+    },
+    MigrationDef {
+        version: 5,
+        description: "optional_due_date_and_time",
+        sql: include_str!("../migrations/005_optional_due_date_and_time.sql"),
+    },
+    MigrationDef {
+        version: 6,
+        description: "add_notes",
+        sql: include_str!("../migrations/006_add_notes.sql"),
+    },
+    MigrationDef {
+        version: 7,
+        description: "add_tags",
+        sql: include_str!("../migrations/007_add_tags.sql"),
+    },
+    MigrationDef {
+        version: 8,
+        description: "add_priority",
+        sql: include_str!("../migrations/008_add_priority.sql"),
+    },
+    MigrationDef {
+        version: 9,
+        description: "add_recurrence",
+        sql: include_str!("../migrations/009_add_recurrence.sql"),
+    },
+    MigrationDef {
+        version: 10,
+        description: "add_reminders",
+        sql: include_str!("../migrations/010_add_reminders.sql"),
+    },
+    MigrationDef {
+        version: 11,
+        description: "add_search",
+        sql: include_str!("../migrations/011_add_search.sql"),
+    },
+    MigrationDef {
+        version: 12,
+        description: "add_operations",
+        sql: include_str!("../migrations/012_add_operations.sql"),
+    },
+    MigrationDef {
+        version: 13,
+        description: "add_trash",
+        sql: include_str!("../migrations/013_add_trash.sql"),
+    },
+    MigrationDef {
+        version: 14,
+        description: "add_task_events",
+        sql: include_str!("../migrations/014_add_task_events.sql"),
+    },
+    MigrationDef {
+        version: 15,
+        description: "add_event_steps",
+        sql: include_str!("../migrations/015_add_event_steps.sql"),
+    },
+    MigrationDef {
+        version: 16,
+        description: "add_next_occurrence",
+        sql: include_str!("../migrations/016_add_next_occurrence.sql"),
+    },
+];
+
+#[derive(Debug)]
+struct MigrationList;
+
+impl MigrationSource<'static> for MigrationList {
+    fn resolve(
+        self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Migration>, BoxDynError>> + Send + 'static>> {
+        Box::pin(async move {
+            // Mirror how tauri-plugin-sql registered migrations, which set up
+            // older databases, so the checksums in `_sqlx_migrations` match
+            Ok(MIGRATIONS
+                .iter()
+                .map(|m| {
+                    Migration::new(
+                        m.version,
+                        Cow::Borrowed(m.description),
+                        MigrationType::ReversibleUp,
+                        Cow::Borrowed(m.sql),
+                        false,
+                    )
+                })
+                .collect())
+        })
+    }
+}
+
+/// How long a connection waits on a locked database before giving up
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Options applied to every connection the pool opens
+fn connect_options(db_file: &Path) -> SqliteConnectOptions {
+    SqliteConnectOptions::new()
+        .filename(db_file)
+        .create_if_missing(true)
+        .foreign_keys(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(BUSY_TIMEOUT)
+}
+
+/// Confirm the connection actually runs with the settings we asked for,
+/// e.g. WAL is silently refused on some network filesystems
+async fn verify_pragmas(conn: &mut SqliteConnection) -> crate::Result<()> {
+    fn check(pragma: &'static str, expected: String, actual: String) -> crate::Result<()> {
+        if expected.eq_ignore_ascii_case(&actual) {
+            Ok(())
+        } else {
+            Err(crate::Error::Pragma {
+                pragma,
+                expected,
+                actual,
+            })
+        }
+    }
+
+    let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys")
+        .fetch_one(&mut *conn)
+        .await?;
+    check("foreign_keys", "1".into(), foreign_keys.to_string())?;
+
+    let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+        .fetch_one(&mut *conn)
+        .await?;
+    check("journal_mode", "wal".into(), journal_mode)?;
+
+    // NORMAL
+    let synchronous: i64 = sqlx::query_scalar("PRAGMA synchronous")
+        .fetch_one(&mut *conn)
+        .await?;
+    check("synchronous", "1".into(), synchronous.to_string())?;
+
+    let busy_timeout: i64 = sqlx::query_scalar("PRAGMA busy_timeout")
+        .fetch_one(&mut *conn)
+        .await?;
+    check(
+        "busy_timeout",
+        BUSY_TIMEOUT.as_millis().to_string(),
+        busy_timeout.to_string(),
+    )?;
+
+    Ok(())
+}
+
+/// Open the database file, creating it if needed, and apply pending migrations,
+/// backing the database up to `backups` first when there are any.
+/// Fails if the connection settings cannot be applied.
+pub async fn open(db_file: &Path, backups: &BackupStore) -> crate::Result<SqlitePool> {
+    let pool = SqlitePool::connect_with(connect_options(db_file)).await?;
+    verify_pragmas(&mut *pool.acquire().await?).await?;
+
+    if has_pending_migrations(&pool).await? {
+        backups.create(&pool, BackupReason::Migration).await?;
+    }
+    migrate(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Bring the schema up to date
+pub async fn migrate(pool: &SqlitePool) -> crate::Result<()> {
+    let migrator = Migrator::new(MigrationList).await?;
+    migrator.run(pool).await?;
+    Ok(())
+}
+
+/// A migrated database living as long as the pool, for tests
+#[cfg(test)]
+pub(crate) async fn memory() -> SqlitePool {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        // Each connection would open a database of its own
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .acquire_timeout(Duration::from_secs(5))
+        .connect_with(
+            SqliteConnectOptions::new()
+                .in_memory(true)
+                .foreign_keys(true),
+        )
+        .await
+        .expect("in-memory database");
+    migrate(&pool).await.expect("migrations");
+    pool
+}
+
+/// An empty directory of its own under the system temp dir, for tests
+#[cfg(test)]
+pub(crate) fn scratch_dir() -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("act-test-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).expect("scratch directory");
+    dir
+}
+
+/// Whether an existing database is behind the latest migration. A new one
+/// has nothing worth backing up
+async fn has_pending_migrations(pool: &SqlitePool) -> crate::Result<bool> {
+    let migrated: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+    )
+    .fetch_one(pool)
+    .await?;
+    if !migrated {
+        return Ok(false);
+    }
+
+    let applied: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations")
+        .fetch_one(pool)
+        .await?;
+    let latest = MIGRATIONS.last().map(|m| m.version);
+    Ok(applied < latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::ConnectOptions;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn every_connection_gets_the_pragmas() {
+        let dir = scratch_dir();
+        let pool = open(&dir.join("act.db"), &BackupStore::new(dir.join("backups")))
+            .await
+            .unwrap();
+
+        // Two at once, so the second is a fresh connection
+        let mut first = pool.acquire().await.unwrap();
+        let mut second = pool.acquire().await.unwrap();
+        verify_pragmas(&mut first).await.unwrap();
+        verify_pragmas(&mut second).await.unwrap();
+
+        let orphan = sqlx::query(
+            "INSERT INTO tasks (id, name, parent_id, created_at) VALUES ('a', 'A', 'missing', '')",
+        )
+        .execute(&mut *second)
+        .await;
+        assert!(orphan
+            .unwrap_err()
+            .to_string()
+            .contains("FOREIGN KEY constraint failed"));
+
+        drop((first, second));
+        pool.close().await;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn refuses_connections_without_the_pragmas() {
+        let mut conn = SqliteConnectOptions::new()
+            .in_memory(true)
+            .foreign_keys(false)
+            .connect()
+            .await
+            .unwrap();
+        let error = verify_pragmas(&mut conn).await.unwrap_err();
+        assert!(matches!(
+            error,
+            crate::Error::Pragma {
+                pragma: "foreign_keys",
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+use serde::ser::SerializeStruct;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+    #[error("could not set {pragma} to {expected} (got {actual})")]
+    Pragma {
+        pragma: &'static str,
+        expected: String,
+        actual: String,
+    },
+    #[error("unknown timezone: {0}")]
+    InvalidTimezone(String),
+    #[error("task not found: {0}")]
+    TaskNotFound(String),
+    #[error("task id {0} is ambiguous, type more of it")]
+    AmbiguousId(String),
+    #[error("cannot move task {task_id} into its own subtree at {parent_id}")]
+    Cycle { task_id: String, parent_id: String },
+    #[error("a due time needs a due date")]
+    DueTimeWithoutDate,
+    #[error("tag not found: {0}")]
+    TagNotFound(String),
+    #[error("a tag named {0} already exists")]
+    DuplicateTag(String),
+    #[error("tag names cannot be empty")]
+    EmptyTagName,
+    #[error("invalid recurrence rule {0}")]
+    InvalidRecurrence(String),
+    #[error("reminder not found: {0}")]
+    ReminderNotFound(String),
+    #[error("reminder offset of {0} minutes is more than a year from the due time")]
+    InvalidReminderOffset(i64),
+    #[error("backup failed: {0}")]
+    Backup(String),
+    #[error("backup not found: {0}")]
+    BackupNotFound(String),
+    #[error("backup {id} is damaged: {reason}")]
+    CorruptBackup { id: String, reason: String },
+    #[error("invalid export: {0}")]
+    InvalidExport(String),
+    #[error("export version {0} is not supported, update Act to import it")]
+    UnsupportedExportVersion(u32),
+    #[error("cannot {step} {operation}: {reason}")]
+    HistoryConflict {
+        step: &'static str,
+        operation: String,
+        reason: String,
+    },
+    #[error("no data directory: set ACT_HOME or pass --data-dir")]
+    NoDataDir,
+    #[error("invalid profile name {0:?}: use letters, digits, - and _")]
+    InvalidProfile(String),
+}
+
+impl Error {
+    /// Stable identifier the frontend can match on without parsing messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Database(_) => "database",
+            Error::Io(_) => "io",
+            Error::Migrate(_) => "migrate",
+            Error::Pragma { .. } => "pragma",
+            Error::InvalidTimezone(_) => "invalidTimezone",
+            Error::TaskNotFound(_) => "taskNotFound",
+            Error::AmbiguousId(_) => "ambiguousId",
+            Error::Cycle { .. } => "cycle",
+            Error::DueTimeWithoutDate => "dueTimeWithoutDate",
+            Error::TagNotFound(_) => "tagNotFound",
+            Error::DuplicateTag(_) => "duplicateTag",
+            Error::EmptyTagName => "emptyTagName",
+            Error::InvalidRecurrence(_) => "invalidRecurrence",
+            Error::ReminderNotFound(_) => "reminderNotFound",
+            Error::InvalidReminderOffset(_) => "invalidReminderOffset",
+            Error::Backup(_) => "backup",
+            Error::BackupNotFound(_) => "backupNotFound",
+            Error::CorruptBackup { .. } => "corruptBackup",
+            Error::InvalidExport(_) => "invalidExport",
+            Error::UnsupportedExportVersion(_) => "unsupportedExportVersion",
+            Error::HistoryConflict { .. } => "historyConflict",
+            Error::NoDataDir => "noDataDir",
+            Error::InvalidProfile(_) => "invalidProfile",
+        }
+    }
+}
+
+// Commands return errors to the webview as `{ kind, message }`
+impl serde::Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("Error", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
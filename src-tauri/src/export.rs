@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::recurrence::RepeatFrom;
+use crate::reminder::ReminderTrigger;
+use crate::task::Priority;
+use crate::{Error, Result};
+
+/// Identifies Act documents among other JSON files
+pub const FORMAT: &str = "act";
+
+/// Bumped whenever the document layout changes incompatibly
+pub const VERSION: u32 = 1;
+
+/// A JSON snapshot of (part of) the task tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Document {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Parents before their children, siblings in `order`
+    pub tasks: Vec<ExportedTask>,
+}
+
+impl Document {
+    pub fn new(tasks: Vec<ExportedTask>) -> Self {
+        Document {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            exported_at: Utc::now(),
+            tasks,
+        }
+    }
+
+    /// Parse a document, checking its format and version before the rest
+    /// so newer documents fail with a clear error
+    pub fn from_value(value: serde_json::Value) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct Header {
+            format: String,
+            version: u32,
+        }
+
+        let invalid = |e: serde_json::Error| Error::InvalidExport(e.to_string());
+        let header = Header::deserialize(&value).map_err(invalid)?;
+        if header.format != FORMAT {
+            return Err(Error::InvalidExport(format!(
+                "unknown format {:?}",
+                header.format
+            )));
+        }
+        if header.version == 0 || header.version > VERSION {
+            return Err(Error::UnsupportedExportVersion(header.version));
+        }
+        serde_json::from_value(value).map_err(invalid)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value = serde_json::from_str(json).map_err(|e| Error::InvalidExport(e.to_string()))?;
+        Self::from_value(value)
+    }
+}
+
+/// A task with everything attached to it. Fields added after version 1
+/// must have defaults so older documents keep importing. Formats that cannot
+/// carry notes or reminders leave them `None`, and importing keeps what the
+/// task already has
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedTask {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    pub completed: bool,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub due_date: Option<NaiveDate>,
+    pub due_time: Option<NaiveTime>,
+    #[sqlx(rename = "task_order")]
+    pub order: i64,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    #[serde(default)]
+    pub repeat_from: RepeatFrom,
+    #[serde(default = "first_occurrence")]
+    pub occurrence: i64,
+    /// Tag names, matched case-insensitively on import
+    #[serde(default)]
+    #[sqlx(skip)]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    pub reminders: Option<Vec<ReminderTrigger>>,
+}
+
+fn first_occurrence() -> i64 {
+    1
+}
+
+/// Which tasks to export: those due within `from..=to` (either end open)
+/// together with their ancestors, limited to the subtree of `root_id`. All
+/// tasks when nothing is set
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub root_id: Option<String>,
+}
+
+/// How imported ids relate to the ones already in the database
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdMode {
+    /// Keep ids, updating tasks that already exist
+    #[default]
+    Merge,
+    /// Give every imported task a new id, so the import only adds tasks
+    Remap,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOptions {
+    #[serde(default)]
+    pub ids: IdMode,
+    /// Report what would change without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub created: usize,
+    pub updated: usize,
+    pub tags_created: usize,
+    /// Things imported differently than the document asked for
+    pub warnings: Vec<String>,
+    pub dry_run: bool,
+}
+
+/// Sort tasks into outline order: every task followed by its subtree,
+/// siblings by `order`, tasks whose parent is missing counting as roots.
+/// Tasks caught in a parent cycle cannot be placed and come back second
+pub fn outline(tasks: Vec<ExportedTask>) -> (Vec<ExportedTask>, Vec<ExportedTask>) {
+    let index: HashMap<&str, usize> = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, task)| (task.id.as_str(), i))
+        .collect();
+
+    let mut roots = Vec::new();
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, task) in tasks.iter().enumerate() {
+        match task.parent_id.as_deref().and_then(|id| index.get(id)) {
+            Some(&parent) => children.entry(parent).or_default().push(i),
+            None => roots.push(i),
+        }
+    }
+    let by_order = |i: &usize| (tasks[*i].order, tasks[*i].created_at);
+    roots.sort_by_key(by_order);
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(by_order);
+    }
+
+    let mut order = Vec::with_capacity(tasks.len());
+    let mut stack: Vec<usize> = roots.into_iter().rev().collect();
+    while let Some(i) = stack.pop() {
+        order.push(i);
+        if let Some(siblings) = children.get(&i) {
+            stack.extend(siblings.iter().rev());
+        }
+    }
+
+    let mut slots: Vec<Option<ExportedTask>> = tasks.into_iter().map(Some).collect();
+    let placed = order.iter().filter_map(|i| slots[*i].take()).collect();
+    let cyclic = slots.into_iter().flatten().collect();
+    (placed, cyclic)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn checks_format_and_version_first() {
+        let document = |format: &str, version: u32| {
+            json!({
+                "format": format,
+                "version": version,
+                "exportedAt": "2026-10-17T08:00:00Z",
+                "tasks": [],
+            })
+        };
+        assert!(Document::from_value(document(FORMAT, VERSION)).is_ok());
+        for version in [0, VERSION + 1] {
+            assert!(matches!(
+                Document::from_value(document(FORMAT, version)),
+                Err(Error::UnsupportedExportVersion(v)) if v == version
+            ));
+        }
+        assert!(matches!(
+            Document::from_value(document("todoist", VERSION)),
+            Err(Error::InvalidExport(_))
+        ));
+        // A newer version is reported even when the rest would not parse
+        let mut newer = document(FORMAT, 2);
+        newer["tasks"] = json!("later");
+        assert!(matches!(
+            Document::from_value(newer),
+            Err(Error::UnsupportedExportVersion(2))
+        ));
+        assert!(matches!(
+            Document::from_json("{\"format\": \"act\""),
+            Err(Error::InvalidExport(_))
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Operations kept for undo, older ones are dropped
+pub const HISTORY_LIMIT: i64 = 500;
+
+/// Which repository method an operation went through
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum OperationKind {
+    Create,
+    Rename,
+    Toggle,
+    /// Moved to the trash
+    Delete,
+    /// Brought back from the trash
+    Restore,
+    Move,
+    Reorder,
+    /// Due date or time changed
+    Reschedule,
+    /// Several of name, due date and completion changed at once
+    Edit,
+}
+
+/// A recorded change to the task tree, as undo and redo report it
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    pub id: i64,
+    pub kind: OperationKind,
+    /// Name of the task acted on, the first one when there were several
+    pub label: String,
+    /// Number of tasks acted on, not counting subtasks and parents that
+    /// changed along with them
+    pub task_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Operation {
+    /// `delete "Groceries" and 2 more`
+    pub fn description(&self) -> String {
+        let verb = match self.kind {
+            OperationKind::Create => "add",
+            OperationKind::Rename => "rename",
+            OperationKind::Toggle => "toggle",
+            OperationKind::Delete => "delete",
+            OperationKind::Restore => "restore",
+            OperationKind::Move => "move",
+            OperationKind::Reorder => "reorder",
+            OperationKind::Reschedule => "reschedule",
+            OperationKind::Edit => "edit",
+        };
+        let mut description = format!("{verb} \"{}\"", self.label);
+        if self.task_count > 1 {
+            description.push_str(&format!(" and {} more", self.task_count - 1));
+        }
+        description
+    }
+}
+
+/// The rows of the tasks an operation touched, taken before or after it.
+/// Undo turns the state after back into the state before, and redo the
+/// other way round
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub tasks: Vec<TaskRow>,
+    /// `(task_id, tag_id)` pairs
+    pub task_tags: Vec<(String, String)>,
+    pub reminders: Vec<ReminderRow>,
+}
+
+/// A `tasks` row as stored, timestamps and days in their SQL text form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRow {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    pub completed: bool,
+    pub completed_at: Option<String>,
+    pub created_at: String,
+    pub due_date: Option<String>,
+    pub due_time: Option<String>,
+    pub task_order: i64,
+    pub notes: String,
+    pub priority: i64,
+    pub recurrence: Option<String>,
+    pub repeat_from: String,
+    pub occurrence: i64,
+    /// Missing from operations recorded before the trash existed
+    #[serde(default)]
+    pub deleted_at: Option<String>,
+    /// Missing from operations recorded before occurrences were linked
+    #[serde(default)]
+    pub next_occurrence_id: Option<String>,
+}
+
+/// A `reminders` row as stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderRow {
+    pub id: String,
+    pub task_id: String,
+    pub remind_at: Option<String>,
+    pub offset_minutes: Option<i64>,
+    pub delivered_at: Option<String>,
+    pub created_at: String,
+}
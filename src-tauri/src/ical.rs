@@ -0,0 +1,732 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use uuid::Uuid;
+
+use crate::date_filter::Zone;
+use crate::export::{Document, ExportFilter, ExportedTask, ImportOptions, ImportReport};
+use crate::recurrence::{RepeatFrom, Rule};
+use crate::reminder::ReminderTrigger;
+use crate::repository::TaskRepository;
+use crate::task::Priority;
+use crate::{Error, Result};
+
+const PRODID: &str = "-//Act//Act Tasks//EN";
+
+const DATE: &str = "%Y%m%d";
+const DATE_TIME: &str = "%Y%m%dT%H%M%S";
+const UTC_DATE_TIME: &str = "%Y%m%dT%H%M%SZ";
+
+/// Longest content line in octets before it is folded (RFC 5545 3.1)
+const LINE_OCTETS: usize = 75;
+
+/// Tasks selected by `filter` as an iCalendar file of VTODOs
+pub async fn export(repo: &TaskRepository, filter: &ExportFilter) -> Result<String> {
+    let zone = repo.timezone().await?;
+    let document = repo.export_tasks(filter).await?;
+    Ok(serialize(&document.tasks, &zone, Utc::now()))
+}
+
+/// Import the VTODOs of an iCalendar file like a JSON document, ids taken
+/// from their UIDs. Anything Act cannot represent is reported as a warning
+pub async fn import(
+    repo: &TaskRepository,
+    text: &str,
+    options: ImportOptions,
+) -> Result<ImportReport> {
+    let zone = repo.timezone().await?;
+    let (tasks, warnings) = parse(text, &zone)?;
+    let mut report = repo.import_tasks(&Document::new(tasks), options).await?;
+    report.warnings.splice(0..0, warnings);
+    Ok(report)
+}
+
+/// One VTODO per task: UID is the task id, RELATED-TO its parent, DUE a
+/// date or, with a due time, the UTC instant that time is in `zone`.
+/// Recurrence becomes RRULE and reminders VALARMs, relative ones counted
+/// from DUE. Sibling order, repeating from completion and the occurrence
+/// number go into `X-ACT-` properties that other apps ignore
+pub fn serialize(tasks: &[ExportedTask], zone: &Zone, now: DateTime<Utc>) -> String {
+    let stamp = |instant: &DateTime<Utc>| instant.format(UTC_DATE_TIME).to_string();
+
+    let mut out = String::new();
+    let mut line = |line: String| fold(&line, &mut out);
+    line("BEGIN:VCALENDAR".to_string());
+    line("VERSION:2.0".to_string());
+    line(format!("PRODID:{PRODID}"));
+
+    for task in tasks {
+        line("BEGIN:VTODO".to_string());
+        line(format!("UID:{}", escape(&task.id)));
+        line(format!("DTSTAMP:{}", stamp(&now)));
+        line(format!("CREATED:{}", stamp(&task.created_at)));
+        line(format!("SUMMARY:{}", escape(&task.name)));
+        if let Some(notes) = task.notes.as_deref().filter(|notes| !notes.is_empty()) {
+            line(format!("DESCRIPTION:{}", escape(notes)));
+        }
+        if task.completed {
+            line("STATUS:COMPLETED".to_string());
+            if let Some(completed_at) = &task.completed_at {
+                line(format!("COMPLETED:{}", stamp(completed_at)));
+            }
+        } else {
+            line("STATUS:NEEDS-ACTION".to_string());
+        }
+        match (task.due_date, task.due_time) {
+            (Some(day), Some(time)) => {
+                let due = zone.to_utc(day.and_time(time));
+                line(format!("DUE:{}", stamp(&due)));
+            }
+            (Some(day), None) => line(format!("DUE;VALUE=DATE:{}", day.format(DATE))),
+            _ => {}
+        }
+        if let Some(priority) = ical_priority(task.priority) {
+            line(format!("PRIORITY:{priority}"));
+        }
+        if !task.tags.is_empty() {
+            let tags: Vec<String> = task.tags.iter().map(|tag| escape(tag)).collect();
+            line(format!("CATEGORIES:{}", tags.join(",")));
+        }
+        if let Some(parent_id) = &task.parent_id {
+            line(format!("RELATED-TO;RELTYPE=PARENT:{}", escape(parent_id)));
+        }
+        if let Some(rule) = &task.recurrence {
+            let rule = rule.trim();
+            let rule = rule
+                .get(..6)
+                .filter(|prefix| prefix.eq_ignore_ascii_case("RRULE:"))
+                .map_or(rule, |_| &rule[6..]);
+            line(format!("RRULE:{rule}"));
+            if task.repeat_from == RepeatFrom::Completion {
+                line("X-ACT-REPEAT-FROM:COMPLETION".to_string());
+            }
+        }
+        if task.occurrence != 1 {
+            line(format!("X-ACT-OCCURRENCE:{}", task.occurrence));
+        }
+        line(format!("X-ACT-ORDER:{}", task.order));
+
+        for reminder in task.reminders.iter().flatten() {
+            line("BEGIN:VALARM".to_string());
+            line("ACTION:DISPLAY".to_string());
+            line(format!("DESCRIPTION:{}", escape(&task.name)));
+            match reminder {
+                ReminderTrigger::Absolute { at } => {
+                    line(format!("TRIGGER;VALUE=DATE-TIME:{}", stamp(at)))
+                }
+                ReminderTrigger::Relative { offset_minutes } => line(format!(
+                    "TRIGGER;RELATED=END:{}",
+                    format_duration(*offset_minutes)
+                )),
+            }
+            line("END:VALARM".to_string());
+        }
+        line("END:VTODO".to_string());
+    }
+    line("END:VCALENDAR".to_string());
+    out
+}
+
+/// Parse the VTODOs of an iCalendar file, ignoring events, timezones and
+/// other components. Date-times become wall-clock times in `zone`; those
+/// with a TZID Act does not know are taken as floating. `task_order` comes
+/// from `X-ACT-ORDER` or else file order among siblings. Also returns what
+/// was dropped, such as recurrence rules beyond the supported subset
+pub fn parse(text: &str, zone: &Zone) -> Result<(Vec<ExportedTask>, Vec<String>)> {
+    let mut tasks = Vec::new();
+    let mut warnings = Vec::new();
+    let mut todo: Option<Todo> = None;
+    let mut alarm: Option<Option<ReminderTrigger>> = None;
+    let mut found_calendar = false;
+
+    for (number, line) in unfold(text) {
+        let invalid = |reason: String| Error::InvalidExport(format!("line {number}: {reason}"));
+        let property =
+            Property::parse(&line).ok_or_else(|| invalid("expected NAME:VALUE".into()))?;
+
+        match (
+            property.name.as_str(),
+            property.value.to_ascii_uppercase().as_str(),
+        ) {
+            ("BEGIN", "VCALENDAR") => found_calendar = true,
+            ("BEGIN", "VTODO") => todo = Some(Todo::default()),
+            ("BEGIN", "VALARM") if todo.is_some() => alarm = Some(None),
+            ("END", "VALARM") => {
+                if let (Some(todo), Some(trigger)) = (&mut todo, alarm.take()) {
+                    match trigger {
+                        Some(trigger) if trigger.check().is_ok() => {
+                            todo.reminders.get_or_insert_default().push(trigger)
+                        }
+                        Some(_) => todo
+                            .dropped
+                            .push("an alarm more than a year from the due date".into()),
+                        None => todo
+                            .dropped
+                            .push("an alarm without a usable TRIGGER".into()),
+                    }
+                }
+            }
+            ("END", "VTODO") => {
+                let Some(todo) = todo.take() else {
+                    return Err(invalid("END:VTODO without BEGIN:VTODO".into()));
+                };
+                let (task, dropped) = todo.finish();
+                warnings.extend(
+                    dropped
+                        .into_iter()
+                        .map(|what| format!("{:?}: dropped {what}", task.name)),
+                );
+                tasks.push(task);
+            }
+            _ => match (&mut todo, &mut alarm) {
+                (Some(_), Some(trigger)) if property.name == "TRIGGER" => {
+                    *trigger = parse_trigger(&property, zone);
+                }
+                (Some(_), Some(_)) => {}
+                (Some(todo), None) => todo.set(&property, zone).map_err(invalid)?,
+                (None, _) => {}
+            },
+        }
+    }
+
+    if !found_calendar {
+        return Err(Error::InvalidExport("not an iCalendar file".to_string()));
+    }
+    if todo.is_some() {
+        return Err(Error::InvalidExport("unterminated VTODO".to_string()));
+    }
+
+    let mut siblings: HashMap<Option<String>, i64> = HashMap::new();
+    for task in &mut tasks {
+        let next = siblings.entry(task.parent_id.clone()).or_default();
+        if task.order < 0 {
+            task.order = *next;
+        }
+        *next = task.order.max(*next) + 1;
+    }
+    Ok((tasks, warnings))
+}
+
+/// A VTODO's properties as they are read
+#[derive(Default)]
+struct Todo {
+    uid: Option<String>,
+    parent_id: Option<String>,
+    summary: String,
+    description: Option<String>,
+    status_completed: bool,
+    completed_at: Option<DateTime<Utc>>,
+    created_at: Option<DateTime<Utc>>,
+    stamped_at: Option<DateTime<Utc>>,
+    due_date: Option<NaiveDate>,
+    due_time: Option<NaiveTime>,
+    priority: Priority,
+    recurrence: Option<String>,
+    repeat_from: RepeatFrom,
+    occurrence: Option<i64>,
+    order: Option<i64>,
+    tags: Vec<String>,
+    /// `None` without a usable VALARM, so importing keeps the task's own
+    reminders: Option<Vec<ReminderTrigger>>,
+    dropped: Vec<String>,
+}
+
+impl Todo {
+    fn set(&mut self, property: &Property, zone: &Zone) -> std::result::Result<(), String> {
+        let value = property.value.as_str();
+        let time =
+            || Time::parse(property).ok_or_else(|| format!("invalid {} {value}", property.name));
+
+        match property.name.as_str() {
+            "UID" => self.uid = Some(unescape(value)),
+            "SUMMARY" => self.summary = unescape(value),
+            "DESCRIPTION" => self.description = Some(unescape(value)),
+            "STATUS" => self.status_completed = value.eq_ignore_ascii_case("COMPLETED"),
+            "COMPLETED" => self.completed_at = Some(time()?.instant(zone)),
+            "CREATED" => self.created_at = Some(time()?.instant(zone)),
+            "DTSTAMP" => self.stamped_at = Some(time()?.instant(zone)),
+            "DUE" => match time()? {
+                Time::Date(day) => self.due_date = Some(day),
+                due => {
+                    let local = due.local(zone);
+                    self.due_date = Some(local.date());
+                    self.due_time = Some(local.time());
+                }
+            },
+            "PRIORITY" => {
+                let priority = value
+                    .parse()
+                    .ok()
+                    .filter(|priority| (0..=9).contains(priority))
+                    .ok_or_else(|| format!("invalid PRIORITY {value}"))?;
+                self.priority = act_priority(priority);
+            }
+            "CATEGORIES" => self.tags.extend(
+                split_list(value)
+                    .into_iter()
+                    .map(|tag| unescape(&tag).trim().to_string())
+                    .filter(|tag| !tag.is_empty()),
+            ),
+            "RELATED-TO" => {
+                let parent = property
+                    .param("RELTYPE")
+                    .is_none_or(|reltype| reltype.eq_ignore_ascii_case("PARENT"));
+                if parent {
+                    self.parent_id = Some(unescape(value));
+                }
+            }
+            "RRULE" => match Rule::parse(value) {
+                Ok(_) => self.recurrence = Some(value.to_string()),
+                Err(_) => self.dropped.push(format!("the unsupported RRULE {value}")),
+            },
+            "X-ACT-REPEAT-FROM" if value.eq_ignore_ascii_case("COMPLETION") => {
+                self.repeat_from = RepeatFrom::Completion
+            }
+            "X-ACT-OCCURRENCE" => {
+                self.occurrence = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|occurrence| *occurrence > 0)
+                        .ok_or_else(|| format!("invalid X-ACT-OCCURRENCE {value}"))?,
+                )
+            }
+            "X-ACT-ORDER" => {
+                self.order = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|order| *order >= 0)
+                        .ok_or_else(|| format!("invalid X-ACT-ORDER {value}"))?,
+                )
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The task, ordered -1 when the file did not say, and what could not be
+    /// imported
+    fn finish(self) -> (ExportedTask, Vec<String>) {
+        let completed = self.status_completed || self.completed_at.is_some();
+        let task = ExportedTask {
+            id: self.uid.unwrap_or_else(|| Uuid::new_v4().to_string()),
+            parent_id: self.parent_id,
+            name: self.summary,
+            notes: self.description,
+            completed,
+            completed_at: self.completed_at.or_else(|| completed.then(Utc::now)),
+            created_at: self.created_at.or(self.stamped_at).unwrap_or_else(Utc::now),
+            due_date: self.due_date,
+            due_time: self.due_time,
+            order: self.order.unwrap_or(-1),
+            priority: self.priority,
+            recurrence: self.recurrence,
+            repeat_from: self.repeat_from,
+            occurrence: self.occurrence.unwrap_or(1),
+            tags: self.tags,
+            reminders: self.reminders,
+        };
+        (task, self.dropped)
+    }
+}
+
+/// A content line split into its name, parameters and value
+struct Property {
+    /// Upper-cased, as names are case-insensitive
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        // Parameter values may be quoted and contain `;` or `:`
+        let mut quoted = false;
+        let mut value_start = None;
+        let mut splits = Vec::new();
+        for (i, c) in line.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                ';' if !quoted => splits.push(i),
+                ':' if !quoted => {
+                    value_start = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let value_start = value_start?;
+        splits.push(value_start);
+
+        let name = line[..splits[0]].trim().to_ascii_uppercase();
+        if name.is_empty() {
+            return None;
+        }
+        let params = splits
+            .windows(2)
+            .filter_map(|pair| {
+                let (key, value) = line[pair[0] + 1..pair[1]].split_once('=')?;
+                Some((
+                    key.to_ascii_uppercase(),
+                    value.trim_matches('"').to_string(),
+                ))
+            })
+            .collect();
+        Some(Property {
+            name,
+            params,
+            value: line[value_start + 1..].to_string(),
+        })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A DATE or DATE-TIME value
+enum Time {
+    Date(NaiveDate),
+    /// No timezone, meaning the same wall-clock time everywhere
+    Floating(NaiveDateTime),
+    Instant(DateTime<Utc>),
+}
+
+impl Time {
+    fn parse(property: &Property) -> Option<Self> {
+        let value = property.value.trim();
+        if property
+            .param("VALUE")
+            .is_some_and(|kind| kind.eq_ignore_ascii_case("DATE"))
+            || value.len() == 8
+        {
+            return NaiveDate::parse_from_str(value, DATE).ok().map(Time::Date);
+        }
+        if let Some(utc) = value.strip_suffix('Z') {
+            let local = NaiveDateTime::parse_from_str(utc, DATE_TIME).ok()?;
+            return Some(Time::Instant(local.and_utc()));
+        }
+        let local = NaiveDateTime::parse_from_str(value, DATE_TIME).ok()?;
+        match property.param("TZID").and_then(parse_tzid) {
+            Some(tz) => Some(Time::Instant(Zone::Named(tz).to_utc(local))),
+            None => Some(Time::Floating(local)),
+        }
+    }
+
+    /// Wall-clock time in `zone`, days at midnight
+    fn local(&self, zone: &Zone) -> NaiveDateTime {
+        match self {
+            Time::Date(day) => day.and_time(NaiveTime::MIN),
+            Time::Floating(local) => *local,
+            Time::Instant(instant) => zone.local_time(*instant),
+        }
+    }
+
+    fn instant(&self, zone: &Zone) -> DateTime<Utc> {
+        match self {
+            Time::Instant(instant) => *instant,
+            _ => zone.to_utc(self.local(zone)),
+        }
+    }
+}
+
+/// IANA names, also behind the `/…/` prefixes some apps add
+fn parse_tzid(tzid: &str) -> Option<Tz> {
+    tzid.parse().ok().or_else(|| {
+        let name = tzid.trim_start_matches('/');
+        name.parse()
+            .ok()
+            .or_else(|| name.split_once('/')?.1.parse().ok())
+    })
+}
+
+fn parse_trigger(property: &Property, zone: &Zone) -> Option<ReminderTrigger> {
+    let absolute = property
+        .param("VALUE")
+        .is_some_and(|kind| kind.eq_ignore_ascii_case("DATE-TIME"));
+    if absolute {
+        let at = Time::parse(property)?.instant(zone);
+        return Some(ReminderTrigger::Absolute { at });
+    }
+    // Act tasks have no start, so alarms relative to one count from DUE too
+    parse_duration(&property.value)
+        .map(|offset_minutes| ReminderTrigger::Relative { offset_minutes })
+}
+
+/// Whole minutes in a DURATION such as `-PT15M` or `P1DT2H`, seconds
+/// rounded towards zero
+fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (sign, rest) = match value.as_bytes().first()? {
+        b'-' => (-1, &value[1..]),
+        b'+' => (1, &value[1..]),
+        _ => (1, value),
+    };
+    let rest = rest.strip_prefix(['P', 'p'])?;
+
+    let mut seconds = 0i64;
+    let mut number = String::new();
+    let mut in_time = false;
+    let mut any = false;
+    for c in rest.chars() {
+        let unit = match c.to_ascii_uppercase() {
+            '0'..='9' => {
+                number.push(c);
+                continue;
+            }
+            'T' if !in_time && number.is_empty() => {
+                in_time = true;
+                continue;
+            }
+            'W' if !in_time => 7 * 86_400,
+            'D' if !in_time => 86_400,
+            'H' if in_time => 3_600,
+            'M' if in_time => 60,
+            'S' if in_time => 1,
+            _ => return None,
+        };
+        let count: i64 = number.parse().ok()?;
+        seconds = seconds.checked_add(count.checked_mul(unit)?)?;
+        number.clear();
+        any = true;
+    }
+    if !any || !number.is_empty() {
+        return None;
+    }
+    Some(sign * seconds / 60)
+}
+
+fn format_duration(minutes: i64) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    format!("{sign}PT{}M", minutes.unsigned_abs())
+}
+
+/// iCalendar priorities run from 1 (highest) to 9, 0 meaning none
+fn ical_priority(priority: Priority) -> Option<u8> {
+    match priority {
+        Priority::None => None,
+        Priority::Low => Some(9),
+        Priority::Medium => Some(5),
+        Priority::High => Some(3),
+        Priority::Urgent => Some(1),
+    }
+}
+
+fn act_priority(priority: u8) -> Priority {
+    match priority {
+        0 => Priority::None,
+        1 => Priority::Urgent,
+        2..=4 => Priority::High,
+        5 => Priority::Medium,
+        _ => Priority::Low,
+    }
+}
+
+/// Escape a TEXT value (RFC 5545 3.3.11)
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(escaped) => out.push(escaped),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Split a list value on the commas that are not escaped, leaving the
+/// items escaped
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let item = items.last_mut().expect("never empty");
+                item.push(c);
+                item.extend(chars.next());
+            }
+            ',' => items.push(String::new()),
+            _ => items.last_mut().expect("never empty").push(c),
+        }
+    }
+    items
+}
+
+/// Append `line` with CRLF, breaking it into lines of at most 75 octets,
+/// each continuation starting with a space. Breaks fall between characters
+/// so multi-byte UTF-8 sequences stay whole
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Logical lines with their first physical line number, continuation
+/// lines (starting with a space or tab) joined back on
+fn unfold(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (i, physical) in text.lines().enumerate() {
+        let physical = physical.strip_suffix('\r').unwrap_or(physical);
+        match physical.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => {
+                lines.last_mut().expect("checked").1.push_str(rest);
+            }
+            _ if physical.trim().is_empty() => {}
+            _ => lines.push((i + 1, physical.to_string())),
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n";
+
+    #[test]
+    fn folds_and_unfolds() {
+        let long = format!("SUMMARY:{}", "Grüße aus Zürich, ".repeat(12));
+        let mut out = String::new();
+        fold(&long, &mut out);
+
+        let physical: Vec<&str> = out.split("\r\n").filter(|l| !l.is_empty()).collect();
+        assert!(physical.len() > 1);
+        assert!(physical.iter().all(|l| l.len() <= LINE_OCTETS));
+        assert!(physical[1..].iter().all(|l| l.starts_with(' ')));
+        assert_eq!(unfold(&out), [(1, long)]);
+
+        let text = "BEGIN:VTODO\nSUMMARY:Split\n  across\n\t lines\r\n\r\nEND:VTODO";
+        assert_eq!(
+            unfold(text),
+            [
+                (1, "BEGIN:VTODO".to_string()),
+                (2, "SUMMARY:Split across lines".to_string()),
+                (6, "END:VTODO".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes_text() {
+        for text in [
+            "plain",
+            "a;b,c",
+            "back\\slash",
+            "two\nlines",
+            "\\n literally",
+            "",
+        ] {
+            assert_eq!(unescape(&escape(text)), text);
+        }
+        assert_eq!(escape("a,b;c\\\r\n"), r"a\,b\;c\\\n");
+        assert_eq!(split_list("one,two\\,three,"), ["one", "two\\,three", ""]);
+    }
+
+    #[test]
+    fn reminders_only_from_alarms() {
+        let zone = Zone::parse("UTC").unwrap();
+        let text = format!(
+            "{CALENDAR}BEGIN:VTODO\r\nUID:a\r\nSUMMARY:No alarm\r\nEND:VTODO\r\n\
+             BEGIN:VTODO\r\nUID:b\r\nSUMMARY:Alarm\r\nBEGIN:VALARM\r\n\
+             TRIGGER;RELATED=END:-PT15M\r\nEND:VALARM\r\nEND:VTODO\r\n\
+             BEGIN:VTODO\r\nUID:c\r\nSUMMARY:Broken alarm\r\nBEGIN:VALARM\r\n\
+             TRIGGER:soon\r\nEND:VALARM\r\nEND:VTODO\r\n\
+             BEGIN:VTODO\r\nUID:d\r\nSUMMARY:Distant alarm\r\nBEGIN:VALARM\r\n\
+             TRIGGER:-P100000000000W\r\nEND:VALARM\r\nBEGIN:VALARM\r\n\
+             TRIGGER:P53W\r\nEND:VALARM\r\nEND:VTODO\r\nEND:VCALENDAR\r\n"
+        );
+        let (tasks, warnings) = parse(&text, &zone).unwrap();
+
+        assert!(tasks[0].reminders.is_none());
+        assert!(matches!(
+            tasks[1].reminders.as_deref(),
+            Some([ReminderTrigger::Relative {
+                offset_minutes: -15
+            }])
+        ));
+        assert!(tasks[2].reminders.is_none());
+        assert!(tasks[3].reminders.is_none());
+        assert_eq!(
+            warnings,
+            [
+                "\"Broken alarm\": dropped an alarm without a usable TRIGGER",
+                "\"Distant alarm\": dropped an alarm more than a year from the due date",
+                "\"Distant alarm\": dropped an alarm more than a year from the due date",
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let zone = Zone::parse("Europe/Zurich").unwrap();
+        let task = ExportedTask {
+            id: "a".to_string(),
+            parent_id: Some("p".to_string()),
+            name: "Plan; review, ship\\now".to_string(),
+            notes: Some("First line\nsecond line".to_string()),
+            completed: true,
+            completed_at: Some("2026-10-17T08:00:00Z".parse().unwrap()),
+            created_at: "2026-10-01T12:30:00Z".parse().unwrap(),
+            due_date: Some("2026-10-25".parse().unwrap()),
+            // The night the clocks go back, when 02:30 happens twice
+            due_time: Some("02:30:00".parse().unwrap()),
+            order: 4,
+            priority: Priority::High,
+            recurrence: Some("FREQ=MONTHLY;BYDAY=-1FR".to_string()),
+            repeat_from: RepeatFrom::Completion,
+            occurrence: 2,
+            tags: vec!["work, mostly".to_string(), "@home".to_string()],
+            reminders: Some(vec![
+                ReminderTrigger::Relative {
+                    offset_minutes: -90,
+                },
+                ReminderTrigger::Absolute {
+                    at: "2026-10-24T18:00:00Z".parse().unwrap(),
+                },
+            ]),
+        };
+        let text = serialize(std::slice::from_ref(&task), &zone, Utc::now());
+        let (tasks, warnings) = parse(&text, &zone).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(
+            serde_json::to_value(&tasks).unwrap(),
+            serde_json::to_value([task]).unwrap()
+        );
+    }
+}
@@ -1,48 +1,141 @@
-use tauri_plugin_sql::{Migration, MigrationKind};
+use clap::Parser;
+use tauri::Manager;
+
+pub mod api;
+pub mod audit;
+pub mod backup;
+mod cli;
+mod commands;
+pub mod date_filter;
+pub mod db;
+mod error;
+pub mod export;
+pub mod history;
+pub mod ical;
+pub mod markdown;
+pub mod notes;
+pub mod profile;
+pub mod recurrence;
+pub mod reminder;
+pub mod repository;
+mod scheduler;
+pub mod search;
+pub mod tag;
+pub mod task;
+pub mod todotxt;
+pub mod trash;
+mod tui;
+
+pub use error::{Error, Result};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let migrations = vec![
-        Migration {
-            version: 1,
-            description: "create_tasks_table",
-            sql: include_str!("../migrations/001_initial.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 2,
-            description: "add_order_column",
-            sql: include_str!("../migrations/002_add_order.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 3,
-            description: "rename_date_created_add_due_date",
-            sql: include_str!("../migrations/003_rename_date_created_add_due_date.sql"),
-            kind: MigrationKind::Up,
-        },
-    ];
-
     // Determine the database path before building the app
-    let home_dir = dirs::home_dir().expect("failed to get home directory");
-    let act_dir = home_dir.join(".act");
-    let db_file = act_dir.join("act.db");
-    let db_url = format!("sqlite:{}", db_file.to_string_lossy());
+    let mut cli = cli::Cli::parse();
+    let command = cli.command.take();
+    let json = cli.json;
+    let (data_dir, profile) = match startup_profile(cli) {
+        Ok(startup) => startup,
+        Err(e) => {
+            eprintln!("act: {e}");
+            std::process::exit(1);
+        }
+    };
 
+    // Subcommands run headless against the same database
+    if let Some(command) = command {
+        std::process::exit(cli::run(command, &data_dir, &profile, json));
+    }
     tauri::Builder::default()
-        .setup(move |_app| {
-            // Create the .act directory in the user's home folder
-            if !act_dir.exists() {
-                std::fs::create_dir_all(&act_dir).expect("failed to create .act directory");
+        .setup(move |app| {
+            let pool = tauri::async_runtime::block_on(data_dir.open(&profile))?;
+            let repo = repository::TaskRepository::new(pool);
+            let api = api::ApiServer::new(app.handle().clone(), &repo, data_dir.clone());
+            let profiles = profile::Profiles::new(data_dir, profile);
+            scheduler::spawn(app.handle().clone(), repo.clone());
+            scheduler::spawn_backups(repo.clone(), profiles.clone());
+            scheduler::spawn_trash_purge(repo.clone());
+            // A busy port should not keep the window from opening
+            if let Err(e) = tauri::async_runtime::block_on(api.restart()) {
+                eprintln!("failed to start the API server: {e}");
             }
-            
+            app.manage(api);
+            app.manage(repo);
+            app.manage(profiles);
+
             Ok(())
         })
-        .plugin(
-            tauri_plugin_sql::Builder::default()
-                .add_migrations(&db_url, migrations)
-                .build()
-        )
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .invoke_handler(tauri::generate_handler![
+            commands::list_profiles,
+            commands::active_profile,
+            commands::switch_profile,
+            commands::list_backups,
+            commands::restore_backup,
+            commands::get_backup_settings,
+            commands::set_backup_settings,
+            commands::export_tasks,
+            commands::import_tasks,
+            commands::export_todo_txt,
+            commands::import_todo_txt,
+            commands::export_ical,
+            commands::import_ical,
+            commands::export_markdown,
+            commands::import_markdown,
+            commands::load_tasks,
+            commands::search_tasks,
+            commands::date_filters,
+            commands::get_timezone,
+            commands::set_timezone,
+            commands::create_task,
+            commands::rename_task,
+            commands::set_due_date,
+            commands::set_priority,
+            commands::set_recurrence,
+            commands::list_reminders,
+            commands::add_reminder,
+            commands::delete_reminder,
+            commands::get_notes,
+            commands::set_notes,
+            commands::render_notes,
+            commands::toggle_task,
+            commands::list_tags,
+            commands::create_tag,
+            commands::rename_tag,
+            commands::merge_tags,
+            commands::delete_tag,
+            commands::attach_tags,
+            commands::detach_tags,
+            commands::delete_subtree,
+            commands::list_trash,
+            commands::restore_from_trash,
+            commands::purge_trash,
+            commands::get_trash_settings,
+            commands::set_trash_settings,
+            commands::reorder_tasks,
+            commands::move_tasks,
+            commands::undo,
+            commands::redo,
+            commands::task_history,
+            commands::rescheduled_tasks,
+            commands::get_api_settings,
+            commands::set_api_settings,
+            commands::api_token,
+            commands::rotate_api_token,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+/// Data directory and profile to open: `--profile` or `ACT_PROFILE`, else the
+/// profile used last, else the default one
+fn startup_profile(cli: cli::Cli) -> Result<(profile::DataDir, String)> {
+    let data_dir = profile::DataDir::resolve(cli.data_dir)?;
+    let profile = cli
+        .profile
+        .or_else(|| data_dir.last_profile())
+        .unwrap_or_else(|| profile::DEFAULT_PROFILE.to_string());
+    profile::check_name(&profile)?;
+    Ok((data_dir, profile))
+}
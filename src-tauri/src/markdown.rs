@@ -0,0 +1,326 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{NaiveDate, NaiveTime, Timelike, Utc};
+use uuid::Uuid;
+
+use crate::date_filter::day_label;
+use crate::export::{Document, ExportFilter, ExportedTask, ImportOptions, ImportReport};
+use crate::recurrence::RepeatFrom;
+use crate::repository::TaskRepository;
+use crate::task::Priority;
+use crate::{Error, Result};
+
+/// Marks a due date after an item's text, as in Obsidian's Tasks plugin
+const DUE_MARK: &str = "📅";
+
+const DATE: &str = "%Y-%m-%d";
+
+/// Columns a tab counts as when comparing indentation
+const TAB_WIDTH: usize = 4;
+
+/// Tasks selected by `filter` as Markdown checklists under a heading per
+/// due day
+pub async fn export(repo: &TaskRepository, filter: &ExportFilter) -> Result<String> {
+    let zone = repo.timezone().await?;
+    let document = repo.export_tasks(filter).await?;
+    let today = zone.local_time(Utc::now()).date();
+    Ok(serialize(&document.tasks, today))
+}
+
+/// Import a pasted checklist as new tasks under `parent_id`, after the
+/// tasks already there
+pub async fn import(
+    repo: &TaskRepository,
+    text: &str,
+    parent_id: Option<&str>,
+    options: ImportOptions,
+) -> Result<ImportReport> {
+    let first_order = repo.next_order(parent_id).await?;
+    let document = Document::new(parse(text, parent_id, first_order)?);
+    repo.import_tasks(&document, options).await
+}
+
+/// Nested `- [ ]` / `- [x]` lists, one section per due day in date order
+/// and "Someday" last, headings labelled like the sidebar relative to
+/// `today`. Each top-level task goes into the section of its own due date,
+/// or else the earliest one in its subtree, and brings its whole subtree
+/// along. Dated items end in `📅 YYYY-MM-DD` and the due time, which is
+/// what `parse` reads back; everything else is for reading only
+pub fn serialize(tasks: &[ExportedTask], today: NaiveDate) -> String {
+    let index: HashMap<&str, usize> = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, task)| (task.id.as_str(), i))
+        .collect();
+    let parent = |i: usize| {
+        tasks[i]
+            .parent_id
+            .as_deref()
+            .and_then(|id| index.get(id).copied())
+    };
+
+    // Tasks come in outline order, so children are seen before parents
+    // when going backwards
+    let mut earliest: Vec<Option<NaiveDate>> = tasks.iter().map(|task| task.due_date).collect();
+    for i in (0..tasks.len()).rev() {
+        if let Some(p) = parent(i) {
+            earliest[p] = match (earliest[p], earliest[i]) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+    }
+
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    // Keyed so that undated tasks sort last
+    let mut sections: BTreeMap<(bool, Option<NaiveDate>), Vec<usize>> = BTreeMap::new();
+    for i in 0..tasks.len() {
+        match parent(i) {
+            Some(p) => children.entry(p).or_default().push(i),
+            None => {
+                let day = tasks[i].due_date.or(earliest[i]);
+                sections.entry((day.is_none(), day)).or_default().push(i);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for ((_, day), roots) in sections {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let heading = day.map_or_else(|| "Someday".to_string(), |day| day_label(day, today));
+        out.push_str(&format!("## {heading}\n\n"));
+
+        let mut stack: Vec<(usize, usize)> = roots.into_iter().rev().map(|i| (i, 0)).collect();
+        while let Some((i, depth)) = stack.pop() {
+            out.push_str(&item(&tasks[i], depth));
+            out.push('\n');
+            if let Some(children) = children.get(&i) {
+                stack.extend(children.iter().rev().map(|&child| (child, depth + 1)));
+            }
+        }
+    }
+    out
+}
+
+fn item(task: &ExportedTask, depth: usize) -> String {
+    let check = if task.completed { 'x' } else { ' ' };
+    let name = task.name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut line = format!("{}- [{check}] {name}", "  ".repeat(depth));
+    if let Some(due_date) = task.due_date {
+        line.push_str(&format!(" {DUE_MARK} {}", due_date.format(DATE)));
+        if let Some(due_time) = task.due_time {
+            let format = if due_time.second() == 0 {
+                "%H:%M"
+            } else {
+                "%H:%M:%S"
+            };
+            line.push_str(&format!(" {}", due_time.format(format)));
+        }
+    }
+    line
+}
+
+/// Parse the list items of a Markdown text into new tasks, ignoring
+/// headings, paragraphs and code blocks. `- [ ]`, `* [x]`, plain bullets
+/// and numbered items all count, a deeper indent than the item before
+/// making an item its subtask. Top-level items go under `parent_id`,
+/// ordered from `first_order`
+pub fn parse(text: &str, parent_id: Option<&str>, first_order: i64) -> Result<Vec<ExportedTask>> {
+    let now = Utc::now();
+    let mut tasks = Vec::new();
+    // Open items by indentation, innermost last
+    let mut open: Vec<(usize, String)> = Vec::new();
+    let mut siblings: HashMap<Option<String>, i64> = HashMap::new();
+    let mut in_code = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let Some((indent, completed, text)) = parse_item(line) else {
+            continue;
+        };
+
+        while open
+            .last()
+            .is_some_and(|(open_indent, _)| *open_indent >= indent)
+        {
+            open.pop();
+        }
+        let parent_id = match open.last() {
+            Some((_, id)) => Some(id.clone()),
+            None => parent_id.map(str::to_string),
+        };
+        let start = if open.is_empty() { first_order } else { 0 };
+        let next = siblings.entry(parent_id.clone()).or_insert(start);
+        let order = *next;
+        *next += 1;
+
+        let (name, due_date, due_time) = split_due(text);
+        let task = ExportedTask {
+            id: Uuid::new_v4().to_string(),
+            parent_id,
+            name: name.to_string(),
+            notes: None,
+            completed,
+            completed_at: completed.then_some(now),
+            created_at: now,
+            due_date,
+            due_time,
+            order,
+            priority: Priority::None,
+            recurrence: None,
+            repeat_from: RepeatFrom::Due,
+            occurrence: 1,
+            tags: Vec::new(),
+            reminders: None,
+        };
+        open.push((indent, task.id.clone()));
+        tasks.push(task);
+    }
+
+    if tasks.is_empty() {
+        return Err(Error::InvalidExport("no list items found".to_string()));
+    }
+    Ok(tasks)
+}
+
+/// Indentation, checkbox state and text of a list item line
+fn parse_item(line: &str) -> Option<(usize, bool, &str)> {
+    let content = line.trim_start();
+    let indent = line[..line.len() - content.len()]
+        .chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum();
+
+    let rest = match content.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let digits = content.find(|c: char| !c.is_ascii_digit())?;
+            if digits == 0 || digits > 9 {
+                return None;
+            }
+            content[digits..].strip_prefix(['.', ')'])?
+        }
+    };
+    // The marker has to be followed by a space, so `**bold**` is no item
+    let rest = rest.strip_prefix([' ', '\t'])?.trim_start();
+
+    let (completed, text) = if let Some(text) = rest.strip_prefix("[ ]") {
+        (false, text)
+    } else if let Some(text) = rest
+        .strip_prefix("[x]")
+        .or_else(|| rest.strip_prefix("[X]"))
+    {
+        (true, text)
+    } else {
+        (false, rest)
+    };
+    let text = text.trim();
+    (!text.is_empty()).then_some((indent, completed, text))
+}
+
+/// Take a trailing `📅 YYYY-MM-DD [HH:MM[:SS]]` off an item's text
+fn split_due(text: &str) -> (&str, Option<NaiveDate>, Option<NaiveTime>) {
+    let Some((name, due)) = text.rsplit_once(DUE_MARK) else {
+        return (text, None, None);
+    };
+    let mut parts = due.split_whitespace();
+    let day = parts
+        .next()
+        .and_then(|day| NaiveDate::parse_from_str(day, DATE).ok());
+    let time = parts.next().map(|time| {
+        NaiveTime::parse_from_str(time, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M:%S"))
+            .ok()
+    });
+    match (day, time, parts.next()) {
+        (Some(day), None, None) => (name.trim_end(), Some(day), None),
+        (Some(day), Some(Some(time)), None) => (name.trim_end(), Some(day), Some(time)),
+        _ => (text, None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_lists() {
+        let text = "# Trip\n\
+                    \n\
+                    - [ ] Pack 📅 2026-10-20\n\
+                    \x20 - [x] Socks\n\
+                    \t* Passport 📅 2026-10-19 07:30\n\
+                    1. Book hotel 📅 someday\n\
+                    ```\n\
+                    - [ ] not a task\n\
+                    ```\n\
+                    **bold** text\n\
+                    2) [X] Call mum\n";
+        // The tab counts as four columns, putting Passport under Socks
+        let tasks = parse(text, Some("root"), 5).unwrap();
+        let summary: Vec<(&str, Option<&str>, i64, bool)> = tasks
+            .iter()
+            .map(|task| {
+                let parent = task.parent_id.as_deref();
+                let parent = parent.map(|id| {
+                    tasks
+                        .iter()
+                        .find(|t| t.id == id)
+                        .map_or(id, |t| t.name.as_str())
+                });
+                (task.name.as_str(), parent, task.order, task.completed)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Pack", Some("root"), 5, false),
+                ("Socks", Some("Pack"), 0, true),
+                ("Passport", Some("Socks"), 0, false),
+                ("Book hotel 📅 someday", Some("root"), 6, false),
+                ("Call mum", Some("root"), 7, true),
+            ]
+        );
+        assert_eq!(tasks[0].due_date, "2026-10-20".parse().ok());
+        assert_eq!(tasks[2].due_time, "07:30:00".parse().ok());
+        assert!(parse("Just a paragraph", None, 0).is_err());
+    }
+
+    #[test]
+    fn serialized_lists_parse_back() {
+        let today: NaiveDate = "2026-10-17".parse().unwrap();
+        let mut tasks = parse(
+            "- [ ] Pack 📅 2026-10-20\n  - [x] Socks 📅 2026-10-18 09:15\n- [ ] Read",
+            None,
+            0,
+        )
+        .unwrap();
+        tasks[0].name = "Pack   well".to_string();
+
+        let text = serialize(&tasks, today);
+        assert_eq!(
+            text,
+            "## Oct 20\n\n\
+             - [ ] Pack well 📅 2026-10-20\n\
+             \x20 - [x] Socks 📅 2026-10-18 09:15\n\
+             \n\
+             ## Someday\n\n\
+             - [ ] Read\n"
+        );
+
+        let parsed = parse(&text, None, 0).unwrap();
+        let names: Vec<&str> = parsed.iter().map(|task| task.name.as_str()).collect();
+        assert_eq!(names, ["Pack well", "Socks", "Read"]);
+        assert_eq!(parsed[1].parent_id.as_ref(), Some(&parsed[0].id));
+        assert_eq!(parsed[1].due_time, tasks[1].due_time);
+    }
+}
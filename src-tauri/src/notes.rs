@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+
+use ammonia::UrlRelative;
+use pulldown_cmark::{html, Options, Parser};
+
+/// Render Markdown notes to HTML that is safe to inject into the webview.
+///
+/// Raw HTML in the notes is stripped down to a small allowlist, and links
+/// only survive with an absolute `http`, `https` or `mailto` URL so the
+/// frontend can hand them to the opener plugin instead of navigating away.
+pub fn render_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+
+    ammonia::Builder::default()
+        .url_schemes(HashSet::from(["http", "https", "mailto"]))
+        .url_relative(UrlRelative::Deny)
+        .link_rel(Some("noopener noreferrer"))
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_scripts_and_handlers() {
+        let html = render_html(
+            "<script>alert(1)</script>\n\n<img src=\"https://example.com/a.png\" onerror=\"alert(2)\">\n\n<p onclick=\"alert(3)\">hi</p>",
+        );
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("alert"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("onclick"));
+        assert!(html.contains("hi"));
+    }
+
+    #[test]
+    fn drops_unsafe_links() {
+        for markdown in [
+            "[click](javascript:alert(1))",
+            "<a href=\"javascript:alert(1)\">click</a>",
+            "[data](data:text/html,hi)",
+            "[relative](/etc/passwd)",
+        ] {
+            let html = render_html(markdown);
+            assert!(!html.contains("href"), "{markdown}: {html}");
+        }
+    }
+
+    #[test]
+    fn keeps_ordinary_markdown() {
+        let html = render_html(
+            "See [the docs](https://example.com/docs) or [mail](mailto:a@example.com).\n\n\
+             - one\n- ~~two~~\n\n1. first\n\n`inline` and\n\n```\nlet x = 1;\n```\n\n**bold** *em*",
+        );
+        assert!(html.contains(
+            "<a href=\"https://example.com/docs\" rel=\"noopener noreferrer\">the docs</a>"
+        ));
+        assert!(html.contains("href=\"mailto:a@example.com\""));
+        assert!(html.contains("<li>one</li>"));
+        assert!(html.contains("<del>two</del>"));
+        assert!(html.contains("<ol>"));
+        assert!(html.contains("<code>inline</code>"));
+        assert!(html.contains("<pre><code>let x = 1;\n</code></pre>"));
+        assert!(html.contains("<strong>bold</strong> <em>em</em>"));
+    }
+}
@@ -0,0 +1,289 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::backup::{Backup, BackupReason, BackupStore};
+use crate::repository::TaskRepository;
+use crate::{db, Error, Result};
+
+/// Profile whose database lives directly in the data directory, where
+/// `~/.act/act.db` has always been
+pub const DEFAULT_PROFILE: &str = "default";
+
+const DB_FILE: &str = "act.db";
+
+/// Remembers the profile switched to last, so the next launch reopens it
+const ACTIVE_PROFILE_FILE: &str = "profile";
+
+/// Directory holding the databases of every profile
+#[derive(Debug, Clone)]
+pub struct DataDir {
+    root: PathBuf,
+}
+
+impl DataDir {
+    /// Use `dir` when given (the `--data-dir` flag or `ACT_HOME`), otherwise
+    /// the platform default: `$XDG_DATA_HOME/act` on Linux, `~/.act`
+    /// elsewhere. An existing `~/.act/act.db` keeps being used on Linux so
+    /// upgrading does not hide anyone's tasks
+    pub fn resolve(dir: Option<PathBuf>) -> Result<Self> {
+        if let Some(root) = dir {
+            return Ok(DataDir { root });
+        }
+
+        let legacy = dirs::home_dir().map(|home| home.join(".act"));
+        let root = if cfg!(target_os = "linux") {
+            legacy
+                .filter(|dir| dir.join(DB_FILE).exists())
+                .or_else(|| dirs::data_dir().map(|data| data.join("act")))
+        } else {
+            legacy
+        };
+        root.map(|root| DataDir { root }).ok_or(Error::NoDataDir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Database file of a profile, named profiles under `profiles/<name>/`
+    pub fn database(&self, profile: &str) -> PathBuf {
+        if profile == DEFAULT_PROFILE {
+            self.root.join(DB_FILE)
+        } else {
+            self.root.join("profiles").join(profile).join(DB_FILE)
+        }
+    }
+
+    /// The default profile followed by every named one, alphabetically
+    pub fn profiles(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        match fs::read_dir(self.root.join("profiles")) {
+            Ok(entries) => {
+                for entry in entries {
+                    let entry = entry?;
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    if entry.file_type()?.is_dir()
+                        && name != DEFAULT_PROFILE
+                        && check_name(&name).is_ok()
+                    {
+                        names.push(name);
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        names.sort();
+        names.insert(0, DEFAULT_PROFILE.to_string());
+        Ok(names)
+    }
+
+    /// Where a profile's backups go
+    pub fn backups(&self, profile: &str) -> BackupStore {
+        BackupStore::new(self.root.join("backups").join(profile))
+    }
+
+    /// Profile switched to last, ignoring a missing or mangled file
+    pub fn last_profile(&self) -> Option<String> {
+        let name = fs::read_to_string(self.root.join(ACTIVE_PROFILE_FILE)).ok()?;
+        let name = name.trim();
+        check_name(name).ok().map(|_| name.to_string())
+    }
+
+    fn remember_profile(&self, name: &str) -> Result<()> {
+        fs::write(self.root.join(ACTIVE_PROFILE_FILE), format!("{name}\n"))?;
+        Ok(())
+    }
+
+    /// Open a profile's database, creating its directory and applying
+    /// pending migrations
+    pub async fn open(&self, profile: &str) -> Result<sqlx::SqlitePool> {
+        check_name(profile)?;
+        let file = self.database(profile);
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        db::open(&file, &self.backups(profile)).await
+    }
+}
+
+/// Profile names double as directory names: letters, digits, `-` and `_`
+pub fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidProfile(name.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInfo {
+    pub name: String,
+    pub database: PathBuf,
+    pub active: bool,
+}
+
+/// The data directory and which of its profiles the repository has open.
+/// Clones share the active profile
+#[derive(Clone)]
+pub struct Profiles {
+    data_dir: DataDir,
+    active: Arc<Mutex<String>>,
+}
+
+impl Profiles {
+    pub fn new(data_dir: DataDir, active: String) -> Self {
+        Self {
+            data_dir,
+            active: Arc::new(Mutex::new(active)),
+        }
+    }
+
+    pub async fn active(&self) -> ProfileInfo {
+        let name = self.active.lock().await.clone();
+        self.info(name, true)
+    }
+
+    pub async fn list(&self) -> Result<Vec<ProfileInfo>> {
+        let active = self.active.lock().await.clone();
+        Ok(self
+            .data_dir
+            .profiles()?
+            .into_iter()
+            .map(|name| {
+                let is_active = name == active;
+                self.info(name, is_active)
+            })
+            .collect())
+    }
+
+    /// Open `name`, creating it if it does not exist yet, and swap it in as
+    /// the repository's pool. The old pool is closed once its queries finish
+    pub async fn switch(&self, repo: &TaskRepository, name: &str) -> Result<ProfileInfo> {
+        let mut active = self.active.lock().await;
+        if *active != name {
+            let pool = self.data_dir.open(name).await?;
+            repo.swap_pool(pool).close().await;
+            self.data_dir.remember_profile(name)?;
+            *active = name.to_string();
+        }
+        Ok(self.info(active.clone(), true))
+    }
+
+    /// Backups of the active profile, newest first
+    pub async fn backups(&self) -> Result<Vec<Backup>> {
+        let active = self.active.lock().await;
+        self.data_dir.backups(&active).list()
+    }
+
+    /// Back up the active profile, then prune its old backups
+    pub async fn backup(&self, repo: &TaskRepository, reason: BackupReason) -> Result<Backup> {
+        let active = self.active.lock().await;
+        let store = self.data_dir.backups(&active);
+        let backup = store.create(&repo.pool(), reason).await?;
+        store.prune(&repo.backup_settings().await?)?;
+        Ok(backup)
+    }
+
+    /// Restore one of the active profile's backups, see
+    /// [`BackupStore::restore`]. Holding the profile lock keeps a switch
+    /// from pointing the repository elsewhere halfway
+    pub async fn restore(&self, repo: &TaskRepository, id: &str) -> Result<Backup> {
+        let active = self.active.lock().await;
+        self.data_dir
+            .backups(&active)
+            .restore(&repo.pool(), id)
+            .await
+    }
+
+    fn info(&self, name: String, active: bool) -> ProfileInfo {
+        ProfileInfo {
+            database: self.data_dir.database(&name),
+            name,
+            active,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date_filter::DateFilter;
+    use crate::task::TaskSort;
+
+    #[test]
+    fn names_double_as_directory_names() {
+        for name in ["work", "Side-project_2", &"a".repeat(64)] {
+            assert!(check_name(name).is_ok(), "{name}");
+        }
+        for name in ["", "../work", "a b", "über", &"a".repeat(65)] {
+            assert!(
+                matches!(check_name(name), Err(Error::InvalidProfile(_))),
+                "{name}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn switching_swaps_the_database() {
+        let dir = db::scratch_dir();
+        let data_dir = DataDir::resolve(Some(dir.clone())).unwrap();
+        let repo = TaskRepository::new(data_dir.open(DEFAULT_PROFILE).await.unwrap());
+        let profiles = Profiles::new(data_dir.clone(), DEFAULT_PROFILE.to_string());
+        let names = |repo: TaskRepository| async move {
+            repo.load_tasks(&DateFilter::All, None, TaskSort::Manual)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|task| task.name)
+                .collect::<Vec<_>>()
+        };
+        repo.create_task("Home", None, None, None).await.unwrap();
+
+        let work = profiles.switch(&repo, "work").await.unwrap();
+        assert!(work.active);
+        assert_eq!(
+            work.database,
+            dir.join("profiles").join("work").join(DB_FILE)
+        );
+        assert!(work.database.exists());
+        assert!(names(repo.clone()).await.is_empty());
+        repo.create_task("Work", None, None, None).await.unwrap();
+        assert_eq!(data_dir.last_profile().as_deref(), Some("work"));
+
+        profiles.switch(&repo, DEFAULT_PROFILE).await.unwrap();
+        assert_eq!(names(repo.clone()).await, ["Home"]);
+        let listed: Vec<(String, bool)> = profiles
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|info| (info.name, info.active))
+            .collect();
+        assert_eq!(
+            listed,
+            [("default".to_string(), true), ("work".to_string(), false)]
+        );
+
+        // A bad name leaves the active profile alone
+        assert!(matches!(
+            profiles.switch(&repo, "../work").await,
+            Err(Error::InvalidProfile(_))
+        ));
+        assert_eq!(profiles.active().await.name, DEFAULT_PROFILE);
+        assert_eq!(names(repo.clone()).await, ["Home"]);
+
+        repo.pool().close().await;
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
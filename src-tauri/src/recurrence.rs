@@ -0,0 +1,464 @@
+use chrono::{Datelike, Days, Duration, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Give up looking for the next occurrence after this many periods, so rules
+/// that can never match (e.g. `BYMONTHDAY=31` every February) terminate
+const MAX_PERIODS: u32 = 1000;
+
+/// Largest `INTERVAL` accepted, which keeps `MAX_PERIODS` steps in range
+const MAX_INTERVAL: u32 = 1000;
+
+/// What the next occurrence of a recurring task is scheduled from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum RepeatFrom {
+    /// The due date of the completed occurrence
+    #[default]
+    Due,
+    /// The day the occurrence was completed
+    Completion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A `BYDAY` entry such as `MO`, or `1MO` / `-1FR` in monthly and yearly
+/// rules
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeekdayNum {
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+/// The subset of an RFC 5545 RRULE the task engine understands: FREQ,
+/// INTERVAL, BYDAY, BYMONTHDAY, COUNT and UNTIL. Without BYMONTH, yearly
+/// rules stay in the anchor's month, so `FREQ=YEARLY;BYDAY=4TH` anchored in
+/// November is the fourth Thursday of every November
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<WeekdayNum>,
+    pub by_month_day: Vec<i32>,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDate>,
+}
+
+impl Rule {
+    /// Parse a rule like `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR`, with or
+    /// without a leading `RRULE:`
+    pub fn parse(rule: &str) -> crate::Result<Self> {
+        let invalid = |reason: &str| crate::Error::InvalidRecurrence(format!("{rule}: {reason}"));
+
+        let body = rule.trim();
+        let body = body
+            .get(..6)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("RRULE:"))
+            .map_or(body, |_| &body[6..]);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut by_day = Vec::new();
+        let mut by_month_day = Vec::new();
+        let mut count = None;
+        let mut until = None;
+
+        for part in body.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| invalid("expected KEY=VALUE"))?;
+            let value = value.to_ascii_uppercase();
+
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(invalid("unsupported FREQ")),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| (1..=MAX_INTERVAL).contains(interval))
+                        .ok_or_else(|| invalid("INTERVAL must be between 1 and 1000"))?
+                }
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(parse_weekday_num)
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| invalid("invalid BYDAY"))?
+                }
+                "BYMONTHDAY" => {
+                    by_month_day = value
+                        .split(',')
+                        .map(|day| {
+                            day.parse::<i32>()
+                                .ok()
+                                .filter(|day| (1..=31).contains(&day.abs()))
+                        })
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| invalid("invalid BYMONTHDAY"))?
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|count| *count > 0)
+                            .ok_or_else(|| invalid("COUNT must be a positive number"))?,
+                    )
+                }
+                "UNTIL" => {
+                    // Date or date-time, only the day matters for due dates
+                    until = Some(
+                        value
+                            .get(..8)
+                            .and_then(|day| NaiveDate::parse_from_str(day, "%Y%m%d").ok())
+                            .ok_or_else(|| invalid("invalid UNTIL"))?,
+                    )
+                }
+                _ => return Err(invalid(&format!("unsupported part {key}"))),
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| invalid("missing FREQ"))?;
+        if count.is_some() && until.is_some() {
+            return Err(invalid("COUNT and UNTIL cannot both be set"));
+        }
+        if matches!(frequency, Frequency::Daily | Frequency::Weekly)
+            && by_day.iter().any(|day| day.ordinal.is_some())
+        {
+            return Err(invalid(
+                "numbered BYDAY is only supported with FREQ=MONTHLY or FREQ=YEARLY",
+            ));
+        }
+        if frequency == Frequency::Weekly && !by_month_day.is_empty() {
+            return Err(invalid("BYMONTHDAY cannot be used with FREQ=WEEKLY"));
+        }
+
+        Ok(Rule {
+            frequency,
+            interval,
+            by_day,
+            by_month_day,
+            count,
+            until,
+        })
+    }
+
+    /// Whether the 1-based `occurrence` is still part of the series
+    pub fn allows_occurrence(&self, occurrence: i64) -> bool {
+        self.count
+            .is_none_or(|count| occurrence <= i64::from(count))
+    }
+
+    /// First occurrence strictly after `anchor` in the series starting at
+    /// `anchor`, or `None` once the series has ended or runs past the last
+    /// representable day
+    pub fn next_after(&self, anchor: NaiveDate) -> Option<NaiveDate> {
+        for period in 0..MAX_PERIODS {
+            let step = u64::from(period.checked_mul(self.interval)?);
+            let mut candidates = match self.frequency {
+                Frequency::Daily => {
+                    let day = anchor.checked_add_days(Days::new(step))?;
+                    if self.matches_weekday(day) && self.matches_month_day(day) {
+                        vec![day]
+                    } else {
+                        Vec::new()
+                    }
+                }
+                Frequency::Weekly => {
+                    let monday = anchor
+                        .checked_sub_days(Days::new(u64::from(
+                            anchor.weekday().num_days_from_monday(),
+                        )))?
+                        .checked_add_days(Days::new(step.checked_mul(7)?))?;
+                    (0..7)
+                        .filter_map(|offset| monday.checked_add_days(Days::new(offset)))
+                        .filter(|day| {
+                            if self.by_day.is_empty() {
+                                day.weekday() == anchor.weekday()
+                            } else {
+                                self.matches_weekday(*day)
+                            }
+                        })
+                        .collect()
+                }
+                Frequency::Monthly => {
+                    let months = Months::new(u32::try_from(step).ok()?);
+                    let first = anchor.with_day(1)?.checked_add_months(months)?;
+                    self.days_in_month(first, anchor.day())
+                }
+                Frequency::Yearly => {
+                    let year = anchor.year().checked_add(i32::try_from(step).ok()?)?;
+                    let first = NaiveDate::from_ymd_opt(year, anchor.month(), 1)?;
+                    self.days_in_month(first, anchor.day())
+                }
+            };
+
+            candidates.sort();
+            if let Some(next) = candidates.into_iter().find(|day| *day > anchor) {
+                return match self.until {
+                    Some(until) if next > until => None,
+                    _ => Some(next),
+                };
+            }
+        }
+        None
+    }
+
+    fn matches_weekday(&self, day: NaiveDate) -> bool {
+        self.by_day.is_empty() || self.by_day.iter().any(|by| by.weekday == day.weekday())
+    }
+
+    fn matches_month_day(&self, day: NaiveDate) -> bool {
+        self.by_month_day.is_empty()
+            || self
+                .by_month_day
+                .iter()
+                .any(|by| resolve_month_day(day.with_day(1).unwrap(), *by) == Some(day))
+    }
+
+    /// Candidate days in the month starting at `first`: `BYMONTHDAY` and
+    /// `BYDAY` narrowing each other when both are set, the anchor's day of
+    /// month otherwise (skipping months that are too short)
+    fn days_in_month(&self, first: NaiveDate, anchor_day: u32) -> Vec<NaiveDate> {
+        let month_days: Vec<NaiveDate> = self
+            .by_month_day
+            .iter()
+            .filter_map(|by| resolve_month_day(first, *by))
+            .collect();
+        let weekdays: Vec<NaiveDate> = self
+            .by_day
+            .iter()
+            .flat_map(|by| weekdays_in_month(first, *by))
+            .collect();
+
+        match (self.by_month_day.is_empty(), self.by_day.is_empty()) {
+            (true, true) => first.with_day(anchor_day).into_iter().collect(),
+            (false, true) => month_days,
+            (true, false) => weekdays,
+            (false, false) => month_days
+                .into_iter()
+                .filter(|day| weekdays.contains(day))
+                .collect(),
+        }
+    }
+}
+
+fn parse_weekday_num(value: &str) -> Option<WeekdayNum> {
+    let split = value.len().checked_sub(2)?;
+    let (ordinal, weekday) = value.split_at(split);
+    let weekday = match weekday {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let ordinal = match ordinal {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .parse::<i32>()
+                .ok()
+                .filter(|n| *n != 0 && n.abs() <= 5)?,
+        ),
+    };
+    Some(WeekdayNum { ordinal, weekday })
+}
+
+fn last_day_of_month(first: NaiveDate) -> NaiveDate {
+    first
+        .checked_add_months(Months::new(1))
+        .map_or(NaiveDate::MAX, |next| next - Duration::days(1))
+}
+
+/// Resolve a `BYMONTHDAY` value, negative counting from the month's end
+fn resolve_month_day(first: NaiveDate, by: i32) -> Option<NaiveDate> {
+    let last = last_day_of_month(first).day() as i32;
+    let day = if by > 0 { by } else { last + 1 + by };
+    u32::try_from(day).ok().and_then(|day| first.with_day(day))
+}
+
+/// Days of the month matching a `BYDAY` entry, all of them without ordinal
+fn weekdays_in_month(first: NaiveDate, by: WeekdayNum) -> Vec<NaiveDate> {
+    let last = last_day_of_month(first);
+    let days: Vec<NaiveDate> = first
+        .iter_days()
+        .take_while(|day| *day <= last)
+        .filter(|day| day.weekday() == by.weekday)
+        .collect();
+
+    match by.ordinal {
+        None => days,
+        Some(n) if n > 0 => days.get(n as usize - 1).copied().into_iter().collect(),
+        Some(n) => days
+            .len()
+            .checked_sub(n.unsigned_abs() as usize)
+            .and_then(|i| days.get(i).copied())
+            .into_iter()
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn next_after() {
+        let cases = [
+            // Months without a 31st are skipped
+            (
+                "FREQ=MONTHLY;BYMONTHDAY=31",
+                "2026-01-31",
+                Some("2026-03-31"),
+            ),
+            (
+                "FREQ=MONTHLY;BYMONTHDAY=-1",
+                "2026-01-31",
+                Some("2026-02-28"),
+            ),
+            ("FREQ=MONTHLY;BYDAY=-1FR", "2026-10-30", Some("2026-11-27")),
+            ("FREQ=MONTHLY;BYDAY=1MO", "2026-10-05", Some("2026-11-02")),
+            (
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR",
+                "2026-10-19",
+                Some("2026-10-23"),
+            ),
+            (
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR",
+                "2026-10-23",
+                Some("2026-11-02"),
+            ),
+            ("FREQ=WEEKLY", "2026-10-17", Some("2026-10-24")),
+            ("FREQ=DAILY;INTERVAL=3", "2026-10-30", Some("2026-11-02")),
+            ("FREQ=DAILY;BYDAY=SA,SU", "2026-10-18", Some("2026-10-24")),
+            ("FREQ=YEARLY", "2024-02-29", Some("2028-02-29")),
+            ("FREQ=YEARLY;BYDAY=4TH", "2026-11-26", Some("2027-11-25")),
+            ("FREQ=YEARLY;BYDAY=MO", "2026-10-26", Some("2027-10-04")),
+            (
+                "FREQ=DAILY;UNTIL=20261020",
+                "2026-10-19",
+                Some("2026-10-20"),
+            ),
+            ("FREQ=DAILY;UNTIL=20261020T120000Z", "2026-10-20", None),
+            ("FREQ=WEEKLY;UNTIL=20261023", "2026-10-17", None),
+            // Never matches, given up after MAX_PERIODS
+            ("FREQ=YEARLY;BYMONTHDAY=30", "2026-02-01", None),
+            ("FREQ=MONTHLY;BYMONTHDAY=1;BYDAY=5MO", "2026-10-01", None),
+        ];
+        for (rule, anchor, expected) in cases {
+            let next = Rule::parse(rule).unwrap().next_after(day(anchor));
+            assert_eq!(next, expected.map(day), "{rule} after {anchor}");
+        }
+    }
+
+    #[test]
+    fn count_limits_occurrences() {
+        let rule = Rule::parse("RRULE:FREQ=DAILY;COUNT=3").unwrap();
+        assert!(rule.allows_occurrence(3));
+        assert!(!rule.allows_occurrence(4));
+        assert!(Rule::parse("FREQ=DAILY")
+            .unwrap()
+            .allows_occurrence(i64::MAX));
+    }
+
+    #[test]
+    fn rejects_unsupported_rules() {
+        for rule in [
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;INTERVAL=1001",
+            "FREQ=DAILY;COUNT=2;UNTIL=20261231",
+            "FREQ=WEEKLY;BYDAY=2MO",
+            "FREQ=WEEKLY;BYMONTHDAY=1",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=MONTHLY;BYDAY=6MO",
+            "FREQ=MONTHLY;BYMONTH=3",
+        ] {
+            assert!(Rule::parse(rule).is_err(), "{rule:?}");
+        }
+    }
+
+    #[test]
+    fn resolves_month_days() {
+        assert_eq!(
+            resolve_month_day(day("2026-02-01"), -1),
+            Some(day("2026-02-28"))
+        );
+        assert_eq!(
+            resolve_month_day(day("2024-02-01"), -1),
+            Some(day("2024-02-29"))
+        );
+        assert_eq!(
+            resolve_month_day(day("2026-10-01"), -31),
+            Some(day("2026-10-01"))
+        );
+        assert_eq!(resolve_month_day(day("2026-02-01"), 31), None);
+        assert_eq!(resolve_month_day(day("2026-02-01"), -30), None);
+    }
+
+    #[test]
+    fn finds_weekdays_in_month() {
+        let first = day("2026-10-01");
+        let friday = |ordinal| WeekdayNum {
+            ordinal,
+            weekday: Weekday::Fri,
+        };
+        assert_eq!(weekdays_in_month(first, friday(None)).len(), 5);
+        assert_eq!(
+            weekdays_in_month(first, friday(Some(5))),
+            [day("2026-10-30")]
+        );
+        assert_eq!(
+            weekdays_in_month(first, friday(Some(-5))),
+            [day("2026-10-02")]
+        );
+        assert_eq!(
+            weekdays_in_month(first, friday(Some(-1))),
+            [day("2026-10-30")]
+        );
+        let fifth_monday = WeekdayNum {
+            ordinal: Some(5),
+            weekday: Weekday::Mon,
+        };
+        assert!(weekdays_in_month(first, fifth_monday).is_empty());
+    }
+
+    #[test]
+    fn ends_at_the_last_representable_day() {
+        let end = NaiveDate::MAX - Duration::days(3);
+        for rule in [
+            "FREQ=DAILY;INTERVAL=1000",
+            "FREQ=WEEKLY;INTERVAL=1000",
+            "FREQ=MONTHLY;INTERVAL=1000",
+            "FREQ=YEARLY;INTERVAL=1000",
+        ] {
+            assert_eq!(Rule::parse(rule).unwrap().next_after(end), None, "{rule}");
+        }
+        let daily = Rule::parse("FREQ=DAILY").unwrap();
+        assert_eq!(daily.next_after(NaiveDate::MAX), None);
+    }
+}
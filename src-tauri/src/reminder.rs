@@ -0,0 +1,148 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::date_filter::Zone;
+use crate::Result;
+
+/// Furthest a relative reminder may fire from the due time, either way
+pub const MAX_OFFSET_MINUTES: i64 = 366 * 24 * 60;
+
+/// When a reminder fires, as given by the frontend
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "lowercase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ReminderTrigger {
+    /// At a fixed instant
+    Absolute { at: DateTime<Utc> },
+    /// Relative to the task's due time, negative for before it
+    Relative { offset_minutes: i64 },
+}
+
+impl ReminderTrigger {
+    /// Reject relative offsets beyond `MAX_OFFSET_MINUTES`
+    pub fn check(&self) -> Result<()> {
+        match *self {
+            ReminderTrigger::Relative { offset_minutes }
+                if !(-MAX_OFFSET_MINUTES..=MAX_OFFSET_MINUTES).contains(&offset_minutes) =>
+            {
+                Err(crate::Error::InvalidReminderOffset(offset_minutes))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Reminder {
+    pub id: String,
+    pub task_id: String,
+    pub remind_at: Option<DateTime<Utc>>,
+    pub offset_minutes: Option<i64>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An undelivered reminder of an open task, joined with what is needed to
+/// work out when it fires
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PendingReminder {
+    pub id: String,
+    pub task_id: String,
+    pub task_name: String,
+    pub remind_at: Option<DateTime<Utc>>,
+    pub offset_minutes: Option<i64>,
+    pub due_date: Option<NaiveDate>,
+    pub due_time: Option<NaiveTime>,
+    /// When the reminder fires, filled in by the repository
+    #[sqlx(skip)]
+    pub fires_at: Option<DateTime<Utc>>,
+}
+
+impl PendingReminder {
+    /// Instant the reminder fires. Relative reminders count from the due
+    /// time, or from the start of the due day for all-day tasks, and never
+    /// fire for someday tasks, nor when the offset leaves the calendar
+    pub fn fire_at(&self, zone: &Zone) -> Option<DateTime<Utc>> {
+        if let Some(remind_at) = self.remind_at {
+            return Some(remind_at);
+        }
+        let due = self
+            .due_date?
+            .and_time(self.due_time.unwrap_or(NaiveTime::MIN));
+        zone.to_utc(due)
+            .checked_add_signed(TimeDelta::try_minutes(self.offset_minutes?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(
+        remind_at: Option<&str>,
+        offset_minutes: Option<i64>,
+        due: Option<(NaiveDate, Option<NaiveTime>)>,
+    ) -> PendingReminder {
+        PendingReminder {
+            id: "r".to_string(),
+            task_id: "t".to_string(),
+            task_name: "Dentist".to_string(),
+            remind_at: remind_at.map(|at| at.parse().unwrap()),
+            offset_minutes,
+            due_date: due.map(|(date, _)| date),
+            due_time: due.and_then(|(_, time)| time),
+            fires_at: None,
+        }
+    }
+
+    #[test]
+    fn fires_relative_to_the_due_time() {
+        let zone = Zone::parse("Europe/Berlin").unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 10, 20).unwrap();
+        let at = |s: &str| Some(s.parse::<DateTime<Utc>>().unwrap());
+
+        let timed = pending(
+            None,
+            Some(-15),
+            Some((day, NaiveTime::from_hms_opt(9, 30, 0))),
+        );
+        assert_eq!(timed.fire_at(&zone), at("2026-10-20T07:15:00Z"));
+        let all_day = pending(None, Some(0), Some((day, None)));
+        assert_eq!(all_day.fire_at(&zone), at("2026-10-19T22:00:00Z"));
+        let someday = pending(None, Some(0), None);
+        assert_eq!(someday.fire_at(&zone), None);
+        let absolute = pending(Some("2026-10-18T08:00:00Z"), None, None);
+        assert_eq!(absolute.fire_at(&zone), at("2026-10-18T08:00:00Z"));
+    }
+
+    #[test]
+    fn offsets_past_the_calendar_never_fire() {
+        let zone = Zone::parse("UTC").unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 10, 20).unwrap();
+        for offset_minutes in [i64::MAX, i64::MIN] {
+            assert_eq!(
+                pending(None, Some(offset_minutes), Some((day, None))).fire_at(&zone),
+                None
+            );
+        }
+        let last = pending(None, Some(MAX_OFFSET_MINUTES), Some((NaiveDate::MAX, None)));
+        assert_eq!(last.fire_at(&zone), None);
+    }
+
+    #[test]
+    fn offsets_stay_within_a_year() {
+        for offset_minutes in [0, MAX_OFFSET_MINUTES, -MAX_OFFSET_MINUTES] {
+            assert!(ReminderTrigger::Relative { offset_minutes }.check().is_ok());
+        }
+        for offset_minutes in [MAX_OFFSET_MINUTES + 1, i64::MIN] {
+            assert!(matches!(
+                ReminderTrigger::Relative { offset_minutes }.check(),
+                Err(crate::Error::InvalidReminderOffset(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,3452 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use sqlx::sqlite::{SqliteConnection, SqlitePool};
+use sqlx::types::Json;
+use sqlx::QueryBuilder;
+use uuid::Uuid;
+
+use crate::audit::{self, HistoryStep, Origin, RescheduledTask, TaskEvent, TaskHistory};
+use crate::backup::BackupSettings;
+use crate::date_filter::{self, DateFilter, DateFilterEntry, DayCount, DueRange, Zone};
+use crate::export::{
+    self, Document, ExportFilter, ExportedTask, IdMode, ImportOptions, ImportReport,
+};
+use crate::history::{Operation, OperationKind, Snapshot, TaskRow, HISTORY_LIMIT};
+use crate::recurrence::{RepeatFrom, Rule};
+use crate::reminder::{PendingReminder, Reminder, ReminderTrigger};
+use crate::search::{self, PathEntry, SearchHit, MATCH_END, MATCH_START};
+use crate::tag::{Tag, TagFilter, TagMatch};
+use crate::task::{to_sql_timestamp, Priority, Task, TaskSort, TaskUpdate};
+use crate::trash::{TrashEntry, TrashSettings};
+use crate::Result;
+
+const TASK_COLUMNS: &str = "t.id, t.name, t.parent_id, t.completed, t.completed_at, \
+    t.created_at, t.due_date, t.due_time, t.task_order, t.priority, \
+    t.recurrence, t.repeat_from, t.occurrence, \
+    COALESCE(sc.completed_subtasks, 0) AS completed_subtasks, \
+    COALESCE(sc.total_subtasks, 0) AS total_subtasks";
+
+/// Unfiltered listing: by due day with someday tasks last
+const ALL_TASKS_ORDER: &str = "t.due_date IS NULL, t.due_date ASC, \
+    t.due_time IS NULL, t.due_time ASC, \
+    t.completed ASC, t.parent_id, t.task_order ASC, t.created_at ASC";
+
+/// Filtered by due day: overdue tasks first, `$3` being today
+const DUE_RANGE_ORDER: &str = "CASE WHEN t.due_date < $3 AND t.completed = 0 THEN 0 ELSE 1 END, \
+    t.parent_id, t.task_order ASC, \
+    CASE WHEN t.due_date < $3 AND t.completed = 0 THEN t.due_date END DESC, \
+    t.due_time IS NULL, t.due_time ASC, t.created_at ASC";
+
+/// Single source of truth for reading and mutating the `tasks` table. Clones
+/// share the pool, so swapping it for another profile affects all of them
+#[derive(Clone)]
+pub struct TaskRepository {
+    pool: Arc<RwLock<SqlitePool>>,
+    /// What changes made through this clone are logged as coming from
+    origin: Origin,
+}
+
+impl TaskRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool: Arc::new(RwLock::new(pool)),
+            origin: Origin::default(),
+        }
+    }
+
+    /// A clone sharing the pool whose changes are logged as made from `origin`
+    pub fn with_origin(&self, origin: Origin) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            origin,
+        }
+    }
+
+    /// The pool of the open profile
+    pub fn pool(&self) -> SqlitePool {
+        self.pool
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Point every clone at `pool`, returning the previous one for the caller
+    /// to close. Queries already running finish on the old pool
+    pub fn swap_pool(&self, pool: SqlitePool) -> SqlitePool {
+        let mut current = self.pool.write().unwrap_or_else(PoisonError::into_inner);
+        std::mem::replace(&mut current, pool)
+    }
+
+    /// Timezone date filters are computed in, the system one unless set
+    pub async fn timezone(&self) -> Result<Zone> {
+        let name: Option<String> =
+            sqlx::query_scalar("SELECT value FROM settings WHERE key = 'timezone'")
+                .fetch_optional(&self.pool())
+                .await?;
+        name.map_or(Ok(Zone::Local), |name| Zone::parse(&name))
+    }
+
+    /// Pin date filters to an IANA timezone, or follow the system with `None`
+    pub async fn set_timezone(&self, name: Option<&str>) -> Result<Zone> {
+        let Some(name) = name else {
+            sqlx::query("DELETE FROM settings WHERE key = 'timezone'")
+                .execute(&self.pool())
+                .await?;
+            return Ok(Zone::Local);
+        };
+
+        let zone = Zone::parse(name)?;
+        sqlx::query(
+            "INSERT INTO settings (key, value) VALUES ('timezone', $1)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        )
+        .bind(zone.name())
+        .execute(&self.pool())
+        .await?;
+        Ok(zone)
+    }
+
+    /// Backup interval and retention, the defaults unless changed
+    pub async fn backup_settings(&self) -> Result<BackupSettings> {
+        let value: Option<String> =
+            sqlx::query_scalar("SELECT value FROM settings WHERE key = 'backup'")
+                .fetch_optional(&self.pool())
+                .await?;
+        Ok(value
+            .and_then(|value| serde_json::from_str(&value).ok())
+            .unwrap_or_default())
+    }
+
+    pub async fn set_backup_settings(&self, settings: &BackupSettings) -> Result<()> {
+        let value = serde_json::to_string(settings).expect("settings serialize to JSON");
+        sqlx::query(
+            "INSERT INTO settings (key, value) VALUES ('backup', $1)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        )
+        .bind(value)
+        .execute(&self.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// How long deleted tasks are kept, the default unless changed
+    pub async fn trash_settings(&self) -> Result<TrashSettings> {
+        let value: Option<String> =
+            sqlx::query_scalar("SELECT value FROM settings WHERE key = 'trash'")
+                .fetch_optional(&self.pool())
+                .await?;
+        Ok(value
+            .and_then(|value| serde_json::from_str(&value).ok())
+            .unwrap_or_default())
+    }
+
+    pub async fn set_trash_settings(&self, settings: &TrashSettings) -> Result<()> {
+        let value = serde_json::to_string(settings).expect("settings serialize to JSON");
+        sqlx::query(
+            "INSERT INTO settings (key, value) VALUES ('trash', $1)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        )
+        .bind(value)
+        .execute(&self.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// Current wall-clock time in the configured timezone
+    async fn now(&self) -> Result<NaiveDateTime> {
+        Ok(self.timezone().await?.local_time(Utc::now()))
+    }
+
+    /// Sidebar filters with per-day task counts, plus someday tasks
+    pub async fn date_filters(&self) -> Result<Vec<DateFilterEntry>> {
+        let today = self.now().await?.date();
+        let rows: Vec<(Option<NaiveDate>, i64, i64)> = sqlx::query_as(
+            "SELECT due_date, COUNT(*), SUM(CASE WHEN completed = 1 THEN 1 ELSE 0 END)
+             FROM tasks
+             WHERE deleted_at IS NULL
+             GROUP BY due_date",
+        )
+        .fetch_all(&self.pool())
+        .await?;
+
+        let mut counts: BTreeMap<NaiveDate, DayCount> = BTreeMap::new();
+        let mut someday = DayCount::default();
+        for (due_date, total, completed) in rows {
+            let count = DayCount { total, completed };
+            match due_date {
+                Some(due_date) => {
+                    counts.insert(due_date, count);
+                }
+                None => someday = count,
+            }
+        }
+
+        Ok(date_filter::sidebar_entries(today, &counts, someday))
+    }
+
+    /// A single task with its subtask counts
+    pub async fn task(&self, id: &str) -> Result<Task> {
+        let mut conn = self.pool().acquire().await?;
+        let tasks = fetch_tasks(&mut conn, &[id.to_string()]).await?;
+        drop(conn);
+        mark_overdue(tasks, self.now().await?)
+            .pop()
+            .ok_or_else(|| crate::Error::TaskNotFound(id.to_string()))
+    }
+
+    /// Full id of the one task whose id starts with `prefix`, so ids can be
+    /// typed abbreviated. An exact match wins over longer ones
+    pub async fn resolve_id(&self, prefix: &str) -> Result<String> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM tasks
+             WHERE substr(id, 1, length($1)) = $1 AND deleted_at IS NULL
+             ORDER BY id = $1 DESC LIMIT 2",
+        )
+        .bind(prefix)
+        .fetch_all(&self.pool())
+        .await?;
+        match ids.as_slice() {
+            [] => Err(crate::Error::TaskNotFound(prefix.to_string())),
+            [id] | [id, _] if id == prefix => Ok(id.clone()),
+            [id] => Ok(id.clone()),
+            _ => Err(crate::Error::AmbiguousId(prefix.to_string())),
+        }
+    }
+
+    /// Load the tasks matching the date filter and, when given, the tag
+    /// filter, together with all of their ancestors
+    pub async fn load_tasks(
+        &self,
+        filter: &DateFilter,
+        tags: Option<&TagFilter>,
+        sort: TaskSort,
+    ) -> Result<Vec<Task>> {
+        let mut tasks = self.query_tasks(filter, tags).await?;
+        if sort == TaskSort::Priority {
+            // Stable, so ties keep the order of the query
+            tasks.sort_by_key(|task| (Reverse(task.priority), !task.overdue, task.order));
+        }
+        Ok(tasks)
+    }
+
+    async fn query_tasks(
+        &self,
+        filter: &DateFilter,
+        tags: Option<&TagFilter>,
+    ) -> Result<Vec<Task>> {
+        let now = self.now().await?;
+        let today = now.date();
+        let range = filter.due_range(today);
+        let tags = tags.filter(|tags| !tags.tag_ids.is_empty());
+        let mut tag_ids: Vec<&String> = tags.iter().flat_map(|tags| &tags.tag_ids).collect();
+        tag_ids.sort();
+        tag_ids.dedup();
+
+        if range.is_none() && tags.is_none() {
+            let sql = format!(
+                "WITH subtask_counts AS (
+                    SELECT
+                        t.id,
+                        COUNT(s.id) AS total_subtasks,
+                        SUM(CASE WHEN s.completed = 1 THEN 1 ELSE 0 END) AS completed_subtasks
+                    FROM tasks t
+                    LEFT JOIN tasks s ON s.parent_id = t.id AND s.deleted_at IS NULL
+                    GROUP BY t.id
+                )
+                SELECT {TASK_COLUMNS}
+                FROM tasks t
+                LEFT JOIN subtask_counts sc ON t.id = sc.id
+                WHERE t.deleted_at IS NULL
+                ORDER BY {ALL_TASKS_ORDER}"
+            );
+            let tasks = sqlx::query_as(&sql).fetch_all(&self.pool()).await?;
+            return Ok(mark_overdue(tasks, now));
+        }
+
+        // $1/$2 bound the due day (both NULL selecting someday tasks), $3 is
+        // today and $4 toggles whether incomplete overdue tasks are included.
+        // Tag ids follow from $5 on.
+        let tag_params = (0..tag_ids.len())
+            .map(|i| format!("${}", i + 5))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let matches = |alias: &str| {
+            let date_matches = due_range_matches(alias, range.as_ref());
+            let tag_matches = match tags.map(|tags| tags.mode) {
+                Some(TagMatch::Any) => format!(
+                    "EXISTS (SELECT 1 FROM task_tags tt \
+                     WHERE tt.task_id = {alias}.id AND tt.tag_id IN ({tag_params}))"
+                ),
+                Some(TagMatch::All) => format!(
+                    "(SELECT COUNT(DISTINCT tt.tag_id) FROM task_tags tt \
+                     WHERE tt.task_id = {alias}.id AND tt.tag_id IN ({tag_params})) = {}",
+                    tag_ids.len()
+                ),
+                None => "1".to_string(),
+            };
+            format!("{alias}.deleted_at IS NULL AND {date_matches} AND {tag_matches}")
+        };
+        let order = match range {
+            Some(_) => DUE_RANGE_ORDER,
+            None => ALL_TASKS_ORDER,
+        };
+        let sql = format!(
+            "WITH RECURSIVE
+            -- Find all tasks that match the date and tag criteria
+            matching_tasks AS (
+                SELECT t.id, t.parent_id FROM tasks t WHERE {task_matches}
+            ),
+            -- Recursively find all parent tasks
+            parent_hierarchy AS (
+                SELECT id, parent_id FROM matching_tasks
+                UNION
+                SELECT t.id, t.parent_id FROM tasks t
+                INNER JOIN parent_hierarchy ph ON t.id = ph.parent_id
+            ),
+            -- Only count subtasks that match the criteria
+            subtask_counts AS (
+                SELECT
+                    t.id,
+                    COUNT(s.id) AS total_subtasks,
+                    SUM(CASE WHEN s.completed = 1 THEN 1 ELSE 0 END) AS completed_subtasks
+                FROM tasks t
+                LEFT JOIN tasks s ON s.parent_id = t.id AND {subtask_matches}
+                GROUP BY t.id
+            )
+            SELECT {TASK_COLUMNS}
+            FROM tasks t
+            LEFT JOIN subtask_counts sc ON t.id = sc.id
+            WHERE t.id IN (SELECT id FROM parent_hierarchy)
+            ORDER BY {order}",
+            task_matches = matches("t"),
+            subtask_matches = matches("s"),
+        );
+
+        let mut query = sqlx::query_as(&sql)
+            .bind(range.and_then(|range| range.start))
+            .bind(range.and_then(|range| range.end))
+            .bind(today)
+            .bind(range.is_some_and(|range| range.include_overdue));
+        for tag_id in tag_ids {
+            query = query.bind(tag_id);
+        }
+        let tasks = query.fetch_all(&self.pool()).await?;
+        Ok(mark_overdue(tasks, now))
+    }
+
+    /// Full-text search over names and notes, best matches first, limited to
+    /// tasks matching `filter`
+    pub async fn search_tasks(
+        &self,
+        query: &str,
+        filter: &DateFilter,
+        limit: u32,
+    ) -> Result<Vec<SearchHit>> {
+        let Some(fts_query) = search::fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let now = self.now().await?;
+        let today = now.date();
+        let range = filter.due_range(today);
+
+        // Name weighs more than notes; task_id is not indexed
+        let sql = format!(
+            "SELECT
+                tasks_fts.task_id,
+                highlight(tasks_fts, 1, '{MATCH_START}', '{MATCH_END}'),
+                CASE WHEN instr(highlight(tasks_fts, 2, '{MATCH_START}', ''), '{MATCH_START}') > 0
+                    THEN snippet(tasks_fts, 2, '{MATCH_START}', '{MATCH_END}', '…', 16)
+                END,
+                bm25(tasks_fts, 0.0, 10.0, 1.0) AS rank
+             FROM tasks_fts
+             INNER JOIN tasks t ON t.id = tasks_fts.task_id
+             WHERE tasks_fts MATCH $5 AND t.deleted_at IS NULL AND {date_matches}
+             ORDER BY rank
+             LIMIT $6",
+            date_matches = due_range_matches("t", range.as_ref()),
+        );
+        let rows: Vec<(String, String, Option<String>, f64)> = sqlx::query_as(&sql)
+            .bind(range.and_then(|range| range.start))
+            .bind(range.and_then(|range| range.end))
+            .bind(today)
+            .bind(range.is_some_and(|range| range.include_overdue))
+            .bind(&fts_query)
+            .bind(limit)
+            .fetch_all(&self.pool())
+            .await?;
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self.pool().acquire().await?;
+        let ids: Vec<String> = rows.iter().map(|(id, ..)| id.clone()).collect();
+        let mut tasks = mark_overdue(fetch_tasks(&mut conn, &ids).await?, now);
+
+        let mut hits = Vec::with_capacity(rows.len());
+        for (id, name, notes, rank) in rows {
+            let Some(index) = tasks.iter().position(|task| task.id == id) else {
+                continue;
+            };
+            let task = tasks.swap_remove(index);
+            let path = ancestor_path(&mut conn, task.parent_id.as_deref()).await?;
+            hits.push(SearchHit {
+                task,
+                name_html: search::highlight_html(&name),
+                notes_html: notes.as_deref().map(search::highlight_html),
+                rank,
+                path,
+            });
+        }
+        Ok(hits)
+    }
+
+    /// Create a task due on `due_date`, optionally at `due_time`, or a
+    /// someday task when both are `None`
+    pub async fn create_task(
+        &self,
+        name: &str,
+        parent_id: Option<&str>,
+        due_date: Option<NaiveDate>,
+        due_time: Option<NaiveTime>,
+    ) -> Result<Task> {
+        check_due(due_date, due_time)?;
+        let now = Utc::now();
+        let mut tx = self.pool().begin().await?;
+        // A trashed parent would take the new task along when purged
+        if let Some(parent_id) = parent_id {
+            check_tasks_exist(&mut tx, &[parent_id.to_string()]).await?;
+        }
+        let lineage = lineage_ids(&mut tx, parent_id).await?;
+        let before = snapshot(&mut tx, &lineage).await?;
+
+        let max_order: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(task_order), -1) FROM tasks WHERE parent_id IS $1",
+        )
+        .bind(parent_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let task = Task {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            parent_id: parent_id.map(str::to_string),
+            completed: false,
+            completed_at: None,
+            created_at: now,
+            due_date,
+            due_time,
+            order: max_order + 1,
+            priority: Priority::None,
+            recurrence: None,
+            repeat_from: RepeatFrom::Due,
+            occurrence: 1,
+            completed_subtasks: 0,
+            total_subtasks: 0,
+            overdue: false,
+        };
+
+        sqlx::query(
+            "INSERT INTO tasks (id, name, parent_id, completed, completed_at, created_at, due_date, due_time, task_order)
+             VALUES ($1, $2, $3, FALSE, NULL, $4, $5, $6, $7)",
+        )
+        .bind(&task.id)
+        .bind(&task.name)
+        .bind(&task.parent_id)
+        .bind(to_sql_timestamp(&task.created_at))
+        .bind(task.due_date)
+        .bind(task.due_time)
+        .bind(task.order)
+        .execute(&mut *tx)
+        .await?;
+
+        // A new open subtask reopens a completed parent
+        if let Some(parent_id) = parent_id {
+            refresh_ancestors(&mut tx, parent_id).await?;
+        }
+
+        let created = [task.id.clone()];
+        record(
+            &mut tx,
+            self.origin,
+            OperationKind::Create,
+            &created,
+            before,
+            &created,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Task {
+            overdue: task.is_overdue(self.now().await?),
+            ..task
+        })
+    }
+
+    pub async fn rename_task(&self, id: &str, name: &str) -> Result<()> {
+        let ids = [id.to_string()];
+        let mut tx = self.pool().begin().await?;
+        let before = snapshot(&mut tx, &ids).await?;
+
+        let result = sqlx::query("UPDATE tasks SET name = $1 WHERE id = $2 AND deleted_at IS NULL")
+            .bind(name)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(crate::Error::TaskNotFound(id.to_string()));
+        }
+        record(
+            &mut tx,
+            self.origin,
+            OperationKind::Rename,
+            &ids,
+            before,
+            &[],
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Markdown notes of a task, empty when it has none
+    pub async fn notes(&self, id: &str) -> Result<String> {
+        sqlx::query_scalar("SELECT notes FROM tasks WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&self.pool())
+            .await?
+            .ok_or_else(|| crate::Error::TaskNotFound(id.to_string()))
+    }
+
+    pub async fn set_notes(&self, id: &str, notes: &str) -> Result<()> {
+        let mut tx = self.pool().begin().await?;
+        let before = snapshot(&mut tx, &[id.to_string()]).await?;
+        let result =
+            sqlx::query("UPDATE tasks SET notes = $1 WHERE id = $2 AND deleted_at IS NULL")
+                .bind(notes)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(crate::Error::TaskNotFound(id.to_string()));
+        }
+        log_changes_since(&mut tx, self.origin, &before).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn set_priority(&self, ids: &[String], priority: Priority) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.pool().begin().await?;
+        check_tasks_exist(&mut tx, ids).await?;
+        let before = snapshot(&mut tx, ids).await?;
+
+        let mut query = QueryBuilder::new("UPDATE tasks SET priority = ");
+        query.push_bind(priority).push(" WHERE id IN (");
+        push_ids(&mut query, ids);
+        query.build().execute(&mut *tx).await?;
+
+        log_changes_since(&mut tx, self.origin, &before).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Make a task recur by an RRULE, or a one-off task with `None`,
+    /// restarting its series
+    pub async fn set_recurrence(
+        &self,
+        id: &str,
+        rule: Option<&str>,
+        repeat_from: RepeatFrom,
+    ) -> Result<()> {
+        let rule = rule.map(str::trim).filter(|rule| !rule.is_empty());
+        if let Some(rule) = rule {
+            Rule::parse(rule)?;
+        }
+
+        let mut tx = self.pool().begin().await?;
+        let before = snapshot(&mut tx, &[id.to_string()]).await?;
+        let result = sqlx::query(
+            "UPDATE tasks SET recurrence = $1, repeat_from = $2, occurrence = 1
+             WHERE id = $3 AND deleted_at IS NULL",
+        )
+        .bind(rule)
+        .bind(repeat_from)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(crate::Error::TaskNotFound(id.to_string()));
+        }
+        log_changes_since(&mut tx, self.origin, &before).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn reminders(&self, task_id: &str) -> Result<Vec<Reminder>> {
+        Ok(sqlx::query_as(
+            "SELECT id, task_id, remind_at, offset_minutes, delivered_at, created_at
+             FROM reminders
+             WHERE task_id = $1
+             ORDER BY remind_at IS NULL, remind_at, offset_minutes, created_at",
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool())
+        .await?)
+    }
+
+    pub async fn add_reminder(&self, task_id: &str, trigger: ReminderTrigger) -> Result<Reminder> {
+        trigger.check()?;
+        let (remind_at, offset_minutes) = match trigger {
+            ReminderTrigger::Absolute { at } => (Some(at), None),
+            ReminderTrigger::Relative { offset_minutes } => (None, Some(offset_minutes)),
+        };
+        let reminder = Reminder {
+            id: Uuid::new_v4().to_string(),
+            task_id: task_id.to_string(),
+            remind_at,
+            offset_minutes,
+            delivered_at: None,
+            created_at: Utc::now(),
+        };
+
+        let mut tx = self.pool().begin().await?;
+        check_tasks_exist(&mut tx, &[task_id.to_string()]).await?;
+        sqlx::query(
+            "INSERT INTO reminders (id, task_id, remind_at, offset_minutes, delivered_at, created_at)
+             VALUES ($1, $2, $3, $4, NULL, $5)",
+        )
+        .bind(&reminder.id)
+        .bind(&reminder.task_id)
+        .bind(reminder.remind_at.as_ref().map(to_sql_timestamp))
+        .bind(reminder.offset_minutes)
+        .bind(to_sql_timestamp(&reminder.created_at))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(reminder)
+    }
+
+    pub async fn delete_reminder(&self, id: &str) -> Result<()> {
+        let result = sqlx::query("DELETE FROM reminders WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(crate::Error::ReminderNotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Undelivered reminders of open tasks that should have fired by `now`,
+    /// oldest first, including those missed while the app was closed
+    pub async fn due_reminders(&self, now: DateTime<Utc>) -> Result<Vec<PendingReminder>> {
+        let zone = self.timezone().await?;
+        let pending: Vec<PendingReminder> = sqlx::query_as(
+            "SELECT r.id, r.task_id, t.name AS task_name, r.remind_at, r.offset_minutes,
+                t.due_date, t.due_time
+             FROM reminders r
+             INNER JOIN tasks t ON t.id = r.task_id
+             WHERE r.delivered_at IS NULL
+                AND t.completed = 0
+                AND t.deleted_at IS NULL
+                AND (r.remind_at IS NULL OR r.remind_at <= $1)",
+        )
+        .bind(to_sql_timestamp(&now))
+        .fetch_all(&self.pool())
+        .await?;
+
+        let mut due: Vec<PendingReminder> = pending
+            .into_iter()
+            .filter_map(|mut reminder| {
+                let fires_at = reminder.fire_at(&zone).filter(|at| *at <= now)?;
+                reminder.fires_at = Some(fires_at);
+                Some(reminder)
+            })
+            .collect();
+        due.sort_by_key(|reminder| reminder.fires_at);
+        Ok(due)
+    }
+
+    /// Record that a reminder was shown so it is not repeated after restart
+    pub async fn mark_reminder_delivered(&self, id: &str, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE reminders SET delivered_at = $1 WHERE id = $2")
+            .bind(to_sql_timestamp(&at))
+            .bind(id)
+            .execute(&self.pool())
+            .await?;
+        Ok(())
+    }
+
+    /// Reschedule tasks along with their incomplete descendants; clearing
+    /// the date turns them into someday tasks
+    pub async fn set_due_date(
+        &self,
+        ids: &[String],
+        due_date: Option<NaiveDate>,
+        due_time: Option<NaiveTime>,
+    ) -> Result<()> {
+        check_due(due_date, due_time)?;
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.pool().begin().await?;
+        check_tasks_exist(&mut tx, ids).await?;
+        let subtree_ids = subtree_ids(&mut tx, ids).await?;
+        let before = snapshot(&mut tx, &subtree_ids).await?;
+        write_due(&mut tx, ids, &subtree_ids, due_date, due_time).await?;
+
+        record(
+            &mut tx,
+            self.origin,
+            OperationKind::Reschedule,
+            ids,
+            before,
+            &[],
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Toggle a task and auto-complete or reopen its ancestors atomically,
+    /// returning every row whose completion changed. Completing a recurring
+    /// task also creates its next occurrence, which is returned too
+    pub async fn toggle_task(&self, id: &str) -> Result<Vec<Task>> {
+        let today = self.now().await?.date();
+        let mut tx = self.pool().begin().await?;
+        // Only the task and its ancestors change, besides new occurrences
+        let lineage = lineage_ids(&mut tx, Some(id)).await?;
+        let before = snapshot(&mut tx, &lineage).await?;
+
+        let (changed, created) = write_toggle(&mut tx, id, today).await?;
+        let tasks = fetch_tasks(&mut tx, &changed).await?;
+        record(
+            &mut tx,
+            self.origin,
+            OperationKind::Toggle,
+            &[id.to_string()],
+            before,
+            &created,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(mark_overdue(tasks, self.now().await?))
+    }
+
+    /// Apply every field of `update` to a task in one transaction, returning
+    /// the rows that changed, the task first. Name, due date and completion
+    /// changes make up one undo step, which then also covers notes and
+    /// priority. A due time is kept along with the due date unless given
+    pub async fn update_task(&self, id: &str, update: &TaskUpdate) -> Result<Vec<Task>> {
+        let today = self.now().await?.date();
+        let mut tx = self.pool().begin().await?;
+        let ids = [id.to_string()];
+        let current: Option<(Option<NaiveDate>, Option<NaiveTime>, bool)> = sqlx::query_as(
+            "SELECT due_date, due_time, completed FROM tasks WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((due_date, due_time, completed)) = current else {
+            return Err(crate::Error::TaskNotFound(id.to_string()));
+        };
+
+        let due = (update.due_date.is_some() || update.due_time.is_some()).then(|| {
+            let due_date = update.due_date.unwrap_or(due_date);
+            // A time kept from before goes along with a cleared date
+            let due_time = update.due_time.unwrap_or_else(|| due_date.and(due_time));
+            (due_date, due_time)
+        });
+        if let Some((due_date, due_time)) = due {
+            check_due(due_date, due_time)?;
+        }
+        let toggle = update.completed.is_some_and(|c| c != completed);
+
+        // The subtree moves with the due date, ancestors follow completion
+        let subtree = subtree_ids(&mut tx, &ids).await?;
+        let mut scope = lineage_ids(&mut tx, Some(id)).await?;
+        for id in &subtree {
+            if !scope.contains(id) {
+                scope.push(id.clone());
+            }
+        }
+        let before = snapshot(&mut tx, &scope).await?;
+
+        if let Some((due_date, due_time)) = due {
+            write_due(&mut tx, &ids, &subtree, due_date, due_time).await?;
+        }
+        if let Some(name) = &update.name {
+            sqlx::query("UPDATE tasks SET name = $1 WHERE id = $2")
+                .bind(name)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        if let Some(notes) = &update.notes {
+            sqlx::query("UPDATE tasks SET notes = $1 WHERE id = $2")
+                .bind(notes)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        if let Some(priority) = update.priority {
+            sqlx::query("UPDATE tasks SET priority = $1 WHERE id = $2")
+                .bind(priority)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let (changed, created) = if toggle {
+            write_toggle(&mut tx, id, today).await?
+        } else {
+            (ids.to_vec(), Vec::new())
+        };
+        let mut tasks = fetch_tasks(&mut tx, &changed).await?;
+        tasks.sort_by_key(|task| task.id != id);
+
+        let kind = match (update.name.is_some(), due.is_some(), toggle) {
+            (false, false, false) => None,
+            (true, false, false) => Some(OperationKind::Rename),
+            (false, true, false) => Some(OperationKind::Reschedule),
+            (false, false, true) => Some(OperationKind::Toggle),
+            _ => Some(OperationKind::Edit),
+        };
+        match kind {
+            Some(kind) => record(&mut tx, self.origin, kind, &ids, before, &created).await?,
+            None => log_changes_since(&mut tx, self.origin, &before).await?,
+        }
+        tx.commit().await?;
+        Ok(mark_overdue(tasks, self.now().await?))
+    }
+
+    /// Move the given tasks together with every descendant to the trash,
+    /// returning the number of rows trashed. They keep their parent and
+    /// order so a restore can put them back
+    pub async fn delete_subtree(&self, ids: &[String]) -> Result<u64> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let mut tx = self.pool().begin().await?;
+
+        let mut query = QueryBuilder::new(
+            "SELECT DISTINCT parent_id FROM tasks
+             WHERE parent_id IS NOT NULL AND deleted_at IS NULL AND id IN (",
+        );
+        push_ids(&mut query, ids);
+        let parent_ids: Vec<String> = query.build_query_scalar().fetch_all(&mut *tx).await?;
+
+        // Subtasks trashed before keep their own entry in the trash
+        let subtree_ids = subtree_ids(&mut tx, ids).await?;
+        if subtree_ids.is_empty() {
+            return Ok(0);
+        }
+        let mut scope = subtree_ids.clone();
+        for parent_id in &parent_ids {
+            scope.extend(lineage_ids(&mut tx, Some(parent_id)).await?);
+        }
+        let before = snapshot(&mut tx, &scope).await?;
+
+        // One timestamp for the whole batch tells it apart from subtasks
+        // trashed on their own
+        let mut query = QueryBuilder::new("UPDATE tasks SET deleted_at = ");
+        query
+            .push_bind(to_sql_timestamp(&Utc::now()))
+            .push(" WHERE id IN (");
+        push_ids(&mut query, &subtree_ids);
+        query.build().execute(&mut *tx).await?;
+        let removed = subtree_ids.len() as u64;
+
+        // Remaining siblings may now all be completed
+        for parent_id in parent_ids {
+            refresh_ancestors(&mut tx, &parent_id).await?;
+        }
+
+        record(
+            &mut tx,
+            self.origin,
+            OperationKind::Delete,
+            ids,
+            before,
+            &[],
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    /// Tasks in the trash, most recently deleted first. Subtasks deleted
+    /// along with a task are part of its entry rather than listed apart
+    pub async fn trash(&self) -> Result<Vec<TrashEntry>> {
+        let mut conn = self.pool().acquire().await?;
+        let sql = format!(
+            "WITH RECURSIVE
+            -- Trashed tasks that were not deleted as part of their parent
+            entries AS (
+                SELECT t.id, t.deleted_at FROM tasks t
+                LEFT JOIN tasks p ON p.id = t.parent_id
+                WHERE t.deleted_at IS NOT NULL AND p.deleted_at IS NOT t.deleted_at
+            ),
+            batches(entry_id, id, deleted_at) AS (
+                SELECT id, id, deleted_at FROM entries
+                UNION ALL
+                SELECT b.entry_id, t.id, b.deleted_at FROM tasks t
+                INNER JOIN batches b ON t.parent_id = b.id
+                WHERE t.deleted_at = b.deleted_at
+            ),
+            batch_sizes AS (
+                SELECT entry_id AS id, COUNT(*) AS task_count FROM batches GROUP BY entry_id
+            ),
+            subtask_counts AS (
+                SELECT
+                    s.parent_id AS id,
+                    COUNT(s.id) AS total_subtasks,
+                    SUM(CASE WHEN s.completed = 1 THEN 1 ELSE 0 END) AS completed_subtasks
+                FROM tasks s
+                INNER JOIN tasks p ON p.id = s.parent_id
+                WHERE s.deleted_at = p.deleted_at
+                GROUP BY s.parent_id
+            )
+            SELECT {TASK_COLUMNS}, t.deleted_at, bs.task_count,
+                p.id IS NOT NULL AND p.deleted_at IS NULL AS parent_live
+            FROM tasks t
+            INNER JOIN batch_sizes bs ON bs.id = t.id
+            LEFT JOIN subtask_counts sc ON t.id = sc.id
+            LEFT JOIN tasks p ON p.id = t.parent_id
+            ORDER BY t.deleted_at DESC, t.task_order ASC"
+        );
+        let rows: Vec<TrashRow> = sqlx::query_as(&sql).fetch_all(&mut *conn).await?;
+
+        let mut entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let path = if row.parent_live {
+                ancestor_path(&mut conn, row.task.parent_id.as_deref()).await?
+            } else {
+                Vec::new()
+            };
+            entries.push(TrashEntry {
+                task: row.task,
+                deleted_at: row.deleted_at,
+                task_count: row.task_count,
+                path,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Bring a trash entry back with the subtasks deleted along with it, at
+    /// its old place among its siblings. It goes to the end of the top level
+    /// when its parent has been deleted since
+    pub async fn restore_from_trash(&self, id: &str) -> Result<Task> {
+        let mut tx = self.pool().begin().await?;
+        let entry: Option<(Option<String>, String, i64, bool)> = sqlx::query_as(
+            "SELECT t.parent_id, t.deleted_at, t.task_order,
+                p.id IS NOT NULL AND p.deleted_at IS NULL
+             FROM tasks t
+             LEFT JOIN tasks p ON p.id = t.parent_id
+             WHERE t.id = $1 AND t.deleted_at IS NOT NULL AND p.deleted_at IS NOT t.deleted_at",
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((parent_id, deleted_at, order, parent_live)) = entry else {
+            return Err(crate::Error::TaskNotFound(id.to_string()));
+        };
+        // Its parent is gone when deleted since, and only the top level is left
+        let (parent_id, order) = match parent_id {
+            Some(_) if !parent_live => (None, None),
+            parent_id => (parent_id, Some(order)),
+        };
+
+        let batch: Vec<String> = sqlx::query_scalar(
+            "WITH RECURSIVE batch(id) AS (
+                SELECT $1
+                UNION
+                SELECT t.id FROM tasks t
+                INNER JOIN batch b ON t.parent_id = b.id
+                WHERE t.deleted_at = $2
+            )
+            SELECT id FROM batch",
+        )
+        .bind(id)
+        .bind(&deleted_at)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut scope = batch.clone();
+        let siblings: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM tasks WHERE parent_id IS $1 AND deleted_at IS NULL")
+                .bind(&parent_id)
+                .fetch_all(&mut *tx)
+                .await?;
+        scope.extend(siblings);
+        scope.extend(lineage_ids(&mut tx, parent_id.as_deref()).await?);
+        let before = snapshot(&mut tx, &scope).await?;
+
+        let mut query = QueryBuilder::new("UPDATE tasks SET deleted_at = NULL WHERE id IN (");
+        push_ids(&mut query, &batch);
+        query.build().execute(&mut *tx).await?;
+
+        let index = match order {
+            // Siblings may have been added or deleted meanwhile, so its place
+            // is after the ones that came before it
+            Some(order) => {
+                let preceding: i64 = sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM tasks
+                     WHERE parent_id IS $1 AND deleted_at IS NULL AND id != $2 AND task_order < $3",
+                )
+                .bind(&parent_id)
+                .bind(id)
+                .bind(order)
+                .fetch_one(&mut *tx)
+                .await?;
+                Some(preceding as usize)
+            }
+            None => {
+                sqlx::query("UPDATE tasks SET parent_id = NULL WHERE id = $1")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                None
+            }
+        };
+        let ids = [id.to_string()];
+        renumber_siblings(&mut tx, parent_id.as_deref(), &ids, index).await?;
+
+        // An open task reopens a completed parent
+        if let Some(parent_id) = &parent_id {
+            refresh_ancestors(&mut tx, parent_id).await?;
+        }
+
+        let task = fetch_tasks(&mut tx, &ids).await?.pop();
+        record(
+            &mut tx,
+            self.origin,
+            OperationKind::Restore,
+            &ids,
+            before,
+            &[],
+        )
+        .await?;
+        tx.commit().await?;
+        let task = task.ok_or_else(|| crate::Error::TaskNotFound(id.to_string()))?;
+        Ok(Task {
+            overdue: task.is_overdue(self.now().await?),
+            ..task
+        })
+    }
+
+    /// Permanently delete what was moved to the trash before `deleted_before`,
+    /// or all of it with `None`, returning the number of rows removed
+    pub async fn purge_trash(&self, deleted_before: Option<DateTime<Utc>>) -> Result<u64> {
+        let deleted_before = deleted_before.as_ref().map(to_sql_timestamp);
+        let mut tx = self.pool().begin().await?;
+        // The cascade takes whole subtrees along, whenever their rows were
+        // trashed. Counted first since cascades are not in rows_affected
+        let purged: i64 = sqlx::query_scalar(
+            "WITH RECURSIVE purged(id) AS (
+                SELECT id FROM tasks
+                WHERE deleted_at IS NOT NULL AND ($1 IS NULL OR deleted_at < $1)
+                UNION
+                SELECT t.id FROM tasks t
+                INNER JOIN purged p ON t.parent_id = p.id
+            )
+            SELECT COUNT(*) FROM purged",
+        )
+        .bind(&deleted_before)
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query(
+            "DELETE FROM tasks WHERE deleted_at IS NOT NULL AND ($1 IS NULL OR deleted_at < $1)",
+        )
+        .bind(&deleted_before)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(purged as u64)
+    }
+
+    /// Persist the given sibling order under `parent_id`
+    pub async fn reorder_tasks(&self, ids: &[String], parent_id: Option<&str>) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.pool().begin().await?;
+        let before = snapshot(&mut tx, ids).await?;
+
+        let mut query = QueryBuilder::new("UPDATE tasks SET task_order = CASE");
+        for (index, id) in ids.iter().enumerate() {
+            query
+                .push(" WHEN id = ")
+                .push_bind(id)
+                .push(" THEN ")
+                .push_bind(index as i64);
+        }
+        query.push(" END WHERE deleted_at IS NULL AND id IN (");
+        push_ids(&mut query, ids);
+        query.push(" AND parent_id IS ").push_bind(parent_id);
+        query.build().execute(&mut *tx).await?;
+
+        record(
+            &mut tx,
+            self.origin,
+            OperationKind::Reorder,
+            ids,
+            before,
+            &[],
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Re-parent tasks at `index` among the new siblings (appending when
+    /// `None`), renumbering both the old and new sibling groups
+    pub async fn move_tasks(
+        &self,
+        ids: &[String],
+        new_parent_id: Option<&str>,
+        index: Option<usize>,
+    ) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.pool().begin().await?;
+
+        let mut query = QueryBuilder::new(
+            "SELECT id, parent_id FROM tasks WHERE deleted_at IS NULL AND id IN (",
+        );
+        push_ids(&mut query, ids);
+        let moving: Vec<(String, Option<String>)> =
+            query.build_query_as().fetch_all(&mut *tx).await?;
+        if let Some(missing) = ids.iter().find(|id| !moving.iter().any(|(m, _)| m == *id)) {
+            return Err(crate::Error::TaskNotFound(missing.clone()));
+        }
+
+        // A task may not end up below itself, which would detach the branch
+        // from every root, nor below a trashed one
+        if let Some(new_parent_id) = new_parent_id {
+            check_tasks_exist(&mut tx, &[new_parent_id.to_string()]).await?;
+            let lineage: Vec<String> = sqlx::query_scalar(
+                "WITH RECURSIVE lineage(id, parent_id) AS (
+                    SELECT id, parent_id FROM tasks WHERE id = $1 AND deleted_at IS NULL
+                    UNION ALL
+                    SELECT t.id, t.parent_id FROM tasks t
+                    INNER JOIN lineage l ON t.id = l.parent_id
+                )
+                SELECT id FROM lineage",
+            )
+            .bind(new_parent_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            if let Some(id) = ids.iter().find(|id| lineage.contains(id)) {
+                return Err(crate::Error::Cycle {
+                    task_id: id.clone(),
+                    parent_id: new_parent_id.to_string(),
+                });
+            }
+        }
+
+        // Both sibling groups get renumbered and both lineages refreshed
+        let mut scope = ids.to_vec();
+        let parent_ids = moving
+            .iter()
+            .map(|(_, parent_id)| parent_id.as_deref())
+            .chain([new_parent_id]);
+        for parent_id in parent_ids {
+            let siblings: Vec<String> =
+                sqlx::query_scalar("SELECT id FROM tasks WHERE parent_id IS $1")
+                    .bind(parent_id)
+                    .fetch_all(&mut *tx)
+                    .await?;
+            scope.extend(siblings);
+            scope.extend(lineage_ids(&mut tx, parent_id).await?);
+        }
+        let before = snapshot(&mut tx, &scope).await?;
+
+        let mut query = QueryBuilder::new("UPDATE tasks SET parent_id = ");
+        query.push_bind(new_parent_id).push(" WHERE id IN (");
+        push_ids(&mut query, ids);
+        query.build().execute(&mut *tx).await?;
+
+        let mut old_parent_ids: Vec<Option<String>> = Vec::new();
+        for (_, parent_id) in moving {
+            if parent_id.as_deref() != new_parent_id && !old_parent_ids.contains(&parent_id) {
+                old_parent_ids.push(parent_id);
+            }
+        }
+
+        for parent_id in &old_parent_ids {
+            renumber_siblings(&mut tx, parent_id.as_deref(), &[], None).await?;
+        }
+        renumber_siblings(&mut tx, new_parent_id, ids, index).await?;
+
+        for parent_id in old_parent_ids
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .chain(new_parent_id)
+        {
+            refresh_ancestors(&mut tx, parent_id).await?;
+        }
+
+        record(&mut tx, self.origin, OperationKind::Move, ids, before, &[]).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Every tag with the number of tasks carrying it, by name
+    pub async fn list_tags(&self) -> Result<Vec<Tag>> {
+        Ok(sqlx::query_as(
+            "SELECT g.id, g.name, g.created_at, COUNT(t.id) AS task_count
+             FROM tags g
+             LEFT JOIN task_tags tt ON tt.tag_id = g.id
+             LEFT JOIN tasks t ON t.id = tt.task_id AND t.deleted_at IS NULL
+             GROUP BY g.id
+             ORDER BY g.name COLLATE NOCASE ASC",
+        )
+        .fetch_all(&self.pool())
+        .await?)
+    }
+
+    pub async fn create_tag(&self, name: &str) -> Result<Tag> {
+        let name = tag_name(name)?;
+        let tag = Tag {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            created_at: Utc::now(),
+            task_count: 0,
+        };
+
+        sqlx::query("INSERT INTO tags (id, name, created_at) VALUES ($1, $2, $3)")
+            .bind(&tag.id)
+            .bind(&tag.name)
+            .bind(to_sql_timestamp(&tag.created_at))
+            .execute(&self.pool())
+            .await
+            .map_err(|e| duplicate_tag(e, name))?;
+        Ok(tag)
+    }
+
+    pub async fn rename_tag(&self, id: &str, name: &str) -> Result<()> {
+        let name = tag_name(name)?;
+        let result = sqlx::query("UPDATE tags SET name = $1 WHERE id = $2")
+            .bind(name)
+            .bind(id)
+            .execute(&self.pool())
+            .await
+            .map_err(|e| duplicate_tag(e, name))?;
+
+        if result.rows_affected() == 0 {
+            return Err(crate::Error::TagNotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Fold `source_ids` into `target_id`: their tasks gain the target tag
+    /// and the source tags are deleted
+    pub async fn merge_tags(&self, source_ids: &[String], target_id: &str) -> Result<()> {
+        let mut tx = self.pool().begin().await?;
+
+        let mut ids = source_ids.to_vec();
+        ids.push(target_id.to_string());
+        check_tags_exist(&mut tx, &ids).await?;
+
+        let source_ids: Vec<String> = source_ids
+            .iter()
+            .filter(|id| *id != target_id)
+            .cloned()
+            .collect();
+        if source_ids.is_empty() {
+            return Ok(());
+        }
+
+        let mut query =
+            QueryBuilder::new("INSERT OR IGNORE INTO task_tags (task_id, tag_id) SELECT task_id, ");
+        query
+            .push_bind(target_id)
+            .push(" FROM task_tags WHERE tag_id IN (");
+        push_ids(&mut query, &source_ids);
+        query.build().execute(&mut *tx).await?;
+
+        // Their task_tags rows go with them through ON DELETE CASCADE
+        let mut query = QueryBuilder::new("DELETE FROM tags WHERE id IN (");
+        push_ids(&mut query, &source_ids);
+        query.build().execute(&mut *tx).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn delete_tag(&self, id: &str) -> Result<()> {
+        let result = sqlx::query("DELETE FROM tags WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(crate::Error::TagNotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Tag every task in `task_ids` with every tag in `tag_ids`, skipping
+    /// pairs that already exist
+    pub async fn attach_tags(&self, task_ids: &[String], tag_ids: &[String]) -> Result<()> {
+        if task_ids.is_empty() || tag_ids.is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.pool().begin().await?;
+        check_tasks_exist(&mut tx, task_ids).await?;
+        check_tags_exist(&mut tx, tag_ids).await?;
+        let before = snapshot(&mut tx, task_ids).await?;
+
+        let pairs = task_ids
+            .iter()
+            .flat_map(|task_id| tag_ids.iter().map(move |tag_id| (task_id, tag_id)));
+        let mut query = QueryBuilder::new("INSERT OR IGNORE INTO task_tags (task_id, tag_id) ");
+        query.push_values(pairs, |mut row, (task_id, tag_id)| {
+            row.push_bind(task_id).push_bind(tag_id);
+        });
+        query.build().execute(&mut *tx).await?;
+
+        log_changes_since(&mut tx, self.origin, &before).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Remove every tag in `tag_ids` from every task in `task_ids`
+    pub async fn detach_tags(&self, task_ids: &[String], tag_ids: &[String]) -> Result<()> {
+        if task_ids.is_empty() || tag_ids.is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.pool().begin().await?;
+        check_tasks_exist(&mut tx, task_ids).await?;
+        let before = snapshot(&mut tx, task_ids).await?;
+
+        let mut query = QueryBuilder::new("DELETE FROM task_tags WHERE task_id IN (");
+        push_ids(&mut query, task_ids);
+        query.push(" AND tag_id IN (");
+        push_ids(&mut query, tag_ids);
+        query.build().execute(&mut *tx).await?;
+
+        log_changes_since(&mut tx, self.origin, &before).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Tasks selected by `filter` with their notes, tags and reminders, in
+    /// outline order
+    pub async fn export_tasks(&self, filter: &ExportFilter) -> Result<Document> {
+        let mut conn = self.pool().acquire().await?;
+        if let Some(root_id) = &filter.root_id {
+            check_tasks_exist(&mut conn, std::slice::from_ref(root_id)).await?;
+        }
+
+        // $1 is the subtree root, $2/$3 the due range
+        let mut tasks: Vec<ExportedTask> = sqlx::query_as(
+            "WITH RECURSIVE
+            scope(id) AS (
+                SELECT id FROM tasks
+                WHERE deleted_at IS NULL AND (($1 IS NULL AND parent_id IS NULL) OR id = $1)
+                UNION
+                SELECT t.id FROM tasks t INNER JOIN scope s ON t.parent_id = s.id
+                WHERE t.deleted_at IS NULL
+            ),
+            matching(id, parent_id) AS (
+                SELECT t.id, t.parent_id FROM tasks t
+                WHERE t.id IN (SELECT id FROM scope)
+                  AND (($2 IS NULL AND $3 IS NULL)
+                       OR (t.due_date IS NOT NULL
+                           AND ($2 IS NULL OR t.due_date >= $2)
+                           AND ($3 IS NULL OR t.due_date <= $3)))
+                UNION
+                SELECT t.id, t.parent_id FROM tasks t
+                INNER JOIN matching m ON t.id = m.parent_id
+                WHERE t.id IN (SELECT id FROM scope)
+            )
+            SELECT id, parent_id, name, notes, completed, completed_at, created_at,
+                   due_date, due_time, task_order, priority, recurrence, repeat_from, occurrence
+            FROM tasks
+            WHERE id IN (SELECT id FROM matching)",
+        )
+        .bind(&filter.root_id)
+        .bind(filter.from)
+        .bind(filter.to)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT tt.task_id, g.name FROM task_tags tt
+             INNER JOIN tags g ON g.id = tt.tag_id
+             ORDER BY g.name COLLATE NOCASE",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        for (task_id, name) in rows {
+            tags.entry(task_id).or_default().push(name);
+        }
+
+        let mut reminders: HashMap<String, Vec<ReminderTrigger>> = HashMap::new();
+        let rows: Vec<(String, Option<DateTime<Utc>>, Option<i64>)> = sqlx::query_as(
+            "SELECT task_id, remind_at, offset_minutes FROM reminders
+             ORDER BY remind_at IS NULL, remind_at, offset_minutes, created_at",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        for (task_id, remind_at, offset_minutes) in rows {
+            let trigger = match (remind_at, offset_minutes) {
+                (Some(at), _) => ReminderTrigger::Absolute { at },
+                (None, Some(offset_minutes)) => ReminderTrigger::Relative { offset_minutes },
+                (None, None) => continue,
+            };
+            reminders.entry(task_id).or_default().push(trigger);
+        }
+
+        for task in &mut tasks {
+            task.tags = tags.remove(&task.id).unwrap_or_default();
+            task.reminders = Some(reminders.remove(&task.id).unwrap_or_default());
+        }
+        let (tasks, _) = export::outline(tasks);
+        Ok(Document::new(tasks))
+    }
+
+    /// Write a document's tasks in one transaction, rolled back again for a
+    /// dry run. Parents missing from both the document and the database
+    /// leave their children at the top level
+    pub async fn import_tasks(
+        &self,
+        document: &Document,
+        options: ImportOptions,
+    ) -> Result<ImportReport> {
+        let (tasks, cyclic) = export::outline(document.tasks.clone());
+        if let Some(task) = cyclic.first() {
+            return Err(crate::Error::InvalidExport(format!(
+                "task {} is its own ancestor",
+                task.id
+            )));
+        }
+
+        let mut ids: HashMap<&str, String> = HashMap::new();
+        for task in &tasks {
+            let id = match options.ids {
+                IdMode::Merge => task.id.clone(),
+                IdMode::Remap => Uuid::new_v4().to_string(),
+            };
+            if ids.insert(&task.id, id).is_some() {
+                return Err(crate::Error::InvalidExport(format!(
+                    "task {} appears twice",
+                    task.id
+                )));
+            }
+        }
+
+        let mut report = ImportReport {
+            dry_run: options.dry_run,
+            ..ImportReport::default()
+        };
+        let mut tag_ids: HashMap<String, String> = HashMap::new();
+        let mut parents: HashSet<Option<String>> = HashSet::new();
+        let imported: Vec<String> = ids.values().cloned().collect();
+        let mut tx = self.pool().begin().await?;
+        let before = snapshot(&mut tx, &imported).await?;
+
+        for task in &tasks {
+            let invalid =
+                |e: crate::Error| crate::Error::InvalidExport(format!("task {}: {e}", task.id));
+            check_due(task.due_date, task.due_time).map_err(invalid)?;
+            if let Some(rule) = &task.recurrence {
+                Rule::parse(rule).map_err(invalid)?;
+            }
+            for trigger in task.reminders.iter().flatten() {
+                trigger.check().map_err(invalid)?;
+            }
+
+            let id = &ids[task.id.as_str()];
+            let parent_id = match task.parent_id.as_deref() {
+                None => None,
+                Some(parent_id) => match ids.get(parent_id) {
+                    Some(mapped) => Some(mapped.clone()),
+                    None if task_exists(&mut tx, parent_id).await? => Some(parent_id.to_string()),
+                    None => {
+                        report.warnings.push(format!(
+                            "parent {parent_id} of {:?} not found, imported at the top level",
+                            task.name
+                        ));
+                        None
+                    }
+                },
+            };
+
+            let previous_parent: Option<Option<String>> =
+                sqlx::query_scalar("SELECT parent_id FROM tasks WHERE id = $1")
+                    .bind(id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+            let sql = match previous_parent {
+                Some(previous_parent) => {
+                    parents.insert(previous_parent);
+                    report.updated += 1;
+                    "UPDATE tasks SET
+                        name = $2, parent_id = $3, notes = COALESCE($4, notes),
+                        completed = $5, completed_at = $6,
+                        created_at = $7, due_date = $8, due_time = $9, task_order = $10,
+                        priority = $11, recurrence = $12, repeat_from = $13, occurrence = $14,
+                        deleted_at = NULL
+                     WHERE id = $1"
+                }
+                None => {
+                    report.created += 1;
+                    "INSERT INTO tasks (
+                        id, name, parent_id, notes, completed, completed_at, created_at,
+                        due_date, due_time, task_order, priority, recurrence, repeat_from, occurrence
+                     ) VALUES (
+                        $1, $2, $3, COALESCE($4, ''), $5, $6, $7, $8, $9, $10, $11, $12, $13, $14
+                     )"
+                }
+            };
+            sqlx::query(sql)
+                .bind(id)
+                .bind(&task.name)
+                .bind(&parent_id)
+                .bind(&task.notes)
+                .bind(task.completed)
+                .bind(task.completed_at.as_ref().map(to_sql_timestamp))
+                .bind(to_sql_timestamp(&task.created_at))
+                .bind(task.due_date)
+                .bind(task.due_time)
+                .bind(task.order)
+                .bind(task.priority)
+                .bind(&task.recurrence)
+                .bind(task.repeat_from)
+                .bind(task.occurrence)
+                .execute(&mut *tx)
+                .await?;
+            parents.insert(parent_id);
+
+            // The document is authoritative for what hangs off the task
+            sqlx::query("DELETE FROM task_tags WHERE task_id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            for name in &task.tags {
+                let name = tag_name(name).map_err(invalid)?;
+                let tag_id = match tag_ids.get(&name.to_lowercase()) {
+                    Some(tag_id) => tag_id.clone(),
+                    None => {
+                        let (tag_id, created) = find_or_create_tag(&mut tx, name).await?;
+                        if created {
+                            report.tags_created += 1;
+                        }
+                        tag_ids.insert(name.to_lowercase(), tag_id.clone());
+                        tag_id
+                    }
+                };
+                sqlx::query("INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES ($1, $2)")
+                    .bind(id)
+                    .bind(tag_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            let Some(reminders) = &task.reminders else {
+                continue;
+            };
+            sqlx::query("DELETE FROM reminders WHERE task_id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            for trigger in reminders {
+                let (remind_at, offset_minutes) = match *trigger {
+                    ReminderTrigger::Absolute { at } => (Some(to_sql_timestamp(&at)), None),
+                    ReminderTrigger::Relative { offset_minutes } => (None, Some(offset_minutes)),
+                };
+                sqlx::query(
+                    "INSERT INTO reminders (id, task_id, remind_at, offset_minutes, delivered_at, created_at)
+                     VALUES ($1, $2, $3, $4, NULL, $5)",
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(id)
+                .bind(remind_at)
+                .bind(offset_minutes)
+                .bind(to_sql_timestamp(&Utc::now()))
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        // A merged row comes out of the trash, while a parent outside the
+        // document may still be in it and would take the row along when purged
+        let rerooted = reroot_under_trashed(&mut tx, &imported).await?;
+        if !rerooted.is_empty() {
+            parents.insert(None);
+        }
+        for (_, name) in rerooted {
+            report.warnings.push(format!(
+                "parent of {name:?} is in the trash, imported at the top level"
+            ));
+        }
+
+        // Merged parents can close a loop through tasks outside the document
+        if let Some((task_id, parent_id)) = find_cycle(&mut tx, &imported).await? {
+            return Err(crate::Error::Cycle { task_id, parent_id });
+        }
+        for parent_id in &parents {
+            renumber_siblings(&mut tx, parent_id.as_deref(), &[], None).await?;
+        }
+        let after = snapshot(&mut tx, &imported).await?;
+        log_changes(&mut tx, self.origin, None, &before, &after).await?;
+
+        if options.dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+        Ok(report)
+    }
+
+    /// `task_order` of a task appended under `parent_id`, which must exist
+    pub async fn next_order(&self, parent_id: Option<&str>) -> Result<i64> {
+        let mut conn = self.pool().acquire().await?;
+        if let Some(parent_id) = parent_id {
+            check_tasks_exist(&mut conn, &[parent_id.to_string()]).await?;
+        }
+        let max_order: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(task_order), -1) FROM tasks WHERE parent_id IS $1",
+        )
+        .bind(parent_id)
+        .fetch_one(&mut *conn)
+        .await?;
+        Ok(max_order + 1)
+    }
+
+    /// Every recorded change to a task, oldest first. Tasks in the trash or
+    /// purged from it keep their history
+    pub async fn task_history(&self, id: &str) -> Result<TaskHistory> {
+        let mut conn = self.pool().acquire().await?;
+        let events: Vec<TaskEvent> = sqlx::query_as(
+            "SELECT id, task_id, field, old_value, new_value, origin, step, created_at
+             FROM task_events
+             WHERE task_id = $1
+             ORDER BY id",
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        // Tasks from before the log started have no events yet
+        if events.is_empty() {
+            let exists: bool =
+                sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM tasks WHERE id = $1)")
+                    .bind(id)
+                    .fetch_one(&mut *conn)
+                    .await?;
+            if !exists {
+                return Err(crate::Error::TaskNotFound(id.to_string()));
+            }
+        }
+        let times_rescheduled = events.iter().filter(|event| event.is_reschedule()).count();
+        Ok(TaskHistory {
+            events,
+            times_rescheduled: times_rescheduled as i64,
+        })
+    }
+
+    /// Tasks whose due date was pushed back at least `min_times` times, the
+    /// most rescheduled first. Undo and redo do not count
+    pub async fn rescheduled_tasks(&self, min_times: u32) -> Result<Vec<RescheduledTask>> {
+        let mut conn = self.pool().acquire().await?;
+        // The same test as `TaskEvent::is_reschedule`, on the JSON text of
+        // the dates, which is quoted but still compares in date order
+        let counts: Vec<(String, i64, Json<Option<NaiveDate>>)> = sqlx::query_as(
+            "WITH slips AS (
+                SELECT id, task_id, old_value FROM task_events
+                WHERE field = 'dueDate' AND step IS NULL
+                    AND old_value != 'null' AND new_value != 'null'
+                    AND new_value > old_value
+            )
+            SELECT
+                e.task_id,
+                COUNT(*) AS times_rescheduled,
+                (SELECT f.old_value FROM slips f
+                 WHERE f.task_id = e.task_id
+                 ORDER BY f.id LIMIT 1) AS original_due_date
+             FROM slips e
+             GROUP BY e.task_id
+             HAVING COUNT(*) >= $1
+             ORDER BY times_rescheduled DESC, MAX(e.id) DESC",
+        )
+        .bind(min_times.max(1))
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let ids: Vec<String> = counts.iter().map(|(id, ..)| id.clone()).collect();
+        let mut tasks: HashMap<String, Task> = fetch_tasks(&mut conn, &ids)
+            .await?
+            .into_iter()
+            .map(|task| (task.id.clone(), task))
+            .collect();
+        drop(conn);
+        let now = self.now().await?;
+
+        // Trashed and purged tasks are left out
+        Ok(counts
+            .into_iter()
+            .filter_map(|(id, times_rescheduled, Json(original_due_date))| {
+                let mut task = tasks.remove(&id)?;
+                task.overdue = task.is_overdue(now);
+                Some(RescheduledTask {
+                    task,
+                    times_rescheduled,
+                    original_due_date,
+                })
+            })
+            .collect())
+    }
+
+    /// Revert the latest operation that is not undone yet and return it,
+    /// `None` when there is nothing left to undo
+    pub async fn undo(&self) -> Result<Option<Operation>> {
+        self.step_history(true).await
+    }
+
+    /// Apply the operation undone last again and return it, `None` when
+    /// nothing was undone since the last change
+    pub async fn redo(&self) -> Result<Option<Operation>> {
+        self.step_history(false).await
+    }
+
+    async fn step_history(&self, undo: bool) -> Result<Option<Operation>> {
+        let mut tx = self.pool().begin().await?;
+        let sql = if undo {
+            "SELECT id, kind, label, task_count, created_at
+             FROM operations WHERE undone = 0 ORDER BY id DESC LIMIT 1"
+        } else {
+            "SELECT id, kind, label, task_count, created_at
+             FROM operations WHERE undone = 1 ORDER BY id ASC LIMIT 1"
+        };
+        let Some(operation) = sqlx::query_as::<_, Operation>(sql)
+            .fetch_optional(&mut *tx)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let (Json(before), Json(after)) = sqlx::query_as::<_, (Json<Snapshot>, Json<Snapshot>)>(
+            "SELECT before, after FROM operations WHERE id = $1",
+        )
+        .bind(operation.id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let (from, to) = if undo {
+            (&after, &before)
+        } else {
+            (&before, &after)
+        };
+        if let Some(reason) = snapshot_conflict(&mut tx, from, to).await? {
+            // It can never apply again, so it is dropped to let older
+            // operations be undone
+            sqlx::query("DELETE FROM operations WHERE id = $1")
+                .bind(operation.id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            return Err(crate::Error::HistoryConflict {
+                step: if undo { "undo" } else { "redo" },
+                operation: operation.description(),
+                reason,
+            });
+        }
+        apply_snapshot(&mut tx, from, to).await?;
+        let step = if undo {
+            HistoryStep::Undo
+        } else {
+            HistoryStep::Redo
+        };
+        log_changes(&mut tx, self.origin, Some(step), from, to).await?;
+        sqlx::query("UPDATE operations SET undone = $1 WHERE id = $2")
+            .bind(undo)
+            .bind(operation.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(operation))
+    }
+}
+
+/// Rewrite `task_order` of the live tasks under `parent_id` as a dense 0..n
+/// sequence, placing `inserted` at `index` (or last) and keeping everything
+/// else in order. Trashed tasks keep theirs to find their place on restore
+async fn renumber_siblings(
+    conn: &mut SqliteConnection,
+    parent_id: Option<&str>,
+    inserted: &[String],
+    index: Option<usize>,
+) -> Result<()> {
+    let mut siblings: Vec<String> = sqlx::query_scalar(
+        "SELECT id FROM tasks
+         WHERE parent_id IS $1 AND deleted_at IS NULL
+         ORDER BY task_order ASC, created_at ASC",
+    )
+    .bind(parent_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    siblings.retain(|id| !inserted.contains(id));
+    let at = index.unwrap_or(siblings.len()).min(siblings.len());
+    siblings.splice(at..at, inserted.iter().cloned());
+
+    for (order, id) in siblings.iter().enumerate() {
+        sqlx::query("UPDATE tasks SET task_order = $1 WHERE id = $2 AND task_order != $1")
+            .bind(order as i64)
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// SQL predicate for `alias` matching a due range bound as $1..$4 (see
+/// `query_tasks`), always true without a range
+fn due_range_matches(alias: &str, range: Option<&DueRange>) -> String {
+    match range {
+        Some(_) => format!(
+            "(($1 IS NULL AND {alias}.due_date IS NULL) \
+             OR ({alias}.due_date >= $1 AND {alias}.due_date <= $2) \
+             OR ($4 AND {alias}.due_date < $3 AND {alias}.completed = 0))"
+        ),
+        None => "1".to_string(),
+    }
+}
+
+/// A trash entry as queried, before its path is looked up
+#[derive(sqlx::FromRow)]
+struct TrashRow {
+    #[sqlx(flatten)]
+    task: Task,
+    deleted_at: DateTime<Utc>,
+    task_count: i64,
+    /// Whether the parent is still there to restore into
+    parent_live: bool,
+}
+
+/// Give `ids` a due date, along with the incomplete tasks in the rest of
+/// their `subtree_ids`, and let their relative reminders fire again
+async fn write_due(
+    conn: &mut SqliteConnection,
+    ids: &[String],
+    subtree_ids: &[String],
+    due_date: Option<NaiveDate>,
+    due_time: Option<NaiveTime>,
+) -> Result<()> {
+    let mut query = QueryBuilder::new("UPDATE tasks SET due_date = ");
+    query
+        .push_bind(due_date)
+        .push(", due_time = ")
+        .push_bind(due_time)
+        .push(" WHERE id IN (");
+    push_ids(&mut query, ids);
+    query.build().execute(&mut *conn).await?;
+
+    let descendant_ids: Vec<String> = subtree_ids
+        .iter()
+        .filter(|id| !ids.contains(id))
+        .cloned()
+        .collect();
+    if !descendant_ids.is_empty() {
+        let mut query = QueryBuilder::new("UPDATE tasks SET due_date = ");
+        query
+            .push_bind(due_date)
+            .push(", due_time = ")
+            .push_bind(due_time)
+            .push(" WHERE completed = 0 AND id IN (");
+        push_ids(&mut query, &descendant_ids);
+        query.build().execute(&mut *conn).await?;
+    }
+
+    // Relative reminders move with the due date and may fire again
+    let mut query = QueryBuilder::new(
+        "UPDATE reminders SET delivered_at = NULL
+         WHERE offset_minutes IS NOT NULL
+            AND task_id IN (SELECT id FROM tasks WHERE completed = 0 AND id IN (",
+    );
+    push_ids(&mut query, subtree_ids);
+    query.push(")");
+    query.build().execute(&mut *conn).await?;
+    Ok(())
+}
+
+/// Flip the completion of `id`, completing or reopening its ancestors and
+/// spawning the next occurrence of what recurs among them. Returns the ids
+/// of the rows whose completion changed and of the rows created
+async fn write_toggle(
+    conn: &mut SqliteConnection,
+    id: &str,
+    today: NaiveDate,
+) -> Result<(Vec<String>, Vec<String>)> {
+    let parent_id: Option<Option<String>> = sqlx::query_scalar(
+        "UPDATE tasks
+         SET completed = NOT completed,
+             completed_at = CASE WHEN completed THEN NULL ELSE $1 END
+         WHERE id = $2 AND deleted_at IS NULL
+         RETURNING parent_id",
+    )
+    .bind(to_sql_timestamp(&Utc::now()))
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some(parent_id) = parent_id else {
+        return Err(crate::Error::TaskNotFound(id.to_string()));
+    };
+
+    let mut changed = vec![id.to_string()];
+    if let Some(parent_id) = parent_id {
+        changed.extend(refresh_ancestors(conn, &parent_id).await?);
+    }
+
+    // Ancestors completed along the way may recur as well
+    let mut query = QueryBuilder::new(
+        "SELECT id FROM tasks WHERE completed = 1 AND recurrence IS NOT NULL AND id IN (",
+    );
+    push_ids(&mut query, &changed);
+    let recurring: Vec<String> = query.build_query_scalar().fetch_all(&mut *conn).await?;
+    let mut spawned = Vec::new();
+    for id in recurring {
+        let Some((next_id, parent_id)) = spawn_next_occurrence(conn, &id, today).await? else {
+            continue;
+        };
+        changed.push(next_id.clone());
+        spawned.push(next_id);
+        // The open occurrence reopens the parent the completion may have closed
+        if let Some(parent_id) = parent_id {
+            for id in refresh_ancestors(conn, &parent_id).await? {
+                if !changed.contains(&id) {
+                    changed.push(id);
+                }
+            }
+        }
+    }
+
+    let created = if spawned.is_empty() {
+        Vec::new()
+    } else {
+        subtree_ids(conn, &spawned).await?
+    };
+    Ok((changed, created))
+}
+
+/// A row of a recurring subtree being copied into its next occurrence
+#[derive(sqlx::FromRow)]
+struct OccurrenceRow {
+    id: String,
+    parent_id: Option<String>,
+    name: String,
+    notes: String,
+    due_date: Option<NaiveDate>,
+    due_time: Option<NaiveTime>,
+    task_order: i64,
+    priority: Priority,
+    recurrence: Option<String>,
+    repeat_from: RepeatFrom,
+    occurrence: i64,
+    next_occurrence_id: Option<String>,
+}
+
+/// Copy the completed recurring task `id` and its subtree, open, as the next
+/// occurrence of its series. Returns the new task id and its parent id, or
+/// `None` when the series has ended or the occurrence was spawned already
+async fn spawn_next_occurrence(
+    conn: &mut SqliteConnection,
+    id: &str,
+    today: NaiveDate,
+) -> Result<Option<(String, Option<String>)>> {
+    // Parents come before their children so foreign keys hold on insert
+    let rows: Vec<OccurrenceRow> = sqlx::query_as(
+        "WITH RECURSIVE subtree(id, depth) AS (
+            SELECT id, 0 FROM tasks WHERE id = $1
+            UNION ALL
+            SELECT t.id, s.depth + 1 FROM tasks t
+            INNER JOIN subtree s ON t.parent_id = s.id
+            WHERE t.deleted_at IS NULL
+        )
+        SELECT t.id, t.parent_id, t.name, t.notes, t.due_date, t.due_time, t.task_order,
+            t.priority, t.recurrence, t.repeat_from, t.occurrence, t.next_occurrence_id
+        FROM subtree s
+        INNER JOIN tasks t ON t.id = s.id
+        ORDER BY s.depth",
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let Some(root) = rows.first() else {
+        return Err(crate::Error::TaskNotFound(id.to_string()));
+    };
+    let Some(rule) = root.recurrence.as_deref() else {
+        return Ok(None);
+    };
+    // Completing it again after a reopen keeps the occurrence spawned before
+    if root.next_occurrence_id.is_some() {
+        return Ok(None);
+    }
+    let rule = Rule::parse(rule)?;
+    if !rule.allows_occurrence(root.occurrence + 1) {
+        return Ok(None);
+    }
+    let anchor = match root.repeat_from {
+        RepeatFrom::Due => root.due_date.unwrap_or(today),
+        RepeatFrom::Completion => today,
+    };
+    let Some(next_due) = rule.next_after(anchor) else {
+        return Ok(None);
+    };
+    // Subtasks keep their due dates relative to the recurring task
+    let shift = next_due - root.due_date.unwrap_or(anchor);
+
+    let max_order: i64 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(task_order), -1) FROM tasks WHERE parent_id IS $1")
+            .bind(&root.parent_id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+    let created_at = to_sql_timestamp(&Utc::now());
+    let mut new_ids: Vec<(String, String)> = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        let new_id = Uuid::new_v4().to_string();
+        let (parent_id, due_date, order, occurrence) = if index == 0 {
+            (
+                row.parent_id.clone(),
+                Some(next_due),
+                max_order + 1,
+                row.occurrence + 1,
+            )
+        } else {
+            let parent_id = new_ids
+                .iter()
+                .find(|(old, _)| Some(old) == row.parent_id.as_ref())
+                .map(|(_, new)| new.clone());
+            let due_date = row.due_date.map(|due_date| due_date + shift);
+            (parent_id, due_date, row.task_order, row.occurrence)
+        };
+
+        sqlx::query(
+            "INSERT INTO tasks (id, name, parent_id, completed, completed_at, created_at,
+                due_date, due_time, task_order, notes, priority, recurrence, repeat_from, occurrence)
+             VALUES ($1, $2, $3, FALSE, NULL, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        )
+        .bind(&new_id)
+        .bind(&row.name)
+        .bind(&parent_id)
+        .bind(&created_at)
+        .bind(due_date)
+        .bind(row.due_time)
+        .bind(order)
+        .bind(&row.notes)
+        .bind(row.priority)
+        .bind(&row.recurrence)
+        .bind(row.repeat_from)
+        .bind(occurrence)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query("INSERT INTO task_tags (task_id, tag_id) SELECT $1, tag_id FROM task_tags WHERE task_id = $2")
+            .bind(&new_id)
+            .bind(&row.id)
+            .execute(&mut *conn)
+            .await?;
+
+        // Relative reminders carry over, absolute ones belong to this occurrence
+        let offsets: Vec<i64> = sqlx::query_scalar(
+            "SELECT offset_minutes FROM reminders WHERE task_id = $1 AND offset_minutes IS NOT NULL",
+        )
+        .bind(&row.id)
+        .fetch_all(&mut *conn)
+        .await?;
+        for offset_minutes in offsets {
+            sqlx::query(
+                "INSERT INTO reminders (id, task_id, remind_at, offset_minutes, delivered_at, created_at)
+                 VALUES ($1, $2, NULL, $3, NULL, $4)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&new_id)
+            .bind(offset_minutes)
+            .bind(&created_at)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        new_ids.push((row.id.clone(), new_id));
+    }
+
+    let next_id = new_ids.swap_remove(0).1;
+    sqlx::query("UPDATE tasks SET next_occurrence_id = $1 WHERE id = $2")
+        .bind(&next_id)
+        .bind(&root.id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(Some((next_id, root.parent_id.clone())))
+}
+
+/// Trimmed tag name, rejecting blank ones
+fn tag_name(name: &str) -> Result<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(crate::Error::EmptyTagName);
+    }
+    Ok(name)
+}
+
+/// Report a clash on the unique tag name as a typed error
+fn duplicate_tag(error: sqlx::Error, name: &str) -> crate::Error {
+    match &error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => {
+            crate::Error::DuplicateTag(name.to_string())
+        }
+        _ => error.into(),
+    }
+}
+
+async fn check_tasks_exist(conn: &mut SqliteConnection, ids: &[String]) -> Result<()> {
+    let mut query = QueryBuilder::new("SELECT id FROM tasks WHERE deleted_at IS NULL AND id IN (");
+    push_ids(&mut query, ids);
+    let found: Vec<String> = query.build_query_scalar().fetch_all(&mut *conn).await?;
+    match ids.iter().find(|id| !found.contains(id)) {
+        Some(missing) => Err(crate::Error::TaskNotFound(missing.clone())),
+        None => Ok(()),
+    }
+}
+
+async fn check_tags_exist(conn: &mut SqliteConnection, ids: &[String]) -> Result<()> {
+    let mut query = QueryBuilder::new("SELECT id FROM tags WHERE id IN (");
+    push_ids(&mut query, ids);
+    let found: Vec<String> = query.build_query_scalar().fetch_all(&mut *conn).await?;
+    match ids.iter().find(|id| !found.contains(id)) {
+        Some(missing) => Err(crate::Error::TagNotFound(missing.clone())),
+        None => Ok(()),
+    }
+}
+
+async fn task_exists(conn: &mut SqliteConnection, id: &str) -> Result<bool> {
+    Ok(sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM tasks WHERE id = $1 AND deleted_at IS NULL)",
+    )
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await?)
+}
+
+/// Move the live tasks among `ids` whose parent is in the trash to the top
+/// level, returning their ids and names
+async fn reroot_under_trashed(
+    conn: &mut SqliteConnection,
+    ids: &[String],
+) -> Result<Vec<(String, String)>> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut query = QueryBuilder::new(
+        "UPDATE tasks SET parent_id = NULL
+         WHERE deleted_at IS NULL
+            AND parent_id IN (SELECT id FROM tasks WHERE deleted_at IS NOT NULL)
+            AND id IN (",
+    );
+    push_ids(&mut query, ids);
+    query.push(" RETURNING id, name");
+    Ok(query.build_query_as().fetch_all(&mut *conn).await?)
+}
+
+/// Id of the tag called `name` (ignoring case), creating it when missing,
+/// and whether it was created
+async fn find_or_create_tag(conn: &mut SqliteConnection, name: &str) -> Result<(String, bool)> {
+    let existing: Option<String> = sqlx::query_scalar("SELECT id FROM tags WHERE name = $1")
+        .bind(name)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if let Some(id) = existing {
+        return Ok((id, false));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO tags (id, name, created_at) VALUES ($1, $2, $3)")
+        .bind(&id)
+        .bind(name)
+        .bind(to_sql_timestamp(&Utc::now()))
+        .execute(&mut *conn)
+        .await?;
+    Ok((id, true))
+}
+
+/// A task among `ids` that has become its own ancestor, with its parent
+async fn find_cycle(
+    conn: &mut SqliteConnection,
+    ids: &[String],
+) -> Result<Option<(String, String)>> {
+    if ids.is_empty() {
+        return Ok(None);
+    }
+
+    let mut query = QueryBuilder::new(
+        "WITH RECURSIVE lineage(id, ancestor) AS (
+            SELECT id, parent_id FROM tasks WHERE parent_id IS NOT NULL AND id IN (",
+    );
+    push_ids(&mut query, ids);
+    query.push(
+        "
+            UNION
+            SELECT l.id, t.parent_id FROM lineage l
+            INNER JOIN tasks t ON t.id = l.ancestor
+            WHERE t.parent_id IS NOT NULL AND l.ancestor != l.id
+        )
+        SELECT t.id, t.parent_id FROM lineage l
+        INNER JOIN tasks t ON t.id = l.id
+        WHERE l.ancestor = l.id
+        LIMIT 1",
+    );
+    Ok(query.build_query_as().fetch_optional(&mut *conn).await?)
+}
+
+/// A due time only makes sense on a due day
+fn check_due(due_date: Option<NaiveDate>, due_time: Option<NaiveTime>) -> Result<()> {
+    if due_date.is_none() && due_time.is_some() {
+        return Err(crate::Error::DueTimeWithoutDate);
+    }
+    Ok(())
+}
+
+fn mark_overdue(mut tasks: Vec<Task>, now: NaiveDateTime) -> Vec<Task> {
+    for task in &mut tasks {
+        task.overdue = task.is_overdue(now);
+    }
+    tasks
+}
+
+/// Push `id1, id2, ...)` as bound parameters to close an `IN (` list
+fn push_ids(query: &mut QueryBuilder<'_, sqlx::Sqlite>, ids: &[String]) {
+    let mut separated = query.separated(", ");
+    for id in ids {
+        separated.push_bind(id.clone());
+    }
+    separated.push_unseparated(")");
+}
+
+/// `parent_id` and its ancestors, from the root down
+async fn ancestor_path(
+    conn: &mut SqliteConnection,
+    parent_id: Option<&str>,
+) -> Result<Vec<PathEntry>> {
+    let Some(parent_id) = parent_id else {
+        return Ok(Vec::new());
+    };
+    Ok(sqlx::query_as(
+        "WITH RECURSIVE lineage(id, name, parent_id, depth) AS (
+            SELECT id, name, parent_id, 0 FROM tasks WHERE id = $1
+            UNION ALL
+            SELECT t.id, t.name, t.parent_id, l.depth + 1 FROM tasks t
+            INNER JOIN lineage l ON t.id = l.parent_id
+        )
+        SELECT id, name FROM lineage ORDER BY depth DESC",
+    )
+    .bind(parent_id)
+    .fetch_all(&mut *conn)
+    .await?)
+}
+
+/// `id` and its ancestors, none for the top level
+async fn lineage_ids(conn: &mut SqliteConnection, id: Option<&str>) -> Result<Vec<String>> {
+    let path = ancestor_path(conn, id).await?;
+    Ok(path.into_iter().map(|entry| entry.id).collect())
+}
+
+/// Current rows of the tasks `ids` that exist, with their tags and reminders
+async fn snapshot(conn: &mut SqliteConnection, ids: &[String]) -> Result<Snapshot> {
+    if ids.is_empty() {
+        return Ok(Snapshot::default());
+    }
+    let mut query = QueryBuilder::new(
+        "SELECT id, name, parent_id, completed, completed_at, created_at, due_date, due_time,
+            task_order, notes, priority, recurrence, repeat_from, occurrence, deleted_at,
+            next_occurrence_id
+         FROM tasks WHERE id IN (",
+    );
+    push_ids(&mut query, ids);
+    query.push(" ORDER BY id");
+    let tasks = query.build_query_as().fetch_all(&mut *conn).await?;
+
+    let mut query = QueryBuilder::new("SELECT task_id, tag_id FROM task_tags WHERE task_id IN (");
+    push_ids(&mut query, ids);
+    query.push(" ORDER BY task_id, tag_id");
+    let task_tags = query.build_query_as().fetch_all(&mut *conn).await?;
+
+    let mut query = QueryBuilder::new(
+        "SELECT id, task_id, remind_at, offset_minutes, delivered_at, created_at
+         FROM reminders WHERE task_id IN (",
+    );
+    push_ids(&mut query, ids);
+    query.push(" ORDER BY id");
+    let reminders = query.build_query_as().fetch_all(&mut *conn).await?;
+
+    Ok(Snapshot {
+        tasks,
+        task_tags,
+        reminders,
+    })
+}
+
+/// Log an operation on `ids` that has just run, given the rows it could
+/// touch as they were `before` and the ids of the tasks it created, along
+/// with the fields it changed. Starts a new redo history, and records
+/// nothing when no row changed
+async fn record(
+    conn: &mut SqliteConnection,
+    origin: Origin,
+    kind: OperationKind,
+    ids: &[String],
+    before: Snapshot,
+    created: &[String],
+) -> Result<()> {
+    let mut scope: Vec<String> = before.tasks.iter().map(|row| row.id.clone()).collect();
+    scope.extend_from_slice(created);
+    let after = snapshot(conn, &scope).await?;
+    if after == before {
+        return Ok(());
+    }
+    log_changes(conn, origin, None, &before, &after).await?;
+    let label = ids
+        .first()
+        .and_then(|id| {
+            after
+                .tasks
+                .iter()
+                .chain(&before.tasks)
+                .find(|row| row.id == *id)
+        })
+        .map(|row| row.name.clone())
+        .unwrap_or_default();
+
+    sqlx::query("DELETE FROM operations WHERE undone = 1")
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        "INSERT INTO operations (kind, label, task_count, before, after, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(kind)
+    .bind(label)
+    .bind(ids.len() as i64)
+    .bind(Json(&before))
+    .bind(Json(&after))
+    .bind(to_sql_timestamp(&Utc::now()))
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query("DELETE FROM operations WHERE id <= (SELECT MAX(id) FROM operations) - $1")
+        .bind(HISTORY_LIMIT)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Append the fields that differ between `before` and `after` to the
+/// append-only `task_events`
+async fn log_changes(
+    conn: &mut SqliteConnection,
+    origin: Origin,
+    step: Option<HistoryStep>,
+    before: &Snapshot,
+    after: &Snapshot,
+) -> Result<()> {
+    let mut tag_names = HashMap::new();
+    if before.task_tags != after.task_tags {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT id, name FROM tags")
+            .fetch_all(&mut *conn)
+            .await?;
+        tag_names.extend(rows);
+    }
+
+    let created_at = to_sql_timestamp(&Utc::now());
+    for change in audit::changes(before, after, &tag_names) {
+        sqlx::query(
+            "INSERT INTO task_events (task_id, field, old_value, new_value, origin, step, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(&change.task_id)
+        .bind(change.field)
+        .bind(Json(&change.old_value))
+        .bind(Json(&change.new_value))
+        .bind(origin)
+        .bind(step)
+        .bind(&created_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Log the fields changed since `before` was taken, on the same tasks
+async fn log_changes_since(
+    conn: &mut SqliteConnection,
+    origin: Origin,
+    before: &Snapshot,
+) -> Result<()> {
+    let ids: Vec<String> = before.tasks.iter().map(|row| row.id.clone()).collect();
+    let after = snapshot(conn, &ids).await?;
+    log_changes(conn, origin, None, before, &after).await
+}
+
+/// Why the database no longer holds the rows of `from`, so `to` cannot be
+/// applied: a row was purged since, or a task about to be removed got
+/// subtasks outside the snapshot that would be deleted along with it
+async fn snapshot_conflict(
+    conn: &mut SqliteConnection,
+    from: &Snapshot,
+    to: &Snapshot,
+) -> Result<Option<String>> {
+    if from.tasks.is_empty() {
+        return Ok(None);
+    }
+    let ids: Vec<String> = from.tasks.iter().map(|row| row.id.clone()).collect();
+    let mut query = QueryBuilder::new("SELECT id FROM tasks WHERE id IN (");
+    push_ids(&mut query, &ids);
+    let found: Vec<String> = query.build_query_scalar().fetch_all(&mut *conn).await?;
+    if let Some(row) = from.tasks.iter().find(|row| !found.contains(&row.id)) {
+        return Ok(Some(format!("{:?} was deleted for good since", row.name)));
+    }
+
+    let removed: Vec<String> = ids
+        .iter()
+        .filter(|id| !to.tasks.iter().any(|row| &row.id == *id))
+        .cloned()
+        .collect();
+    if removed.is_empty() {
+        return Ok(None);
+    }
+    let mut query = QueryBuilder::new("SELECT name FROM tasks WHERE parent_id IN (");
+    push_ids(&mut query, &removed);
+    query.push(" AND id NOT IN (");
+    push_ids(&mut query, &ids);
+    query.push(" LIMIT 1");
+    let added: Option<String> = query
+        .build_query_scalar()
+        .fetch_optional(&mut *conn)
+        .await?;
+    Ok(added.map(|name| format!("subtask {name:?} was added since")))
+}
+
+/// Turn the rows of `from` into those of `to`: tasks only in `from` are
+/// deleted, tasks only in `to` inserted again with their tags and
+/// reminders, and columns that differ set to their value in `to`. Columns
+/// the operation did not change keep whatever was edited since
+async fn apply_snapshot(conn: &mut SqliteConnection, from: &Snapshot, to: &Snapshot) -> Result<()> {
+    // Restored subtrees are inserted in any order, children possibly first
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *conn)
+        .await?;
+
+    let current: HashMap<&str, &TaskRow> = from
+        .tasks
+        .iter()
+        .map(|row| (row.id.as_str(), row))
+        .collect();
+    let kept: HashSet<&str> = to.tasks.iter().map(|row| row.id.as_str()).collect();
+
+    let removed: Vec<String> = from
+        .tasks
+        .iter()
+        .filter(|row| !kept.contains(row.id.as_str()))
+        .map(|row| row.id.clone())
+        .collect();
+    if !removed.is_empty() {
+        let mut query = QueryBuilder::new("DELETE FROM tasks WHERE id IN (");
+        push_ids(&mut query, &removed);
+        query.build().execute(&mut *conn).await?;
+    }
+
+    let mut inserted = HashSet::new();
+    for row in &to.tasks {
+        match current.get(row.id.as_str()) {
+            Some(old) => update_task_row(conn, old, row).await?,
+            None => {
+                insert_task_row(conn, row).await?;
+                inserted.insert(row.id.as_str());
+            }
+        }
+    }
+
+    for (task_id, tag_id) in &to.task_tags {
+        if !inserted.contains(task_id.as_str()) {
+            continue;
+        }
+        // The tag may have been deleted in the meantime
+        sqlx::query("INSERT OR IGNORE INTO task_tags (task_id, tag_id) SELECT $1, id FROM tags WHERE id = $2")
+            .bind(task_id)
+            .bind(tag_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    for reminder in &to.reminders {
+        if !inserted.contains(reminder.task_id.as_str()) {
+            continue;
+        }
+        sqlx::query(
+            "INSERT OR IGNORE INTO reminders (id, task_id, remind_at, offset_minutes, delivered_at, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(&reminder.id)
+        .bind(&reminder.task_id)
+        .bind(&reminder.remind_at)
+        .bind(reminder.offset_minutes)
+        .bind(&reminder.delivered_at)
+        .bind(&reminder.created_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn insert_task_row(conn: &mut SqliteConnection, row: &TaskRow) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO tasks (id, name, parent_id, completed, completed_at, created_at,
+            due_date, due_time, task_order, notes, priority, recurrence, repeat_from, occurrence,
+            deleted_at, next_occurrence_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)",
+    )
+    .bind(&row.id)
+    .bind(&row.name)
+    .bind(&row.parent_id)
+    .bind(row.completed)
+    .bind(&row.completed_at)
+    .bind(&row.created_at)
+    .bind(&row.due_date)
+    .bind(&row.due_time)
+    .bind(row.task_order)
+    .bind(&row.notes)
+    .bind(row.priority)
+    .bind(&row.recurrence)
+    .bind(&row.repeat_from)
+    .bind(row.occurrence)
+    .bind(&row.deleted_at)
+    .bind(&row.next_occurrence_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Set the columns in which `new` differs from `old`
+async fn update_task_row(conn: &mut SqliteConnection, old: &TaskRow, new: &TaskRow) -> Result<()> {
+    let mut query = QueryBuilder::new("UPDATE tasks SET ");
+    let mut columns = query.separated(", ");
+    let mut changed = false;
+    macro_rules! set_changed {
+        ($($column:ident),+) => {$(
+            if old.$column != new.$column {
+                columns.push(concat!(stringify!($column), " = "));
+                columns.push_bind_unseparated(new.$column.clone());
+                changed = true;
+            }
+        )+};
+    }
+    set_changed!(
+        name,
+        parent_id,
+        completed,
+        completed_at,
+        created_at,
+        due_date,
+        due_time,
+        task_order,
+        notes,
+        priority,
+        recurrence,
+        repeat_from,
+        occurrence,
+        deleted_at,
+        next_occurrence_id
+    );
+    if !changed {
+        return Ok(());
+    }
+    query.push(" WHERE id = ").push_bind(&new.id);
+    query.build().execute(&mut *conn).await?;
+    Ok(())
+}
+
+/// Collect `ids` and all of their descendants, leaving out trashed ones
+async fn subtree_ids(conn: &mut SqliteConnection, ids: &[String]) -> Result<Vec<String>> {
+    let mut query = QueryBuilder::new(
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT id FROM tasks WHERE deleted_at IS NULL AND id IN (",
+    );
+    push_ids(&mut query, ids);
+    query.push(
+        "
+            UNION
+            SELECT t.id FROM tasks t
+            INNER JOIN subtree s ON t.parent_id = s.id
+            WHERE t.deleted_at IS NULL
+        )
+        SELECT id FROM subtree",
+    );
+    Ok(query.build_query_scalar().fetch_all(&mut *conn).await?)
+}
+
+/// Load full rows for the live tasks among `ids`, with subtask counts over
+/// all live children
+async fn fetch_tasks(conn: &mut SqliteConnection, ids: &[String]) -> Result<Vec<Task>> {
+    let mut query = QueryBuilder::new(format!(
+        "WITH subtask_counts AS (
+            SELECT
+                s.parent_id AS id,
+                COUNT(s.id) AS total_subtasks,
+                SUM(CASE WHEN s.completed = 1 THEN 1 ELSE 0 END) AS completed_subtasks
+            FROM tasks s
+            WHERE s.parent_id IS NOT NULL AND s.deleted_at IS NULL
+            GROUP BY s.parent_id
+        )
+        SELECT {TASK_COLUMNS}
+        FROM tasks t
+        LEFT JOIN subtask_counts sc ON t.id = sc.id
+        WHERE t.deleted_at IS NULL AND t.id IN ("
+    ));
+    push_ids(&mut query, ids);
+    Ok(query.build_query_as().fetch_all(&mut *conn).await?)
+}
+
+/// Recompute completion of `parent_id` from its children and walk up the
+/// tree for as long as a parent's state changes, returning the changed ids
+async fn refresh_ancestors(conn: &mut SqliteConnection, parent_id: &str) -> Result<Vec<String>> {
+    let mut changed = Vec::new();
+    let mut current = Some(parent_id.to_string());
+
+    while let Some(id) = current.take() {
+        let Some((completed, grandparent_id, children, completed_children)) =
+            sqlx::query_as::<_, (bool, Option<String>, i64, i64)>(
+                "SELECT
+                    p.completed,
+                    p.parent_id,
+                    COUNT(c.id),
+                    COALESCE(SUM(CASE WHEN c.completed = 1 THEN 1 ELSE 0 END), 0)
+                 FROM tasks p
+                 LEFT JOIN tasks c ON c.parent_id = p.id AND c.deleted_at IS NULL
+                 WHERE p.id = $1
+                 GROUP BY p.id",
+            )
+            .bind(&id)
+            .fetch_optional(&mut *conn)
+            .await?
+        else {
+            break;
+        };
+
+        if children == 0 {
+            break;
+        }
+
+        let all_completed = children == completed_children;
+        if all_completed && !completed {
+            sqlx::query("UPDATE tasks SET completed = TRUE, completed_at = $1 WHERE id = $2")
+                .bind(to_sql_timestamp(&Utc::now()))
+                .bind(&id)
+                .execute(&mut *conn)
+                .await?;
+        } else if !all_completed && completed {
+            sqlx::query("UPDATE tasks SET completed = FALSE, completed_at = NULL WHERE id = $1")
+                .bind(&id)
+                .execute(&mut *conn)
+                .await?;
+        } else {
+            break;
+        }
+
+        changed.push(id);
+        current = grandparent_id;
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    async fn repo() -> TaskRepository {
+        TaskRepository::new(crate::db::memory().await)
+    }
+
+    async fn add(repo: &TaskRepository, name: &str, parent_id: Option<&str>) -> String {
+        repo.create_task(name, parent_id, None, None)
+            .await
+            .unwrap()
+            .id
+    }
+
+    async fn all(repo: &TaskRepository) -> Vec<Task> {
+        repo.load_tasks(&DateFilter::All, None, TaskSort::Manual)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn completion_propagates_to_ancestors() {
+        let repo = repo().await;
+        let parent = add(&repo, "Parent", None).await;
+        let first = add(&repo, "First", Some(&parent)).await;
+        let second = add(&repo, "Second", Some(&parent)).await;
+
+        repo.toggle_task(&first).await.unwrap();
+        assert!(!repo.task(&parent).await.unwrap().completed);
+
+        let changed = repo.toggle_task(&second).await.unwrap();
+        assert!(changed.iter().any(|task| task.id == parent));
+        let done = repo.task(&parent).await.unwrap();
+        assert!(done.completed);
+        assert_eq!((done.completed_subtasks, done.total_subtasks), (2, 2));
+
+        // Reopening a subtask reopens the parent again
+        repo.toggle_task(&first).await.unwrap();
+        assert!(!repo.task(&parent).await.unwrap().completed);
+        assert!(matches!(
+            repo.toggle_task("missing").await,
+            Err(Error::TaskNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn deletes_whole_subtrees() {
+        let repo = repo().await;
+        let root = add(&repo, "Root", None).await;
+        let child = add(&repo, "Child", Some(&root)).await;
+        add(&repo, "Grandchild", Some(&child)).await;
+        let other = add(&repo, "Other", None).await;
+
+        let deleted = repo
+            .delete_subtree(std::slice::from_ref(&root))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 3);
+        let left: Vec<String> = all(&repo).await.into_iter().map(|task| task.id).collect();
+        assert_eq!(left, [other]);
+        assert_eq!(repo.delete_subtree(&[]).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_cycles() {
+        let repo = repo().await;
+        let root = add(&repo, "Root", None).await;
+        let child = add(&repo, "Child", Some(&root)).await;
+        let grandchild = add(&repo, "Grandchild", Some(&child)).await;
+
+        for parent in [&root, &grandchild] {
+            assert!(matches!(
+                repo.move_tasks(std::slice::from_ref(&root), Some(parent), None)
+                    .await,
+                Err(Error::Cycle { .. })
+            ));
+        }
+
+        repo.move_tasks(std::slice::from_ref(&grandchild), None, Some(0))
+            .await
+            .unwrap();
+        let tasks = all(&repo).await;
+        let moved = tasks.iter().find(|task| task.id == grandchild).unwrap();
+        assert_eq!((moved.parent_id.as_deref(), moved.order), (None, 0));
+        let root = tasks.iter().find(|task| task.id == root).unwrap();
+        assert_eq!(root.order, 1);
+    }
+
+    #[tokio::test]
+    async fn today_follows_the_configured_timezone() {
+        let repo = repo().await;
+        // A day ahead of UTC-11, whatever the time
+        let zone = repo.set_timezone(Some("Pacific/Kiritimati")).await.unwrap();
+        let today = zone.local_time(Utc::now()).date();
+        repo.create_task("Today", None, Some(today), None)
+            .await
+            .unwrap();
+
+        let today_tasks = |repo: TaskRepository| async move {
+            repo.load_tasks(&DateFilter::Today, None, TaskSort::Manual)
+                .await
+                .unwrap()
+                .len()
+        };
+        assert_eq!(today_tasks(repo.clone()).await, 1);
+        repo.set_timezone(Some("Pacific/Pago_Pago")).await.unwrap();
+        assert_eq!(today_tasks(repo.clone()).await, 0);
+        assert!(matches!(
+            repo.set_timezone(Some("Mars/Olympus")).await,
+            Err(Error::InvalidTimezone(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn sidebar_counts_per_day() {
+        let repo = repo().await;
+        let today = repo.now().await.unwrap().date();
+        let id = repo
+            .create_task("Now", None, Some(today), None)
+            .await
+            .unwrap()
+            .id;
+        repo.create_task("Later", None, Some(today + chrono::Duration::days(9)), None)
+            .await
+            .unwrap();
+        add(&repo, "Someday", None).await;
+        repo.toggle_task(&id).await.unwrap();
+
+        let entries = repo.date_filters().await.unwrap();
+        let counts: Vec<(&str, i64, i64)> = entries
+            .iter()
+            .map(|e| (e.label.as_str(), e.total_task_count, e.completed_task_count))
+            .collect();
+        assert_eq!(counts[0], ("All", 3, 1));
+        assert_eq!(counts[1], ("Today", 1, 1));
+        assert_eq!(counts[2], ("Tomorrow", 0, 0));
+        assert_eq!(counts.last().unwrap(), &("Someday", 1, 0));
+        assert_eq!(entries.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn filters_by_tags() {
+        let repo = repo().await;
+        let work = repo.create_tag("work").await.unwrap().id;
+        let home = repo.create_tag("home").await.unwrap().id;
+        let a = add(&repo, "A", None).await;
+        let b = add(&repo, "B", None).await;
+        let c = add(&repo, "C", None).await;
+        let d = add(&repo, "D", Some(&c)).await;
+        repo.attach_tags(&[a.clone(), b.clone()], std::slice::from_ref(&work))
+            .await
+            .unwrap();
+        // Attaching twice is a no-op
+        repo.attach_tags(&[a.clone(), d.clone()], std::slice::from_ref(&home))
+            .await
+            .unwrap();
+        repo.attach_tags(std::slice::from_ref(&a), std::slice::from_ref(&home))
+            .await
+            .unwrap();
+
+        let names = |tag_ids: &[&String], mode| {
+            let filter = TagFilter {
+                tag_ids: tag_ids.iter().map(|id| id.to_string()).collect(),
+                mode,
+            };
+            let repo = repo.clone();
+            async move {
+                repo.load_tasks(&DateFilter::All, Some(&filter), TaskSort::Manual)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|task| task.name)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(names(&[&work], TagMatch::Any).await, ["A", "B"]);
+        assert_eq!(names(&[&work, &home], TagMatch::All).await, ["A"]);
+        // Matches come with their ancestors
+        assert_eq!(names(&[&home], TagMatch::Any).await, ["A", "C", "D"]);
+
+        repo.detach_tags(std::slice::from_ref(&a), std::slice::from_ref(&work))
+            .await
+            .unwrap();
+        assert_eq!(names(&[&work], TagMatch::Any).await, ["B"]);
+        let counts: Vec<(String, i64)> = repo
+            .list_tags()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tag| (tag.name, tag.task_count))
+            .collect();
+        assert_eq!(counts, [("home".into(), 2), ("work".into(), 1)]);
+
+        assert!(matches!(
+            repo.create_tag(" WORK ").await,
+            Err(Error::DuplicateTag(_))
+        ));
+        assert!(matches!(
+            repo.create_tag("  ").await,
+            Err(Error::EmptyTagName)
+        ));
+        assert!(matches!(
+            repo.attach_tags(std::slice::from_ref(&a), &["missing".to_string()])
+                .await,
+            Err(Error::TagNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn sorts_by_priority_then_overdue() {
+        let repo = repo().await;
+        let long_ago = NaiveDate::from_ymd_opt(2020, 1, 1);
+        add(&repo, "A", None).await;
+        let b = add(&repo, "B", None).await;
+        let c = add(&repo, "C", None).await;
+        let d = repo
+            .create_task("D", None, long_ago, None)
+            .await
+            .unwrap()
+            .id;
+        repo.set_priority(&[b, d], Priority::High).await.unwrap();
+        repo.set_priority(&[c], Priority::Low).await.unwrap();
+
+        let names = |sort| {
+            let repo = repo.clone();
+            async move {
+                repo.load_tasks(&DateFilter::All, None, sort)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|task| task.name)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(names(TaskSort::Manual).await, ["D", "A", "B", "C"]);
+        assert_eq!(names(TaskSort::Priority).await, ["D", "B", "C", "A"]);
+    }
+
+    #[tokio::test]
+    async fn completing_a_recurring_task_spawns_the_next_occurrence() {
+        let repo = repo().await;
+        let due = NaiveDate::from_ymd_opt(2026, 10, 30).unwrap();
+        let task = repo
+            .create_task("Report", None, Some(due), None)
+            .await
+            .unwrap();
+        repo.set_recurrence(
+            &task.id,
+            Some("FREQ=MONTHLY;BYDAY=-1FR;COUNT=2"),
+            RepeatFrom::Due,
+        )
+        .await
+        .unwrap();
+
+        let changed = repo.toggle_task(&task.id).await.unwrap();
+        let next = changed.iter().find(|t| t.id != task.id).unwrap();
+        assert_eq!(next.due_date, NaiveDate::from_ymd_opt(2026, 11, 27));
+        assert_eq!((next.occurrence, next.completed), (2, false));
+
+        // COUNT=2 ends the series there
+        let changed = repo.toggle_task(&next.id).await.unwrap();
+        assert_eq!(changed.len(), 1);
+        assert!(matches!(
+            repo.set_recurrence(&task.id, Some("FREQ=HOURLY"), RepeatFrom::Due)
+                .await,
+            Err(Error::InvalidRecurrence(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn completing_again_after_a_reopen_spawns_nothing() {
+        let repo = repo().await;
+        let due = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        let task = repo
+            .create_task("Water plants", None, Some(due), None)
+            .await
+            .unwrap();
+        repo.set_recurrence(&task.id, Some("FREQ=DAILY"), RepeatFrom::Due)
+            .await
+            .unwrap();
+        let occurrences =
+            |tasks: Vec<Task>| tasks.into_iter().filter(|t| t.occurrence == 2).count();
+
+        assert_eq!(repo.toggle_task(&task.id).await.unwrap().len(), 2);
+        assert_eq!(repo.toggle_task(&task.id).await.unwrap().len(), 1);
+        assert_eq!(repo.toggle_task(&task.id).await.unwrap().len(), 1);
+        assert_eq!(occurrences(all(&repo).await), 1);
+
+        // Undoing back past the first completion and redoing keeps the link
+        for _ in 0..3 {
+            repo.undo().await.unwrap().unwrap();
+        }
+        assert_eq!(occurrences(all(&repo).await), 0);
+        for _ in 0..2 {
+            repo.redo().await.unwrap().unwrap();
+        }
+        assert_eq!(repo.toggle_task(&task.id).await.unwrap().len(), 1);
+        assert_eq!(occurrences(all(&repo).await), 1);
+
+        // Once the next occurrence is purged, completing spawns a new one
+        let next = all(&repo)
+            .await
+            .into_iter()
+            .find(|t| t.occurrence == 2)
+            .unwrap();
+        repo.delete_subtree(&[next.id]).await.unwrap();
+        repo.purge_trash(None).await.unwrap();
+        repo.toggle_task(&task.id).await.unwrap();
+        assert_eq!(repo.toggle_task(&task.id).await.unwrap().len(), 2);
+        assert_eq!(occurrences(all(&repo).await), 1);
+    }
+
+    #[tokio::test]
+    async fn reminders_fire_once_while_the_task_is_open() {
+        let repo = repo().await;
+        repo.set_timezone(Some("UTC")).await.unwrap();
+        let task = repo
+            .create_task(
+                "Dentist",
+                None,
+                NaiveDate::from_ymd_opt(2026, 10, 20),
+                NaiveTime::from_hms_opt(9, 0, 0),
+            )
+            .await
+            .unwrap();
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let relative = repo
+            .add_reminder(
+                &task.id,
+                ReminderTrigger::Relative {
+                    offset_minutes: -30,
+                },
+            )
+            .await
+            .unwrap();
+        let absolute = repo
+            .add_reminder(
+                &task.id,
+                ReminderTrigger::Absolute {
+                    at: at("2026-10-19T12:00:00Z"),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            repo.add_reminder(
+                &task.id,
+                ReminderTrigger::Relative {
+                    offset_minutes: -100_000_000_000
+                }
+            )
+            .await,
+            Err(Error::InvalidReminderOffset(_))
+        ));
+
+        assert!(repo
+            .due_reminders(at("2026-10-19T11:59:00Z"))
+            .await
+            .unwrap()
+            .is_empty());
+        let due = repo
+            .due_reminders(at("2026-10-20T08:45:00Z"))
+            .await
+            .unwrap();
+        let fired: Vec<_> = due.iter().map(|r| (r.id.as_str(), r.fires_at)).collect();
+        assert_eq!(
+            fired,
+            [
+                (absolute.id.as_str(), Some(at("2026-10-19T12:00:00Z"))),
+                (relative.id.as_str(), Some(at("2026-10-20T08:30:00Z"))),
+            ]
+        );
+
+        repo.mark_reminder_delivered(&absolute.id, at("2026-10-19T12:00:00Z"))
+            .await
+            .unwrap();
+        let due = repo
+            .due_reminders(at("2026-10-20T08:45:00Z"))
+            .await
+            .unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, relative.id);
+
+        repo.toggle_task(&task.id).await.unwrap();
+        assert!(repo
+            .due_reminders(at("2026-10-20T08:45:00Z"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Imports hold reminders to the same range
+        let mut document = repo.export_tasks(&ExportFilter::default()).await.unwrap();
+        document.tasks[0].reminders = Some(vec![ReminderTrigger::Relative {
+            offset_minutes: i64::MAX,
+        }]);
+        assert!(matches!(
+            repo.import_tasks(&document, ImportOptions::default()).await,
+            Err(Error::InvalidExport(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn search_ranks_names_above_notes() {
+        let repo = repo().await;
+        let groceries = add(&repo, "Groceries", None).await;
+        repo.set_notes(&groceries, "Buy milk and bread")
+            .await
+            .unwrap();
+        let errands = add(&repo, "Errands", None).await;
+        let cows = add(&repo, "Milk the cows", Some(&errands)).await;
+        let trashed = add(&repo, "Milk shake", None).await;
+        repo.delete_subtree(&[trashed]).await.unwrap();
+
+        let hits = repo
+            .search_tasks("mil", &DateFilter::All, 10)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = hits.iter().map(|hit| hit.task.id.as_str()).collect();
+        assert_eq!(ids, [cows.as_str(), groceries.as_str()]);
+        assert!(hits[0].rank < hits[1].rank);
+        assert_eq!(hits[0].name_html, "<mark>Milk</mark> the cows");
+        assert_eq!(hits[0].notes_html, None);
+        let path: Vec<&str> = hits[0]
+            .path
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(path, ["Errands"]);
+        assert_eq!(hits[1].name_html, "Groceries");
+        assert_eq!(
+            hits[1].notes_html.as_deref(),
+            Some("Buy <mark>milk</mark> and bread")
+        );
+
+        // Operators are searched for as words, and punctuation is ignored
+        for query in ["milk OR", "milk NEAR(", "   "] {
+            let hits = repo
+                .search_tasks(query, &DateFilter::All, 10)
+                .await
+                .unwrap();
+            assert!(hits.is_empty(), "{query}");
+        }
+        for query in ["\"milk", "-milk", "milk)"] {
+            let hits = repo
+                .search_tasks(query, &DateFilter::All, 10)
+                .await
+                .unwrap();
+            assert_eq!(hits.len(), 2, "{query}");
+        }
+        let hits = repo
+            .search_tasks("milk cows", &DateFilter::All, 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn exports_and_reimports_in_both_id_modes() {
+        let repo = repo().await;
+        let trip = add(&repo, "Trip", None).await;
+        let tickets = repo
+            .create_task(
+                "Tickets",
+                Some(&trip),
+                NaiveDate::from_ymd_opt(2026, 11, 2),
+                None,
+            )
+            .await
+            .unwrap()
+            .id;
+        repo.set_notes(&trip, "Window seat").await.unwrap();
+        repo.set_priority(std::slice::from_ref(&trip), Priority::High)
+            .await
+            .unwrap();
+        let tag = repo.create_tag("travel").await.unwrap();
+        repo.attach_tags(&[trip.clone(), tickets.clone()], &[tag.id])
+            .await
+            .unwrap();
+        repo.add_reminder(
+            &tickets,
+            ReminderTrigger::Relative {
+                offset_minutes: -60,
+            },
+        )
+        .await
+        .unwrap();
+        let document = repo.export_tasks(&ExportFilter::default()).await.unwrap();
+        let exported = |repo: &TaskRepository| {
+            let repo = repo.clone();
+            async move {
+                let document = repo.export_tasks(&ExportFilter::default()).await.unwrap();
+                serde_json::to_value(document.tasks).unwrap()
+            }
+        };
+        let original = exported(&repo).await;
+
+        for ids in [IdMode::Merge, IdMode::Remap] {
+            let options = ImportOptions { ids, dry_run: true };
+            let report = repo.import_tasks(&document, options).await.unwrap();
+            assert!(report.dry_run);
+            let counts = (report.created, report.updated, report.tags_created);
+            match ids {
+                IdMode::Merge => assert_eq!(counts, (0, 2, 0)),
+                IdMode::Remap => assert_eq!(counts, (2, 0, 0)),
+            }
+            assert!(report.warnings.is_empty());
+            assert_eq!(exported(&repo).await, original, "{ids:?} dry run");
+        }
+
+        // Merging the same document changes nothing
+        let options = ImportOptions {
+            ids: IdMode::Merge,
+            dry_run: false,
+        };
+        let report = repo.import_tasks(&document, options).await.unwrap();
+        assert_eq!((report.created, report.updated), (0, 2));
+        assert_eq!(exported(&repo).await, original);
+
+        // Remapping adds a copy with its own ids, tags and reminders
+        let options = ImportOptions {
+            ids: IdMode::Remap,
+            dry_run: false,
+        };
+        let report = repo.import_tasks(&document, options).await.unwrap();
+        assert_eq!((report.created, report.tags_created), (2, 0));
+        let tasks = repo
+            .export_tasks(&ExportFilter::default())
+            .await
+            .unwrap()
+            .tasks;
+        assert_eq!(tasks.len(), 4);
+        let copy = tasks
+            .iter()
+            .find(|task| task.name == "Trip" && task.id != trip)
+            .unwrap();
+        let copied_tickets = tasks
+            .iter()
+            .find(|task| task.name == "Tickets" && task.id != tickets)
+            .unwrap();
+        assert_eq!(copied_tickets.parent_id.as_ref(), Some(&copy.id));
+        assert_eq!(copy.notes.as_deref(), Some("Window seat"));
+        assert_eq!(copy.priority, Priority::High);
+        assert_eq!(copied_tickets.tags, ["travel"]);
+        assert!(matches!(
+            copied_tickets.reminders.as_deref(),
+            Some([ReminderTrigger::Relative {
+                offset_minutes: -60
+            }])
+        ));
+    }
+
+    #[tokio::test]
+    async fn undo_and_redo_restore_state() {
+        let repo = repo().await;
+        let id = add(&repo, "Draft", None).await;
+        repo.rename_task(&id, "Final").await.unwrap();
+
+        let undone = repo.undo().await.unwrap().unwrap();
+        assert_eq!(undone.kind, OperationKind::Rename);
+        assert_eq!(repo.task(&id).await.unwrap().name, "Draft");
+        repo.redo().await.unwrap().unwrap();
+        assert_eq!(repo.task(&id).await.unwrap().name, "Final");
+        assert!(repo.redo().await.unwrap().is_none());
+
+        // Nothing brings a purged task back
+        repo.delete_subtree(std::slice::from_ref(&id))
+            .await
+            .unwrap();
+        repo.purge_trash(None).await.unwrap();
+        assert!(matches!(
+            repo.undo().await,
+            Err(Error::HistoryConflict { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn undoing_a_create_keeps_later_subtasks() {
+        let repo = repo().await;
+        let parent = add(&repo, "Parent", None).await;
+        let child = add(&repo, "Child", Some(&parent)).await;
+        sqlx::query("DELETE FROM operations WHERE label = 'Child'")
+            .execute(&repo.pool())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            repo.undo().await,
+            Err(Error::HistoryConflict { .. })
+        ));
+        assert!(repo.task(&child).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn trash_restores_and_purges_whole_entries() {
+        let repo = repo().await;
+        let root = add(&repo, "Root", None).await;
+        let child = add(&repo, "Child", Some(&root)).await;
+        add(&repo, "Grandchild", Some(&child)).await;
+        let other = add(&repo, "Other", None).await;
+        let entries = |repo: TaskRepository| async move {
+            repo.trash()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| {
+                    let path: Vec<String> = entry.path.into_iter().map(|p| p.name).collect();
+                    (entry.task.name, entry.task_count, path)
+                })
+                .collect::<Vec<_>>()
+        };
+        // Deleted long enough ago to be purged
+        let backdate = |repo: TaskRepository| async move {
+            sqlx::query("UPDATE tasks SET deleted_at = '2026-01-01T00:00:00.000Z' WHERE deleted_at IS NOT NULL")
+                .execute(&repo.pool())
+                .await
+                .unwrap();
+        };
+
+        let ids = std::slice::from_ref(&child);
+        assert_eq!(repo.delete_subtree(ids).await.unwrap(), 2);
+        assert_eq!(
+            entries(repo.clone()).await,
+            [("Child".to_string(), 2, vec!["Root".to_string()])]
+        );
+        assert!(matches!(
+            repo.rename_task(&child, "Edited").await,
+            Err(Error::TaskNotFound(_))
+        ));
+        assert!(matches!(
+            repo.create_task("Late", Some(&child), None, None).await,
+            Err(Error::TaskNotFound(_))
+        ));
+        let restored = repo.restore_from_trash(&child).await.unwrap();
+        assert_eq!(restored.parent_id.as_ref(), Some(&root));
+        assert_eq!(all(&repo).await.len(), 4);
+
+        // With its parent trashed since, an entry comes back at the top level
+        repo.delete_subtree(ids).await.unwrap();
+        backdate(repo.clone()).await;
+        repo.delete_subtree(std::slice::from_ref(&root))
+            .await
+            .unwrap();
+        assert_eq!(
+            entries(repo.clone()).await,
+            [
+                ("Root".to_string(), 1, vec![]),
+                ("Child".to_string(), 2, vec![])
+            ]
+        );
+        let restored = repo.restore_from_trash(&child).await.unwrap();
+        assert_eq!(restored.parent_id, None);
+        assert!(matches!(
+            repo.restore_from_trash(&child).await,
+            Err(Error::TaskNotFound(_))
+        ));
+
+        // Retention only purges what was deleted before the cutoff
+        repo.delete_subtree(ids).await.unwrap();
+        backdate(repo.clone()).await;
+        repo.restore_from_trash(&root).await.unwrap();
+        repo.delete_subtree(std::slice::from_ref(&root))
+            .await
+            .unwrap();
+        let cutoff = TrashSettings::default().cutoff(Utc::now());
+        assert_eq!(repo.purge_trash(cutoff).await.unwrap(), 2);
+        assert_eq!(
+            entries(repo.clone()).await,
+            [("Root".to_string(), 1, vec![])]
+        );
+        assert_eq!(repo.purge_trash(None).await.unwrap(), 1);
+        assert!(repo.trash().await.unwrap().is_empty());
+        let left: Vec<String> = all(&repo).await.into_iter().map(|task| task.id).collect();
+        assert_eq!(left, [other]);
+    }
+
+    #[tokio::test]
+    async fn counts_only_later_due_dates_as_reschedules() {
+        let repo = repo().await;
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d);
+        let task = repo
+            .create_task("Report", None, day(20), None)
+            .await
+            .unwrap();
+        let ids = [task.id.clone()];
+        for due in [day(22), day(21), day(25), None, day(26)] {
+            repo.set_due_date(&ids, due, None).await.unwrap();
+        }
+        repo.undo().await.unwrap();
+        repo.redo().await.unwrap();
+
+        let rescheduled = repo.rescheduled_tasks(1).await.unwrap();
+        assert_eq!(rescheduled.len(), 1);
+        assert_eq!(rescheduled[0].times_rescheduled, 2);
+        assert_eq!(rescheduled[0].original_due_date, day(20));
+
+        let history = repo.task_history(&task.id).await.unwrap();
+        assert_eq!(history.times_rescheduled, 2);
+        let steps: Vec<HistoryStep> = history.events.iter().filter_map(|e| e.step).collect();
+        assert_eq!(steps, [HistoryStep::Undo, HistoryStep::Redo]);
+        assert!(history.events.iter().all(|e| e.origin == Origin::Gui));
+        assert!(repo.rescheduled_tasks(3).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn updates_every_field_as_one_step() {
+        let repo = repo().await;
+        let parent = add(&repo, "Trip", None).await;
+        let id = add(&repo, "Tickets", Some(&parent)).await;
+        let day = NaiveDate::from_ymd_opt(2026, 11, 2);
+        let update = TaskUpdate {
+            name: Some("Train tickets".to_string()),
+            notes: Some("Window seat".to_string()),
+            priority: Some(Priority::High),
+            completed: Some(true),
+            due_date: Some(day),
+            due_time: None,
+        };
+
+        let changed = repo.update_task(&id, &update).await.unwrap();
+        let ids: Vec<&str> = changed.iter().map(|task| task.id.as_str()).collect();
+        assert_eq!(ids, [id.as_str(), parent.as_str()]);
+        let task = &changed[0];
+        assert_eq!(task.name, "Train tickets");
+        assert_eq!(
+            (task.due_date, task.priority, task.completed),
+            (day, Priority::High, true)
+        );
+        assert!(changed[1].completed);
+        assert_eq!(repo.notes(&id).await.unwrap(), "Window seat");
+
+        let undone = repo.undo().await.unwrap().unwrap();
+        assert_eq!(undone.kind, OperationKind::Edit);
+        let task = repo.task(&id).await.unwrap();
+        assert_eq!(
+            (task.name.as_str(), task.due_date, task.completed),
+            ("Tickets", None, false)
+        );
+        assert_eq!(repo.notes(&id).await.unwrap(), "");
+        assert!(!repo.task(&parent).await.unwrap().completed);
+
+        // Nothing is written when a field is refused
+        let update = TaskUpdate {
+            name: Some("Renamed".to_string()),
+            due_time: Some(NaiveTime::from_hms_opt(9, 0, 0)),
+            ..TaskUpdate::default()
+        };
+        assert!(matches!(
+            repo.update_task(&id, &update).await,
+            Err(Error::DueTimeWithoutDate)
+        ));
+        assert_eq!(repo.task(&id).await.unwrap().name, "Tickets");
+
+        // Notes and priority alone stay out of the undo history
+        let operations = || async {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM operations")
+                .fetch_one(&repo.pool())
+                .await
+                .unwrap()
+        };
+        let recorded = operations().await;
+        let update = TaskUpdate {
+            notes: Some("Aisle".to_string()),
+            priority: Some(Priority::Low),
+            ..TaskUpdate::default()
+        };
+        let changed = repo.update_task(&id, &update).await.unwrap();
+        assert_eq!(changed[0].priority, Priority::Low);
+        assert_eq!(operations().await, recorded);
+        assert!(matches!(
+            repo.update_task("missing", &TaskUpdate::default()).await,
+            Err(Error::TaskNotFound(_))
+        ));
+    }
+}
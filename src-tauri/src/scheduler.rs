@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::backup::BackupReason;
+use crate::profile::Profiles;
+use crate::repository::TaskRepository;
+
+/// How often pending reminders are checked
+const TICK: Duration = Duration::from_secs(30);
+
+/// How often the age of the newest backup is checked
+const BACKUP_TICK: Duration = Duration::from_secs(60);
+
+/// How often the trash is checked for entries past their retention
+const TRASH_TICK: Duration = Duration::from_secs(60 * 60);
+
+/// Reminders older than this when delivered are presented as missed
+const MISSED_AFTER: chrono::Duration = chrono::Duration::minutes(5);
+
+/// Deliver reminders in the background for as long as the app runs. The
+/// first check happens right away, catching up on reminders that came due
+/// while the app was closed
+pub fn spawn(app: AppHandle, repo: TaskRepository) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = deliver_due(&app, &repo).await {
+                eprintln!("failed to deliver reminders: {e}");
+            }
+        }
+    });
+}
+
+async fn deliver_due(app: &AppHandle, repo: &TaskRepository) -> crate::Result<()> {
+    let now = Utc::now();
+    for reminder in repo.due_reminders(now).await? {
+        let missed = reminder
+            .fires_at
+            .is_some_and(|fires_at| now - fires_at > MISSED_AFTER);
+        let body = if missed {
+            "Missed reminder"
+        } else {
+            "Reminder"
+        };
+
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title(&reminder.task_name)
+            .body(body)
+            .show()
+        {
+            // Leave it undelivered so the next tick retries
+            eprintln!("failed to show reminder {}: {e}", reminder.id);
+            continue;
+        }
+        repo.mark_reminder_delivered(&reminder.id, now).await?;
+    }
+    Ok(())
+}
+
+/// Back up the open profile right away, then whenever its newest backup is
+/// older than the configured interval
+pub fn spawn_backups(repo: TaskRepository, profiles: Profiles) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = profiles.backup(&repo, BackupReason::Startup).await {
+            eprintln!("failed to back up the database: {e}");
+        }
+
+        let mut interval = tokio::time::interval(BACKUP_TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes immediately, right after the startup backup
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = backup_if_due(&repo, &profiles).await {
+                eprintln!("failed to back up the database: {e}");
+            }
+        }
+    });
+}
+
+async fn backup_if_due(repo: &TaskRepository, profiles: &Profiles) -> crate::Result<()> {
+    let settings = repo.backup_settings().await?;
+    if settings.interval_minutes == 0 {
+        return Ok(());
+    }
+
+    let interval = chrono::Duration::minutes(i64::from(settings.interval_minutes));
+    let newest = profiles.backups().await?.first().map(|b| b.created_at);
+    if newest.is_none_or(|created_at| Utc::now() - created_at >= interval) {
+        profiles.backup(repo, BackupReason::Scheduled).await?;
+    }
+    Ok(())
+}
+
+/// Purge the trash of entries older than the configured retention, at
+/// startup and then every hour
+pub fn spawn_trash_purge(repo: TaskRepository) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TRASH_TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = purge_expired(&repo).await {
+                eprintln!("failed to purge the trash: {e}");
+            }
+        }
+    });
+}
+
+async fn purge_expired(repo: &TaskRepository) -> crate::Result<()> {
+    if let Some(cutoff) = repo.trash_settings().await?.cutoff(Utc::now()) {
+        repo.purge_trash(Some(cutoff)).await?;
+    }
+    Ok(())
+}
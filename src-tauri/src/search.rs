@@ -0,0 +1,88 @@
+use serde::Serialize;
+
+use crate::task::Task;
+
+/// Marks FTS5 puts around matched terms before they become `<mark>` tags,
+/// so the rest of the text can be HTML-escaped first
+pub const MATCH_START: &str = "\u{2}";
+pub const MATCH_END: &str = "\u{3}";
+
+/// An ancestor of a search hit, from the root down
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PathEntry {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub task: Task,
+    /// The task name as HTML with matched terms in `<mark>`
+    pub name_html: String,
+    /// An excerpt of the notes around the matches, when they matched
+    pub notes_html: Option<String>,
+    /// Lower is a better match
+    pub rank: f64,
+    pub path: Vec<PathEntry>,
+}
+
+/// Turn free text into an FTS5 query: every word must match, the last one as
+/// a prefix so results follow typing. Words are quoted so FTS5 operators in
+/// the input are searched for literally. `None` when there is nothing to find
+pub fn fts_query(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    let last = words.len().checked_sub(1)?;
+
+    Some(
+        words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                if i == last {
+                    format!("{word}*")
+                } else {
+                    word.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Escape FTS5 output for HTML and turn the match markers into `<mark>` tags
+pub fn highlight_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+        .replace(MATCH_START, "<mark>")
+        .replace(MATCH_END, "</mark>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_words_and_prefixes_the_last() {
+        assert_eq!(fts_query(" \t "), None);
+        assert_eq!(fts_query("buy mil").as_deref(), Some(r#""buy" "mil"*"#));
+        assert_eq!(
+            fts_query(r#"say "hi" OR NOT*"#).as_deref(),
+            Some(r#""say" """hi""" "OR" "NOT*"*"#)
+        );
+    }
+
+    #[test]
+    fn escapes_before_marking() {
+        assert_eq!(
+            highlight_html(&format!("<b>{MATCH_START}Tom & Jerry's{MATCH_END}</b>")),
+            "&lt;b&gt;<mark>Tom &amp; Jerry&#39;s</mark>&lt;/b&gt;"
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    #[sqlx(default)]
+    pub task_count: i64,
+}
+
+/// Limits loaded tasks to those carrying some or all of `tag_ids`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagFilter {
+    pub tag_ids: Vec<String>,
+    #[serde(default)]
+    pub mode: TagMatch,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    /// At least one of the tags
+    #[default]
+    Any,
+    /// Every one of the tags
+    All,
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::recurrence::RepeatFrom;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    pub completed: bool,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    /// Calendar day the task is due, `None` for someday tasks
+    pub due_date: Option<NaiveDate>,
+    /// Wall-clock deadline on `due_date`, `None` when due any time that day
+    pub due_time: Option<NaiveTime>,
+    #[sqlx(rename = "task_order")]
+    pub order: i64,
+    pub priority: Priority,
+    /// RFC 5545 RRULE, `None` for one-off tasks
+    pub recurrence: Option<String>,
+    pub repeat_from: RepeatFrom,
+    /// 1-based position in the recurring series
+    pub occurrence: i64,
+    #[sqlx(default)]
+    pub completed_subtasks: i64,
+    #[sqlx(default)]
+    pub total_subtasks: i64,
+    /// Incomplete and past its deadline, filled in by the repository
+    #[sqlx(skip)]
+    pub overdue: bool,
+}
+
+impl Task {
+    /// Whether the task is still open past its due day, or past its due
+    /// time on that day. `now` is the wall-clock time in the user's timezone
+    pub fn is_overdue(&self, now: NaiveDateTime) -> bool {
+        let Some(due_date) = self.due_date else {
+            return false;
+        };
+        !self.completed
+            && match self.due_time {
+                Some(due_time) => due_date.and_time(due_time) < now,
+                None => due_date < now.date(),
+            }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type,
+)]
+#[serde(rename_all = "lowercase")]
+#[repr(i32)]
+pub enum Priority {
+    #[default]
+    None = 0,
+    Low = 1,
+    Medium = 2,
+    High = 3,
+    Urgent = 4,
+}
+
+/// Fields to change on one task at once, leaving out those that stay. The
+/// API's `PATCH /tasks/{id}` body
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskUpdate {
+    pub name: Option<String>,
+    pub notes: Option<String>,
+    pub priority: Option<Priority>,
+    /// Completing or reopening affects ancestors like a toggle
+    pub completed: Option<bool>,
+    /// Null makes it a someday task
+    #[serde(default, deserialize_with = "present")]
+    pub due_date: Option<Option<NaiveDate>>,
+    #[serde(default, deserialize_with = "present")]
+    pub due_time: Option<Option<NaiveTime>>,
+}
+
+/// Tell a field set to null from one left out, which stays `None`
+fn present<'de, D, T>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
+
+/// How `load_tasks` orders siblings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskSort {
+    /// Overdue first, then the manual `task_order`
+    #[default]
+    Manual,
+    /// Highest priority first, then overdue, then the manual `task_order`
+    Priority,
+}
+
+/// Format a timestamp the way the webview stores them (`Date.toISOString()`),
+/// so string comparisons in SQL stay consistent with existing rows
+pub fn to_sql_timestamp(instant: &DateTime<Utc>) -> String {
+    instant.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
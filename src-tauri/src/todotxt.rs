@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, SecondsFormat, Timelike, Utc};
+use uuid::Uuid;
+
+use crate::date_filter::Zone;
+use crate::export::{Document, ExportFilter, ExportedTask, ImportOptions, ImportReport};
+use crate::recurrence::RepeatFrom;
+use crate::repository::TaskRepository;
+use crate::task::Priority;
+use crate::{Error, Result};
+
+const DATE: &str = "%Y-%m-%d";
+
+/// `key:value` pairs the parser takes out of a line, so name words that look
+/// like one have to be encoded
+const KEYS: &[&str] = &[
+    "id",
+    "parent",
+    "name",
+    "created",
+    "completed",
+    "due",
+    "time",
+    "pri",
+    "rrule",
+    "repeat",
+    "occurrence",
+];
+
+/// Tasks selected by `filter` as a todo.txt file
+pub async fn export(repo: &TaskRepository, filter: &ExportFilter) -> Result<String> {
+    let zone = repo.timezone().await?;
+    let document = repo.export_tasks(filter).await?;
+    Ok(serialize(&document.tasks, &zone))
+}
+
+/// Import a todo.txt file like a JSON document, ids from `id:` keys and
+/// fresh ones for lines without
+pub async fn import(
+    repo: &TaskRepository,
+    text: &str,
+    options: ImportOptions,
+) -> Result<ImportReport> {
+    let zone = repo.timezone().await?;
+    let document = Document::new(parse(text, &zone)?);
+    repo.import_tasks(&document, options).await
+}
+
+/// One todo.txt line per task, days taken in `zone`. Besides the completion
+/// mark, dates, priority and `+project`/`@context` tags, `key:value` pairs
+/// carry what todo.txt has no syntax for: `id:` and `parent:` the hierarchy,
+/// `created:` and `completed:` the exact timestamps, and `due:`, `time:`,
+/// `pri:` (completed tasks), `rrule:`, `repeat:` and `occurrence:` the rest.
+/// A name that would not read back as written, e.g. one with a `+word` or a
+/// run of spaces, goes percent-encoded in `name:` instead. Lines are in
+/// outline order so sibling order survives too. Notes and reminders do not
+/// fit on a line, and tags join their words by `_`
+pub fn serialize(tasks: &[ExportedTask], zone: &Zone) -> String {
+    let day = |instant: &DateTime<Utc>| zone.local_time(*instant).date().format(DATE);
+
+    let mut out = String::new();
+    for task in tasks {
+        let mut tokens: Vec<String> = Vec::new();
+        if task.completed {
+            tokens.push("x".to_string());
+            let completed_at = task.completed_at.unwrap_or(task.created_at);
+            tokens.push(day(&completed_at).to_string());
+        } else if let Some(letter) = priority_letter(task.priority) {
+            tokens.push(format!("({letter})"));
+        }
+        tokens.push(day(&task.created_at).to_string());
+        if is_plain(&task.name) {
+            tokens.extend(task.name.split_whitespace().map(str::to_string));
+        }
+
+        for tag in &task.tags {
+            let tag = tag.split_whitespace().collect::<Vec<_>>().join("_");
+            if tag.starts_with('@') {
+                tokens.push(tag);
+            } else {
+                tokens.push(format!("+{tag}"));
+            }
+        }
+        if let Some(due_date) = task.due_date {
+            tokens.push(format!("due:{}", due_date.format(DATE)));
+        }
+        if let Some(due_time) = task.due_time {
+            let format = if due_time.second() == 0 {
+                "%H:%M"
+            } else {
+                "%H:%M:%S"
+            };
+            tokens.push(format!("time:{}", due_time.format(format)));
+        }
+        if let Some(letter) = priority_letter(task.priority).filter(|_| task.completed) {
+            tokens.push(format!("pri:{letter}"));
+        }
+        if let Some(rule) = &task.recurrence {
+            tokens.push(format!("rrule:{rule}"));
+            if task.repeat_from == RepeatFrom::Completion {
+                tokens.push("repeat:completion".to_string());
+            }
+        }
+        if task.occurrence != 1 {
+            tokens.push(format!("occurrence:{}", task.occurrence));
+        }
+        if !is_plain(&task.name) {
+            tokens.push(format!("name:{}", encode(&task.name)));
+        }
+        tokens.push(format!(
+            "created:{}",
+            task.created_at.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+        ));
+        if let Some(completed_at) = task.completed_at.filter(|_| task.completed) {
+            tokens.push(format!(
+                "completed:{}",
+                completed_at.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            ));
+        }
+        tokens.push(format!("id:{}", task.id));
+        if let Some(parent_id) = &task.parent_id {
+            tokens.push(format!("parent:{parent_id}"));
+        }
+
+        out.push_str(&tokens.join(" "));
+        out.push('\n');
+    }
+    out
+}
+
+/// Parse a todo.txt file, blank lines skipped. Days become midnight in
+/// `zone`, and `task_order` follows the line order among siblings. Tasks
+/// get no notes or reminders, so importing keeps the ones they have
+pub fn parse(text: &str, zone: &Zone) -> Result<Vec<ExportedTask>> {
+    let mut tasks = Vec::new();
+    let mut siblings: HashMap<Option<String>, i64> = HashMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |reason: String| Error::InvalidExport(format!("line {}: {reason}", i + 1));
+        let mut task = parse_line(line, zone).map_err(invalid)?;
+
+        let next = siblings.entry(task.parent_id.clone()).or_default();
+        task.order = *next;
+        *next += 1;
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+fn parse_line(line: &str, zone: &Zone) -> std::result::Result<ExportedTask, String> {
+    let midnight = |day: NaiveDate| zone.to_utc(day.and_time(NaiveTime::MIN));
+    let mut tokens = line.split_whitespace().peekable();
+
+    let completed = tokens.next_if_eq(&"x").is_some();
+    let mut priority = Priority::None;
+    let mut completed_at = None;
+    if completed {
+        completed_at = tokens.next_if(|token| parse_day(token).is_some());
+    } else if let Some(token) = tokens.next_if(|token| parse_priority(token).is_some()) {
+        priority = parse_priority(token).unwrap_or_default();
+    }
+    let created_at = tokens.next_if(|token| parse_day(token).is_some());
+
+    let mut task = ExportedTask {
+        id: String::new(),
+        parent_id: None,
+        name: String::new(),
+        notes: None,
+        completed,
+        completed_at: completed_at.and_then(parse_day).map(midnight),
+        created_at: created_at
+            .and_then(parse_day)
+            .map_or_else(Utc::now, midnight),
+        due_date: None,
+        due_time: None,
+        order: 0,
+        priority,
+        recurrence: None,
+        repeat_from: RepeatFrom::Due,
+        occurrence: 1,
+        tags: Vec::new(),
+        reminders: None,
+    };
+    if task.completed && task.completed_at.is_none() {
+        task.completed_at = Some(Utc::now());
+    }
+
+    let mut name = None;
+    let mut words = Vec::new();
+    for token in tokens {
+        if let Some(tag) = token.strip_prefix('+').filter(|tag| !tag.is_empty()) {
+            task.tags.push(tag.to_string());
+            continue;
+        }
+        if token.len() > 1 && token.starts_with('@') {
+            task.tags.push(token.to_string());
+            continue;
+        }
+
+        let Some((key, value)) = token.split_once(':').filter(|(_, value)| !value.is_empty())
+        else {
+            words.push(token);
+            continue;
+        };
+        match key {
+            "id" => task.id = value.to_string(),
+            "name" => name = Some(decode(value).ok_or_else(|| format!("invalid name {value}"))?),
+            "created" => task.created_at = parse_timestamp(value)?,
+            "completed" if task.completed => task.completed_at = Some(parse_timestamp(value)?),
+            "parent" => task.parent_id = Some(value.to_string()),
+            "due" => {
+                task.due_date =
+                    Some(parse_day(value).ok_or_else(|| format!("invalid due date {value}"))?)
+            }
+            "time" => {
+                task.due_time = Some(
+                    NaiveTime::parse_from_str(value, "%H:%M")
+                        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"))
+                        .map_err(|_| format!("invalid time {value}"))?,
+                )
+            }
+            "pri" => {
+                task.priority = parse_priority(&format!("({value})"))
+                    .ok_or_else(|| format!("invalid priority {value}"))?
+            }
+            "rrule" => task.recurrence = Some(value.to_string()),
+            "repeat" if value == "completion" => task.repeat_from = RepeatFrom::Completion,
+            "occurrence" => {
+                task.occurrence = value
+                    .parse()
+                    .ok()
+                    .filter(|occurrence| *occurrence > 0)
+                    .ok_or_else(|| format!("invalid occurrence {value}"))?
+            }
+            // Anything else, e.g. a URL, is part of the name
+            _ => words.push(token),
+        }
+    }
+
+    // Stray words next to an encoded name are kept after it
+    task.name = match name {
+        Some(name) if words.is_empty() => name,
+        Some(name) => format!("{name} {}", words.join(" ")),
+        None => words.join(" "),
+    };
+    if task.id.is_empty() {
+        task.id = Uuid::new_v4().to_string();
+    }
+    Ok(task)
+}
+
+fn parse_day(token: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(token, DATE).ok()
+}
+
+fn parse_timestamp(value: &str) -> std::result::Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|instant| instant.with_timezone(&Utc))
+        .map_err(|_| format!("invalid timestamp {value}"))
+}
+
+/// Whether `name` reads back unchanged from its words: single spaces only,
+/// nothing taken for a tag or `key:value`, and no leading completion mark,
+/// priority or date
+fn is_plain(name: &str) -> bool {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    if words.join(" ") != name {
+        return false;
+    }
+    let first = words.first().copied().unwrap_or_default();
+    if first == "x" || parse_day(first).is_some() || parse_priority(first).is_some() {
+        return false;
+    }
+    words.iter().all(|word| {
+        let tag = word.len() > 1 && (word.starts_with('+') || word.starts_with('@'));
+        let key = word
+            .split_once(':')
+            .is_some_and(|(key, value)| !value.is_empty() && KEYS.contains(&key));
+        !tag && !key
+    })
+}
+
+/// Percent-encode `%` and whitespace so `value` stays one token
+fn encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '%' || c.is_whitespace() {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                out.push_str(&format!("%{byte:02X}"));
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// `(A)` is the most urgent; Act has four levels, so `(D)` and below are low
+fn parse_priority(token: &str) -> Option<Priority> {
+    let letter = token.strip_prefix('(')?.strip_suffix(')')?;
+    match letter {
+        "A" => Some(Priority::Urgent),
+        "B" => Some(Priority::High),
+        "C" => Some(Priority::Medium),
+        _ if letter.len() == 1 && letter.chars().all(|c| c.is_ascii_uppercase()) => {
+            Some(Priority::Low)
+        }
+        _ => None,
+    }
+}
+
+fn priority_letter(priority: Priority) -> Option<char> {
+    match priority {
+        Priority::None => None,
+        Priority::Low => Some('D'),
+        Priority::Medium => Some('C'),
+        Priority::High => Some('B'),
+        Priority::Urgent => Some('A'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, name: &str) -> ExportedTask {
+        ExportedTask {
+            id: id.to_string(),
+            parent_id: None,
+            name: name.to_string(),
+            notes: None,
+            completed: false,
+            completed_at: None,
+            created_at: "2026-10-17T06:40:41.856Z".parse().unwrap(),
+            due_date: None,
+            due_time: None,
+            order: 0,
+            priority: Priority::None,
+            recurrence: None,
+            repeat_from: RepeatFrom::Due,
+            occurrence: 1,
+            tags: Vec::new(),
+            reminders: None,
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let zone = Zone::parse("Europe/Zurich").unwrap();
+        let names = [
+            "Write report",
+            "Buy +milk and @bread",
+            "Call about due:friday",
+            "pri:A id:7 name:x",
+            "x marks the spot",
+            "(A) plan",
+            "2026-10-17 retro",
+            "Two  spaces\tand a tab ",
+            " leading",
+            "100% done",
+            "https://example.com/a?b=c",
+            "Grüße",
+            "",
+        ];
+        let mut tasks: Vec<ExportedTask> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mut task = task(&format!("task-{i}"), name);
+                task.order = i as i64;
+                task
+            })
+            .collect();
+
+        let mut done = task("done", "Finished");
+        done.completed = true;
+        done.completed_at = Some("2026-10-17T21:30:05.123Z".parse().unwrap());
+        done.priority = Priority::High;
+        done.order = tasks.len() as i64;
+        tasks.push(done);
+
+        let mut child = task("child", "Sub +task");
+        child.parent_id = Some("task-0".to_string());
+        child.due_date = Some("2026-10-20".parse().unwrap());
+        child.due_time = Some("09:30:15".parse().unwrap());
+        child.priority = Priority::Low;
+        child.recurrence = Some("FREQ=WEEKLY;BYDAY=MO".to_string());
+        child.repeat_from = RepeatFrom::Completion;
+        child.occurrence = 3;
+        child.tags = vec!["work".to_string(), "@home".to_string()];
+        tasks.push(child);
+
+        let parsed = parse(&serialize(&tasks, &zone), &zone).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&tasks).unwrap()
+        );
+    }
+
+    #[test]
+    fn reads_plain_todo_txt() {
+        let zone = Zone::parse("UTC").unwrap();
+        let tasks = parse(
+            "x 2026-10-16 2026-10-01 Pay rent +home due:2026-10-31\n\n(B) Email @work",
+            &zone,
+        )
+        .unwrap();
+
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks[0].completed);
+        assert_eq!(tasks[0].name, "Pay rent");
+        assert_eq!(tasks[0].tags, ["home"]);
+        assert_eq!(tasks[0].due_date, "2026-10-31".parse().ok());
+        assert_eq!(
+            tasks[0].completed_at,
+            "2026-10-16T00:00:00Z".parse::<DateTime<Utc>>().ok()
+        );
+        assert_eq!(tasks[1].name, "Email");
+        assert_eq!(tasks[1].priority, Priority::High);
+        assert_eq!(tasks[1].tags, ["@work"]);
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        let zone = Zone::parse("UTC").unwrap();
+        for line in ["a due:tomorrow", "a name:%zz", "a created:yesterday"] {
+            assert!(parse(line, &zone).is_err(), "{line}");
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::search::PathEntry;
+use crate::task::Task;
+
+/// A task deleted together with its subtasks, as the trash lists it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    /// Subtask counts cover the subtasks deleted along with it
+    pub task: Task,
+    pub deleted_at: DateTime<Utc>,
+    /// Rows a restore brings back, the task included
+    pub task_count: i64,
+    /// Where a restore puts it back, from the root down. Empty for the top
+    /// level, which is also where it goes when its parent is gone
+    pub path: Vec<PathEntry>,
+}
+
+/// How long deleted tasks stay in the trash
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashSettings {
+    /// Days after which the trash is purged, 0 to keep deleted tasks until
+    /// purged by hand
+    pub retention_days: u32,
+}
+
+impl TrashSettings {
+    /// Deletion time before which entries are due for purging, `None` when
+    /// they are kept
+    pub fn cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        (self.retention_days > 0).then(|| now - Duration::days(self.retention_days.into()))
+    }
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        TrashSettings { retention_days: 30 }
+    }
+}
@@ -0,0 +1,685 @@
+use chrono::{NaiveDate, NaiveTime, Utc};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::cli::{due_label, parse_day, parse_time};
+use crate::date_filter::DateFilterEntry;
+use crate::history::Operation;
+use crate::repository::TaskRepository;
+use crate::task::{Task, TaskSort};
+use crate::Result;
+
+const SIDEBAR_WIDTH: u16 = 26;
+
+/// Narrowest a task column gets before the leftmost ones scroll out of view
+const COLUMN_WIDTH: u16 = 32;
+
+const HELP: &str =
+    "hjkl move  space done  n add  d due  ⌫ delete  J/K reorder  u/^R undo/redo  t completed  q quit";
+
+/// Browse and edit tasks in the terminal, laid out like the window: the
+/// date filters on the left, then a column of subtasks per level
+pub async fn run(repo: &TaskRepository) -> Result<()> {
+    let mut app = App::new(repo);
+    app.reload().await?;
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal).await;
+    ratatui::restore();
+    result
+}
+
+#[derive(PartialEq)]
+enum Focus {
+    Sidebar,
+    Tasks,
+}
+
+enum Mode {
+    Normal,
+    /// Typing into the status line
+    Input {
+        prompt: Prompt,
+        text: String,
+    },
+    /// Waiting for y/n before deleting a task with subtasks
+    ConfirmDelete {
+        id: String,
+        name: String,
+    },
+}
+
+enum Prompt {
+    /// Name of a new task under the parent, `None` at the top level
+    Add { parent_id: Option<String> },
+    /// Due day and optional time of a task, as `act add --due` takes them
+    Due { id: String },
+}
+
+impl Prompt {
+    fn label(&self) -> &'static str {
+        match self {
+            Prompt::Add { .. } => "New task: ",
+            Prompt::Due { .. } => "Due day [HH:MM], empty for someday: ",
+        }
+    }
+}
+
+struct App<'a> {
+    repo: &'a TaskRepository,
+    today: NaiveDate,
+    filters: Vec<DateFilterEntry>,
+    filter_index: usize,
+    /// Tasks matching the selected filter, with their ancestors
+    tasks: Vec<Task>,
+    show_completed: bool,
+    focus: Focus,
+    /// Selected row of each open column, the focused column last
+    cursor: Vec<usize>,
+    mode: Mode,
+    /// Outcome of the last key, shown instead of the help line
+    message: Option<String>,
+    quit: bool,
+}
+
+impl<'a> App<'a> {
+    fn new(repo: &'a TaskRepository) -> Self {
+        Self {
+            repo,
+            today: Utc::now().date_naive(),
+            filters: Vec::new(),
+            filter_index: 0,
+            tasks: Vec::new(),
+            show_completed: true,
+            focus: Focus::Tasks,
+            cursor: vec![0],
+            mode: Mode::Normal,
+            message: None,
+            quit: false,
+        }
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            self.message = None;
+            if let Err(e) = self.handle_key(key).await {
+                self.message = Some(e.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Load the sidebar and the selected filter's tasks again, keeping the
+    /// selection on the same tasks where they are still listed
+    async fn reload(&mut self) -> Result<()> {
+        let path = self.path();
+        let filter = self
+            .filters
+            .get(self.filter_index)
+            .map(|e| e.filter.clone());
+
+        self.today = self.repo.timezone().await?.local_time(Utc::now()).date();
+        self.filters = self.repo.date_filters().await?;
+        // Days leave the sidebar once their last task is gone
+        self.filter_index = filter
+            .and_then(|filter| self.filters.iter().position(|e| e.filter == filter))
+            .unwrap_or(0);
+        self.tasks = self
+            .repo
+            .load_tasks(&self.entry().filter, None, TaskSort::Manual)
+            .await?;
+
+        self.restore(path);
+        Ok(())
+    }
+
+    /// Ids of the selected task in each open column
+    fn path(&self) -> Vec<Option<String>> {
+        (0..self.cursor.len())
+            .map(|level| self.selected(level).map(|task| task.id.clone()))
+            .collect()
+    }
+
+    /// Point the cursor at the tasks on `path` again, or at their old rows
+    /// when they are gone, closing the columns beyond
+    fn restore(&mut self, path: Vec<Option<String>>) {
+        let rows = std::mem::take(&mut self.cursor);
+        for (level, id) in path.into_iter().enumerate() {
+            let column = self.column(level);
+            let found = id.and_then(|id| column.iter().position(|task| task.id == id));
+            let row = found.unwrap_or(rows[level].min(column.len().saturating_sub(1)));
+            self.cursor.push(row);
+            if found.is_none() {
+                break;
+            }
+        }
+        if self.cursor.is_empty() {
+            self.cursor.push(0);
+        }
+    }
+
+    fn entry(&self) -> &DateFilterEntry {
+        &self.filters[self.filter_index]
+    }
+
+    fn depth(&self) -> usize {
+        self.cursor.len() - 1
+    }
+
+    fn children(&self, parent_id: Option<&str>) -> Vec<&Task> {
+        self.tasks
+            .iter()
+            .filter(|task| task.parent_id.as_deref() == parent_id)
+            .filter(|task| self.show_completed || !task.completed)
+            .collect()
+    }
+
+    /// Tasks in the column at `level`, the subtasks of the task selected
+    /// one column to the left
+    fn column(&self, level: usize) -> Vec<&Task> {
+        match level.checked_sub(1) {
+            None => self.children(None),
+            Some(left) => match self.selected(left) {
+                Some(parent) => self.children(Some(&parent.id)),
+                None => Vec::new(),
+            },
+        }
+    }
+
+    fn selected(&self, level: usize) -> Option<&Task> {
+        let row = *self.cursor.get(level)?;
+        self.column(level).get(row).copied()
+    }
+
+    /// The task under the cursor when the task columns have focus
+    fn focused(&self) -> Option<&Task> {
+        match self.focus {
+            Focus::Sidebar => None,
+            Focus::Tasks => self.selected(self.depth()),
+        }
+    }
+
+    async fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return Ok(());
+        }
+        match self.mode {
+            Mode::Normal => self.normal_key(key).await,
+            Mode::Input { .. } => self.input_key(key).await,
+            Mode::ConfirmDelete { .. } => {
+                let Mode::ConfirmDelete { id, .. } =
+                    std::mem::replace(&mut self.mode, Mode::Normal)
+                else {
+                    unreachable!();
+                };
+                if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+                    self.delete(id).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn normal_key(&mut self, key: KeyEvent) -> Result<()> {
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('r') if control => {
+                let operation = self.repo.redo().await?;
+                self.after_step(operation, "redid", "nothing to redo")
+                    .await?;
+            }
+            KeyCode::Char('u') => {
+                let operation = self.repo.undo().await?;
+                self.after_step(operation, "undid", "nothing to undo")
+                    .await?;
+            }
+            KeyCode::Char('J') => self.reorder(1).await?,
+            KeyCode::Char('K') => self.reorder(-1).await?,
+            KeyCode::Down if shift => self.reorder(1).await?,
+            KeyCode::Up if shift => self.reorder(-1).await?,
+            KeyCode::Char('j') | KeyCode::Down => self.step(1).await?,
+            KeyCode::Char('k') | KeyCode::Up => self.step(-1).await?,
+            KeyCode::Char('h') | KeyCode::Left => match self.focus {
+                Focus::Tasks if self.depth() > 0 => {
+                    self.cursor.pop();
+                }
+                _ => self.focus = Focus::Sidebar,
+            },
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Enter => match self.focus {
+                Focus::Sidebar => self.focus = Focus::Tasks,
+                // Also into a task without subtasks, to add the first one
+                Focus::Tasks if self.focused().is_some() => self.cursor.push(0),
+                Focus::Tasks => {}
+            },
+            KeyCode::Char(' ') => {
+                if let Some(task) = self.focused() {
+                    let id = task.id.clone();
+                    self.repo.toggle_task(&id).await?;
+                    self.reload().await?;
+                }
+            }
+            KeyCode::Char('t') => {
+                let path = self.path();
+                self.show_completed = !self.show_completed;
+                self.restore(path);
+            }
+            KeyCode::Char('n' | 'i') => {
+                let parent_id = match self.focus {
+                    Focus::Sidebar => None,
+                    Focus::Tasks => self
+                        .depth()
+                        .checked_sub(1)
+                        .and_then(|left| self.selected(left))
+                        .map(|parent| parent.id.clone()),
+                };
+                self.mode = Mode::Input {
+                    prompt: Prompt::Add { parent_id },
+                    text: String::new(),
+                };
+            }
+            KeyCode::Char('d') => {
+                if let Some(task) = self.focused() {
+                    let mut text = task
+                        .due_date
+                        .map(|day| day.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default();
+                    if let Some(time) = task.due_time {
+                        text.push_str(&time.format(" %H:%M").to_string());
+                    }
+                    self.mode = Mode::Input {
+                        prompt: Prompt::Due {
+                            id: task.id.clone(),
+                        },
+                        text,
+                    };
+                }
+            }
+            KeyCode::Backspace | KeyCode::Delete => {
+                if let Some(task) = self.focused() {
+                    let id = task.id.clone();
+                    if task.total_subtasks > 0 {
+                        self.mode = Mode::ConfirmDelete {
+                            id,
+                            name: task.name.clone(),
+                        };
+                    } else {
+                        self.delete(id).await?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Move the selection in the focused column, or to another filter
+    async fn step(&mut self, delta: isize) -> Result<()> {
+        match self.focus {
+            Focus::Sidebar => {
+                let last = self.filters.len() - 1;
+                let index = self.filter_index.saturating_add_signed(delta).min(last);
+                if index != self.filter_index {
+                    self.filter_index = index;
+                    self.cursor = vec![0];
+                    self.reload().await?;
+                }
+            }
+            Focus::Tasks => {
+                let depth = self.depth();
+                let last = self.column(depth).len().saturating_sub(1);
+                self.cursor[depth] = self.cursor[depth].saturating_add_signed(delta).min(last);
+            }
+        }
+        Ok(())
+    }
+
+    /// Swap the focused task with its neighbour in the column
+    async fn reorder(&mut self, delta: isize) -> Result<()> {
+        if self.focus != Focus::Tasks {
+            return Ok(());
+        }
+        let depth = self.depth();
+        let column = self.column(depth);
+        let row = self.cursor[depth];
+        let Some(target) = row
+            .checked_add_signed(delta)
+            .filter(|&target| target < column.len())
+        else {
+            return Ok(());
+        };
+
+        let parent_id = column[row].parent_id.clone();
+        let mut ids: Vec<String> = column.iter().map(|task| task.id.clone()).collect();
+        ids.swap(row, target);
+        self.repo.reorder_tasks(&ids, parent_id.as_deref()).await?;
+        self.reload().await
+    }
+
+    async fn after_step(
+        &mut self,
+        operation: Option<Operation>,
+        verb: &str,
+        none: &str,
+    ) -> Result<()> {
+        self.message = Some(match operation {
+            Some(operation) => format!("{verb} {}", operation.description()),
+            None => none.to_string(),
+        });
+        self.reload().await
+    }
+
+    async fn delete(&mut self, id: String) -> Result<()> {
+        let deleted = self.repo.delete_subtree(&[id]).await?;
+        self.reload().await?;
+        self.message = match deleted {
+            0 => None,
+            1 => Some("moved to the trash".to_string()),
+            _ => Some(format!("moved {deleted} tasks to the trash")),
+        };
+        Ok(())
+    }
+
+    async fn input_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Mode::Input { text, .. } = &mut self.mode else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            KeyCode::Enter => {
+                let Mode::Input { prompt, text } = std::mem::replace(&mut self.mode, Mode::Normal)
+                else {
+                    unreachable!();
+                };
+                match prompt {
+                    Prompt::Add { parent_id } => self.add(parent_id, text.trim()).await?,
+                    Prompt::Due { id } => match self.parse_due(&text) {
+                        Ok((day, time)) => {
+                            self.repo.set_due_date(&[id], day, time).await?;
+                            self.reload().await?;
+                        }
+                        Err(e) => {
+                            // Leave the text to be corrected
+                            self.message = Some(e);
+                            self.mode = Mode::Input {
+                                prompt: Prompt::Due { id },
+                                text,
+                            };
+                        }
+                    },
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Create a task due on the selected filter's day, like the window does,
+    /// and select it
+    async fn add(&mut self, parent_id: Option<String>, name: &str) -> Result<()> {
+        if name.is_empty() {
+            return Ok(());
+        }
+        let due_date = self.entry().due_date;
+        let task = self
+            .repo
+            .create_task(name, parent_id.as_deref(), due_date, None)
+            .await?;
+        if self.focus == Focus::Sidebar {
+            self.focus = Focus::Tasks;
+            self.cursor = vec![0];
+        }
+        self.reload().await?;
+
+        let depth = self.depth();
+        if let Some(row) = self.column(depth).iter().position(|t| t.id == task.id) {
+            self.cursor[depth] = row;
+        }
+        Ok(())
+    }
+
+    /// Due day and time from text such as `tomorrow 14:30`, both `None`
+    /// for empty text
+    fn parse_due(
+        &self,
+        text: &str,
+    ) -> std::result::Result<(Option<NaiveDate>, Option<NaiveTime>), String> {
+        let mut parts = text.split_whitespace();
+        let day = parts.next().map(parse_day).transpose()?;
+        let time = parts.next().map(parse_time).transpose()?;
+        if parts.next().is_some() {
+            return Err("expected a day and at most a time".to_string());
+        }
+        Ok((day.map(|day| day.resolve(self.today)), time))
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [sidebar, columns] =
+            Layout::horizontal([Constraint::Length(SIDEBAR_WIDTH), Constraint::Min(0)]).areas(main);
+        self.draw_sidebar(frame, sidebar);
+        self.draw_columns(frame, columns);
+
+        if let Mode::Input { prompt, text } = &self.mode {
+            let typed = Line::from(vec![Span::raw(prompt.label()), Span::raw(text.as_str())]);
+            let x = status.x + (typed.width() as u16).min(status.width.saturating_sub(1));
+            frame.set_cursor_position((x, status.y));
+        }
+        frame.render_widget(self.status_line(), status);
+    }
+
+    fn draw_sidebar(&self, frame: &mut Frame, area: Rect) {
+        let width = usize::from(area.width.saturating_sub(2));
+        let items: Vec<ListItem> = self
+            .filters
+            .iter()
+            .map(|entry| {
+                let count = format!("{}/{}", entry.completed_task_count, entry.total_task_count);
+                let pad = width.saturating_sub(count.len());
+                ListItem::new(format!("{:<pad$}{count}", entry.label))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Act "))
+            .highlight_style(highlight(self.focus == Focus::Sidebar));
+        let mut state = ListState::default().with_selected(Some(self.filter_index));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    /// The open columns, then the focused task's subtasks as a preview,
+    /// dropping columns from the left when they do not fit
+    fn draw_columns(&self, frame: &mut Frame, area: Rect) {
+        let depth = self.depth();
+        let preview = self
+            .selected(depth)
+            .is_some_and(|task| !self.children(Some(&task.id)).is_empty());
+        let last = if preview { depth + 1 } else { depth };
+        let fit = usize::from((area.width / COLUMN_WIDTH).max(1));
+        let levels: Vec<usize> = ((last + 1).saturating_sub(fit)..=last).collect();
+
+        let areas = Layout::horizontal(
+            levels
+                .iter()
+                .map(|_| Constraint::Ratio(1, levels.len() as u32)),
+        )
+        .split(area);
+        for (&level, &area) in levels.iter().zip(areas.iter()) {
+            let title = match level.checked_sub(1) {
+                None => self.entry().label.clone(),
+                Some(left) => self
+                    .selected(left)
+                    .map(|parent| parent.name.clone())
+                    .unwrap_or_default(),
+            };
+            let items: Vec<ListItem> = self
+                .column(level)
+                .into_iter()
+                .map(|task| self.item(task))
+                .collect();
+            let list = List::new(items)
+                .block(Block::bordered().title(format!(" {title} ")))
+                .highlight_style(highlight(self.focus == Focus::Tasks && level == depth));
+            let mut state = ListState::default().with_selected(self.cursor.get(level).copied());
+            frame.render_stateful_widget(list, area, &mut state);
+        }
+    }
+
+    /// `[ ] Name  Tomorrow 14:30  1/3 ›`
+    fn item(&self, task: &Task) -> ListItem<'static> {
+        let mark = if task.completed { "[x] " } else { "[ ] " };
+        let mut spans = vec![Span::raw(mark), Span::raw(task.name.clone())];
+        if let Some(due) = due_label(task.due_date, task.due_time, self.today) {
+            let style = if task.overdue {
+                Style::new().red()
+            } else {
+                Style::new().dim()
+            };
+            spans.push(Span::styled(format!("  {due}"), style));
+        }
+        if task.total_subtasks > 0 {
+            spans.push(format!("  {}/{} ›", task.completed_subtasks, task.total_subtasks).dim());
+        }
+        let line = Line::from(spans);
+        ListItem::new(if task.completed {
+            line.dim().crossed_out()
+        } else {
+            line
+        })
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        match &self.mode {
+            Mode::Input { prompt, text } => {
+                let mut line = Line::from(vec![prompt.label().bold(), Span::raw(text.as_str())]);
+                if let Some(message) = &self.message {
+                    line.push_span(format!("  {message}").red());
+                }
+                line
+            }
+            Mode::ConfirmDelete { name, .. } => {
+                Line::from(format!("Trash \"{name}\" and its subtasks? (y/n)")).bold()
+            }
+            Mode::Normal => match &self.message {
+                Some(message) => Line::from(message.as_str()),
+                None => Line::from(HELP).dim(),
+            },
+        }
+    }
+}
+
+/// Row style of the selection, reversed in the focused list and bold on
+/// the path leading to it
+fn highlight(focused: bool) -> Style {
+    if focused {
+        Style::new().reversed()
+    } else {
+        Style::new().bold()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+
+    /// Press a key per character, `\n` being Enter and `\u{8}` Backspace
+    async fn type_keys(app: &mut App<'_>, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\u{8}' => KeyCode::Backspace,
+                c => KeyCode::Char(c),
+            };
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+                .await
+                .unwrap();
+        }
+    }
+
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 12)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[tokio::test]
+    async fn keys_edit_the_focused_task() {
+        let repo = TaskRepository::new(crate::db::memory().await);
+        let mut app = App::new(&repo);
+        app.reload().await.unwrap();
+
+        type_keys(&mut app, "nPack\nlnSocks\n").await;
+        let socks = app.focused().unwrap().clone();
+        assert_eq!(socks.name, "Socks");
+        assert_eq!(app.selected(0).unwrap().name, "Pack");
+
+        // A bad time keeps the prompt open to correct it
+        type_keys(&mut app, "dtomorrow 25:00\n").await;
+        assert!(matches!(app.mode, Mode::Input { .. }));
+        assert_eq!(app.message.as_deref(), Some("expected HH:MM"));
+        type_keys(&mut app, "\u{8}\u{8}\u{8}\u{8}\u{8}9:30\n").await;
+        let socks = app.focused().unwrap().clone();
+        assert_eq!(socks.due_date, app.today.succ_opt());
+        assert_eq!(socks.due_time, NaiveTime::from_hms_opt(9, 30, 0));
+        let screen = screen(&app);
+        assert!(
+            screen.contains("Pack") && screen.contains("Socks"),
+            "{screen}"
+        );
+
+        type_keys(&mut app, " ").await;
+        assert!(app.focused().unwrap().completed);
+        assert!(app.selected(0).unwrap().completed);
+        type_keys(&mut app, "u").await;
+        assert!(app.message.as_deref().unwrap().starts_with("undid"));
+        assert!(!app.focused().unwrap().completed);
+
+        // Trashing a task with subtasks asks first
+        type_keys(&mut app, "h\u{8}n").await;
+        assert_eq!(app.tasks.len(), 2);
+        type_keys(&mut app, "\u{8}y").await;
+        assert!(app.tasks.is_empty());
+        assert_eq!(app.message.as_deref(), Some("moved 2 tasks to the trash"));
+    }
+
+    #[tokio::test]
+    async fn due_text_takes_a_day_and_a_time() {
+        let repo = TaskRepository::new(crate::db::memory().await);
+        let mut app = App::new(&repo);
+        app.reload().await.unwrap();
+        let today = app.today;
+
+        assert_eq!(app.parse_due("  "), Ok((None, None)));
+        assert_eq!(
+            app.parse_due("tomorrow 14:30"),
+            Ok((today.succ_opt(), NaiveTime::from_hms_opt(14, 30, 0)))
+        );
+        assert_eq!(
+            app.parse_due("2026-11-02"),
+            Ok((NaiveDate::from_ymd_opt(2026, 11, 2), None))
+        );
+        assert!(app.parse_due("someday").is_err());
+        assert!(app.parse_due("today 9:00 sharp").is_err());
+    }
+}
@@ -22,6 +22,8 @@ function App() {
       deleteTasks: taskManager.deleteTasks,
       reorderTasks: taskManager.reorderTasks,
       moveTasksToParent: taskManager.moveTasksToParent,
+      undo: taskManager.undo,
+      redo: taskManager.redo,
     },
   });
 
@@ -126,7 +128,7 @@ function App() {
   );
 
   const handleDateFilterDrop = useCallback(
-    async (draggedTaskIds: string[], targetDate: Date) => {
+    async (draggedTaskIds: string[], targetDate?: string) => {
       try {
         // Move tasks to root level (remove parent) when dropping on date filter
         await taskManager.moveTasksToParent(draggedTaskIds, undefined);
@@ -153,8 +155,13 @@ function App() {
   const handleTaskDueDateUpdate = useCallback(
     async (task: Task, newDueDate: string) => {
       try {
-        const dueDate = new Date(newDueDate);
-        await taskManager.updateTasksDates(task.id, dueDate);
+        // Clearing the picker makes the task a someday task; a new day keeps
+        // the task's time of day
+        if (newDueDate) {
+          await taskManager.updateTasksDates(task.id, newDueDate, task.dueTime);
+        } else {
+          await taskManager.updateTasksDates(task.id);
+        }
       } catch (error) {
         console.error("Failed to update task due date:", error);
       }
@@ -460,13 +467,16 @@ function App() {
             }
 
             // Determine the due date to use
-            let taskDate: Date | undefined;
+            let taskDate: string | undefined;
             if (dueDate) {
               // Use the date from the date picker if one was selected
-              taskDate = new Date(dueDate);
+              taskDate = dueDate;
             } else {
               // Fall back to date from selected filter if no explicit date was set
-              taskDate = getDateFromFilter(taskManager.selectedDateFilter);
+              taskDate = getDateFromFilter(
+                taskManager.selectedDateFilter,
+                taskManager.dateFilters,
+              );
             }
 
             taskManager.addTask(taskName, parentTaskId, taskDate);
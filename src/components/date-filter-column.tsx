@@ -6,7 +6,7 @@ interface DateFilterColumnProps {
   selectedDateFilter?: DateFilter;
   focusedDateIndex: number;
   onDateFilterClick: (filter: DateFilter, index: number) => void;
-  onDateFilterDrop?: (draggedTaskIds: string[], targetDate: Date) => void;
+  onDateFilterDrop?: (draggedTaskIds: string[], targetDate?: string) => void;
 }
 
 export function DateFilterColumn({
@@ -21,6 +21,7 @@ export function DateFilterColumn({
   const tomorrowFilter = dateFilters.find((f) => f.type === "tomorrow");
   const yesterdayFilter = dateFilters.find((f) => f.type === "yesterday");
   const historicalFilters = dateFilters.filter((f) => f.type === "date");
+  const somedayFilter = dateFilters.find((f) => f.type === "someday");
 
   const allFilterItems = [
     ...(allFilter ? [allFilter] : []),
@@ -28,6 +29,7 @@ export function DateFilterColumn({
     ...(tomorrowFilter ? [tomorrowFilter] : []),
     ...(yesterdayFilter ? [yesterdayFilter] : []),
     ...historicalFilters,
+    ...(somedayFilter ? [somedayFilter] : []),
   ];
 
   return (
@@ -52,7 +54,8 @@ export function DateFilterColumn({
           (todayFilter ||
             tomorrowFilter ||
             yesterdayFilter ||
-            historicalFilters.length > 0) && (
+            historicalFilters.length > 0 ||
+            somedayFilter) && (
             <div className="border-t-2 border-neutral-200 dark:border-neutral-700" />
           )}
 
@@ -117,17 +120,13 @@ export function DateFilterColumn({
               histIndex;
             return (
               <DateFilterItem
-                key={
-                  filter.date?.toISOString() || `${filter.type}-${histIndex}`
-                }
+                key={filter.day || `${filter.type}-${histIndex}`}
                 filter={filter}
                 index={globalIndex}
                 isSelected={
                   selectedDateFilter?.type === "date" &&
-                  !!selectedDateFilter.date &&
-                  !!filter.date &&
-                  selectedDateFilter.date.toDateString() ===
-                    filter.date.toDateString()
+                  !!selectedDateFilter.day &&
+                  selectedDateFilter.day === filter.day
                 }
                 isFocused={focusedDateIndex === globalIndex}
                 onDateFilterClick={onDateFilterClick}
@@ -137,6 +136,23 @@ export function DateFilterColumn({
           })}
         </div>
 
+        {/* Tasks without a due date */}
+        {somedayFilter && (
+          <>
+            <div className="border-t-2 border-neutral-200 dark:border-neutral-700" />
+            <div className="divide-y divide-neutral-100 dark:divide-neutral-800">
+              <DateFilterItem
+                filter={somedayFilter}
+                index={allFilterItems.length - 1}
+                isSelected={selectedDateFilter?.type === "someday"}
+                isFocused={focusedDateIndex === allFilterItems.length - 1}
+                onDateFilterClick={onDateFilterClick}
+                onDateFilterDrop={onDateFilterDrop}
+              />
+            </div>
+          </>
+        )}
+
         {allFilterItems.length === 0 && (
           <div className="text-center py-8 text-neutral-400 dark:text-neutral-500 text-sm">
             No dates yet
@@ -11,7 +11,7 @@ interface DateFilterItemProps {
   isSelected: boolean;
   isFocused: boolean;
   onDateFilterClick: (filter: DateFilter, index: number) => void;
-  onDateFilterDrop?: (draggedTaskIds: string[], targetDate: Date) => void;
+  onDateFilterDrop?: (draggedTaskIds: string[], targetDate?: string) => void;
 }
 
 export function DateFilterItem({
@@ -25,25 +25,8 @@ export function DateFilterItem({
   const { currentDragData, getDropEffect, setCurrentDragData } = useDrag();
   const [isDragOver, setIsDragOver] = React.useState(false);
 
-  // Get the target date for this filter
-  const getTargetDate = (): Date | null => {
-    switch (filter.type) {
-      case "today":
-        return new Date();
-      case "tomorrow":
-        const tomorrow = new Date();
-        tomorrow.setDate(tomorrow.getDate() + 1);
-        return tomorrow;
-      case "yesterday":
-        const yesterday = new Date();
-        yesterday.setDate(yesterday.getDate() - 1);
-        return yesterday;
-      case "date":
-        return filter.date || null;
-      default:
-        return null;
-    }
-  };
+  // Every filter but All maps to a due day, Someday clearing the due date
+  const acceptsDrop = filter.type !== "all";
 
   const handleDragOver = (e: React.DragEvent) => {
     e.preventDefault();
@@ -54,8 +37,7 @@ export function DateFilterItem({
       return;
     }
 
-    const targetDate = getTargetDate();
-    const canDrop = targetDate && currentDragData.taskIds.length > 0;
+    const canDrop = acceptsDrop && currentDragData.taskIds.length > 0;
 
     if (canDrop) {
       e.dataTransfer.dropEffect = getDropEffect(false, true); // false = cross-column (date filter)
@@ -86,11 +68,10 @@ export function DateFilterItem({
     try {
       const data = JSON.parse(e.dataTransfer.getData("text/plain"));
       if (data.type === "tasks" && data.taskIds && onDateFilterDrop) {
-        const targetDate = getTargetDate();
-        if (targetDate) {
+        if (acceptsDrop) {
           // Clear drag context immediately to prevent visual artifacts
           setCurrentDragData(null);
-          onDateFilterDrop(data.taskIds, targetDate);
+          onDateFilterDrop(data.taskIds, filter.dueDate);
         }
       }
     } catch (error) {
@@ -1,10 +1,11 @@
 import React from "react";
 import { Calendar } from "lucide-react";
-import { formatDateLabel } from "../utils/date";
+import { formatDateLabel, parseDay } from "../utils/date";
 import { cn } from "../utils";
 
 interface DatePickerProps {
-  value: string; // ISO date string
+  value: string; // Calendar day (YYYY-MM-DD), empty when unset
+  time?: string; // Time of day (HH:MM:SS) shown after the day
   onChange: (date: string) => void;
   isOverdue?: boolean;
   placeholder?: string;
@@ -15,6 +16,7 @@ interface DatePickerProps {
 
 export function DatePicker({
   value,
+  time,
   onChange,
   isOverdue = false,
   placeholder = "Today",
@@ -53,7 +55,8 @@ export function DatePicker({
     >
       {showIcon && <Calendar size={14} />}
       <span className="whitespace-nowrap">
-        {value ? formatDateLabel(new Date(value)) : placeholder}
+        {value ? formatDateLabel(parseDay(value)) : placeholder}
+        {value && time && ` ${time.slice(0, 5)}`}
       </span>
 
       <input
@@ -56,12 +56,8 @@ export function TaskItem({
   const inputRef = React.useRef<HTMLInputElement>(null);
   const isCompleted = task.completed;
 
-  // Calculate if the task is overdue
-  const isOverdue = React.useMemo(() => {
-    const now = new Date();
-    const today = new Date(now.getFullYear(), now.getMonth(), now.getDate());
-    return task.dueDate < today && !isCompleted;
-  }, [task.dueDate, isCompleted]);
+  // Overdue is computed by the backend, which knows about due times
+  const isOverdue = task.overdue && !isCompleted;
 
   React.useEffect(() => {
     setInputValue(task.name);
@@ -358,9 +354,11 @@ export function TaskItem({
       <div className="flex items-center gap-2 select-none relative z-10">
         {/* Due date picker */}
         <DatePicker
-          value={task.dueDate.toISOString().split("T")[0]}
+          value={task.dueDate ?? ""}
+          time={task.dueTime}
           onChange={(newDate) => onTaskDueDateUpdate(task, newDate)}
           isOverdue={isOverdue}
+          placeholder="Someday"
           className="ms-1"
         />
       </div>
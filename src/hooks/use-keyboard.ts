@@ -11,8 +11,11 @@ interface TaskOperations {
   reorderTasks: (taskIds: string[], parentId?: string) => Promise<void>;
   moveTasksToParent: (
     taskIds: string | string[],
-    newParentId?: string
+    newParentId?: string,
+    index?: number
   ) => Promise<void>;
+  undo: () => Promise<void>;
+  redo: () => Promise<void>;
 }
 
 interface UseKeyboardProps {
@@ -155,7 +158,6 @@ export const useKeyboard = ({ appState, taskOps }: UseKeyboardProps) => {
         targetColumnIndex = appState.focusedColumn + 1;
       }
 
-      const currentColumn = appState.columns[appState.focusedColumn];
       const targetColumn = appState.columns[targetColumnIndex];
 
       // Prevent moving a task to its own subtask column
@@ -163,36 +165,19 @@ export const useKeyboard = ({ appState, taskOps }: UseKeyboardProps) => {
         return; // Cannot move a task to its own subtask column
       }
 
-      // Get tasks in both columns
-      const currentColumnTasks = TaskService.getTasksByParentId(
-        taskOps.tasks,
-        currentColumn?.parentTaskId,
-        appState.showCompleted
-      );
       const targetColumnTasks = TaskService.getTasksByParentId(
         taskOps.tasks,
         targetColumn?.parentTaskId,
         appState.showCompleted
       );
 
-      // Move the task to the target column (this will add it to the end automatically)
+      // Move the task to the end of the target column; the backend closes
+      // the gap it leaves in the current column
       await taskOps.moveTasksToParent(
         focusedTask.id,
         targetColumn?.parentTaskId
       );
 
-      // Reorder the current column to close the gap left by the moved task
-      const remainingCurrentTasks = currentColumnTasks
-        .filter((task) => task.id !== focusedTask.id)
-        .map((task) => task.id);
-
-      if (remainingCurrentTasks.length > 0) {
-        await taskOps.reorderTasks(
-          remainingCurrentTasks,
-          currentColumn?.parentTaskId
-        );
-      }
-
       // Update focus to follow the moved task in the target column
       appState.setFocusedColumn(targetColumnIndex);
       const newTaskIndex = targetColumnTasks.length; // Task will be at the end
@@ -235,6 +220,22 @@ export const useKeyboard = ({ appState, taskOps }: UseKeyboardProps) => {
         return;
       }
 
+      // Command/Ctrl + Z to undo, with Shift (or Ctrl + Y) to redo
+      if ((e.metaKey || e.ctrlKey) && (e.key === "z" || e.key === "Z")) {
+        e.preventDefault();
+        if (e.shiftKey) {
+          taskOps.redo();
+        } else {
+          taskOps.undo();
+        }
+        return;
+      }
+      if (e.ctrlKey && e.key === "y") {
+        e.preventDefault();
+        taskOps.redo();
+        return;
+      }
+
       // Space key to toggle completion of focused task
       if (e.key === " ") {
         e.preventDefault();
@@ -1,5 +1,5 @@
 import { useEffect, useCallback, useState } from "react";
-import { Task, DateFilter } from "../types";
+import { Task, DateFilter, Operation } from "../types";
 import { TaskService } from "../services/task-service";
 import { useTasks } from "./use-tasks";
 import { useAppState } from "./use-app-state";
@@ -24,18 +24,22 @@ export interface UseTaskManagerReturn {
   updateTaskName: (taskId: string, newName: string) => void;
   setSelectedDateFilter: (filter?: DateFilter) => void;
   loadTasks: (dateFilter?: DateFilter) => Promise<void>;
-  addTask: (name: string, parentId?: string, date?: Date) => Promise<void>;
+  addTask: (name: string, parentId?: string, dueDate?: string) => Promise<void>;
   toggleTask: (taskId: string) => Promise<void>;
   deleteTasks: (taskIds: string | string[]) => Promise<void>;
   reorderTasks: (taskIds: string[], parentId?: string) => Promise<void>;
   moveTasksToParent: (
     taskIds: string | string[],
-    newParentId?: string
+    newParentId?: string,
+    index?: number
   ) => Promise<void>;
   updateTasksDates: (
     taskIds: string | string[],
-    newDate: Date
+    dueDate?: string,
+    dueTime?: string
   ) => Promise<void>;
+  undo: () => Promise<void>;
+  redo: () => Promise<void>;
 }
 
 export const useTaskManager = (): UseTaskManagerReturn => {
@@ -114,8 +118,8 @@ export const useTaskManager = (): UseTaskManagerReturn => {
   );
 
   const moveTasksToParent = useCallback(
-    async (taskIds: string | string[], newParentId?: string) => {
-      await TaskService.moveTasksToParent(taskIds, newParentId);
+    async (taskIds: string | string[], newParentId?: string, index?: number) => {
+      await TaskService.moveTasksToParent(taskIds, newParentId, index);
       // Refresh tasks to get updated order
       await tasks.loadTasks(tasks.selectedDateFilter);
     },
@@ -123,8 +127,8 @@ export const useTaskManager = (): UseTaskManagerReturn => {
   );
 
   const updateTasksDates = useCallback(
-    async (taskIds: string | string[], newDate: Date) => {
-      await TaskService.updateTasksDates(taskIds, newDate);
+    async (taskIds: string | string[], dueDate?: string, dueTime?: string) => {
+      await TaskService.updateTasksDates(taskIds, dueDate, dueTime);
       // Refresh tasks to get updated dates
       await tasks.loadTasks(tasks.selectedDateFilter);
       // Refresh date filters for counts
@@ -133,6 +137,39 @@ export const useTaskManager = (): UseTaskManagerReturn => {
     [tasks]
   );
 
+  const reloadAfter = useCallback(
+    async (step: () => Promise<Operation | null>) => {
+      const operation = await step();
+      if (!operation) return;
+      // Any task may have come back or gone, along with its date
+      await tasks.loadTasks(tasks.selectedDateFilter);
+      await tasks.loadDateFilters();
+    },
+    [tasks]
+  );
+
+  // Tasks changed through the HTTP API may belong anywhere in the tree
+  const { loadTasks, loadDateFilters, selectedDateFilter } = tasks;
+  useEffect(() => {
+    const unlisten = TaskService.onApiChanges(() => {
+      loadTasks(selectedDateFilter);
+      loadDateFilters();
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, [loadTasks, loadDateFilters, selectedDateFilter]);
+
+  const undo = useCallback(
+    () => reloadAfter(() => TaskService.undo()),
+    [reloadAfter]
+  );
+
+  const redo = useCallback(
+    () => reloadAfter(() => TaskService.redo()),
+    [reloadAfter]
+  );
+
   return {
     // Task data
     tasks: tasks.tasks,
@@ -159,5 +196,7 @@ export const useTaskManager = (): UseTaskManagerReturn => {
     reorderTasks,
     moveTasksToParent,
     updateTasksDates,
+    undo,
+    redo,
   };
 };
@@ -9,7 +9,7 @@ export interface UseTasksReturn {
   loading: boolean;
   error: string | null;
   setSelectedDateFilter: (filter?: DateFilter) => void;
-  addTask: (name: string, parentId?: string, date?: Date) => Promise<void>;
+  addTask: (name: string, parentId?: string, dueDate?: string) => Promise<void>;
   updateTask: (id: string, name: string) => Promise<void>;
   deleteTasks: (taskIds: string | string[]) => Promise<void>;
   toggleTask: (id: string) => Promise<void>;
@@ -61,9 +61,9 @@ export const useTasks = (): UseTasksReturn => {
   }, []);
 
   const addTask = useCallback(
-    async (name: string, parentId?: string, date?: Date) => {
+    async (name: string, parentId?: string, dueDate?: string) => {
       try {
-        await TaskService.createTask(name, parentId, date);
+        await TaskService.createTask(name, parentId, dueDate);
         // Reload tasks to get updated parent completion states
         await loadTasks(selectedDateFilter);
         // Reload date filters in case new date was added
@@ -107,8 +107,14 @@ export const useTasks = (): UseTasksReturn => {
   const toggleTask = useCallback(
     async (id: string) => {
       try {
-        await TaskService.toggleTask(id);
-        await loadTasks(selectedDateFilter);
+        const changed = await TaskService.toggleTask(id);
+        if (changed.some((task) => !tasks.some((t) => t.id === task.id))) {
+          // A recurring task spawned its next occurrence
+          await loadTasks(selectedDateFilter);
+        } else {
+          // Patch completion in place instead of reloading every task
+          setTasks((prev) => TaskService.applyCompletionChanges(prev, changed));
+        }
         // Reload date filters in case completion dates changed
         await loadDateFilters();
       } catch (err) {
@@ -116,7 +122,7 @@ export const useTasks = (): UseTasksReturn => {
         console.error("Failed to toggle task:", err);
       }
     },
-    [loadTasks, selectedDateFilter, loadDateFilters]
+    [tasks, loadTasks, selectedDateFilter, loadDateFilters]
   );
 
   // Load date filters on mount
@@ -1,505 +1,461 @@
-import { Task, DateFilter } from "../types";
-import { DatabaseService, DatabaseTask } from "./database";
-import { generateDateFiltersFromDates } from "../utils/date";
-import { v4 as uuidv4 } from "uuid";
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import {
+  ApiChanges,
+  ApiSettings,
+  Backup,
+  BackupSettings,
+  Task,
+  DateFilter,
+  ExportDocument,
+  ExportFilter,
+  ImportOptions,
+  ImportReport,
+  Operation,
+  Priority,
+  Profile,
+  Reminder,
+  ReminderTrigger,
+  RepeatFrom,
+  RescheduledTask,
+  SearchHit,
+  Tag,
+  TagFilter,
+  TaskEvent,
+  TaskHistory,
+  TaskSort,
+  TrashEntry,
+  TrashSettings,
+} from "../types";
+
+/** Task as serialized by the Rust backend */
+interface TaskRecord {
+  id: string;
+  name: string;
+  parentId: string | null;
+  completed: boolean;
+  completedAt: string | null;
+  createdAt: string;
+  dueDate: string | null;
+  dueTime: string | null;
+  order: number;
+  priority: Priority;
+  recurrence: string | null;
+  repeatFrom: RepeatFrom;
+  occurrence: number;
+  completedSubtasks: number;
+  totalSubtasks: number;
+  overdue: boolean;
+}
+
+/** Reminder as serialized by the Rust backend */
+interface ReminderRecord {
+  id: string;
+  taskId: string;
+  remindAt: string | null;
+  offsetMinutes: number | null;
+  deliveredAt: string | null;
+  createdAt: string;
+}
+
+/** Tag as serialized by the Rust backend */
+interface TagRecord extends Omit<Tag, "createdAt"> {
+  createdAt: string;
+}
+
+/** Backup as serialized by the Rust backend */
+interface BackupRecord extends Omit<Backup, "createdAt"> {
+  createdAt: string;
+}
+
+/** Search hit as serialized by the Rust backend */
+interface SearchHitRecord extends Omit<SearchHit, "task" | "notesHtml"> {
+  task: TaskRecord;
+  notesHtml: string | null;
+}
+
+/** Trash entry as serialized by the Rust backend */
+interface TrashEntryRecord extends Omit<TrashEntry, "task" | "deletedAt"> {
+  task: TaskRecord;
+  deletedAt: string;
+}
+
+/** Task history as serialized by the Rust backend */
+interface TaskHistoryRecord extends Omit<TaskHistory, "events"> {
+  events: (Omit<TaskEvent, "createdAt"> & { createdAt: string })[];
+}
+
+/** Rescheduled task as serialized by the Rust backend */
+interface RescheduledTaskRecord
+  extends Omit<RescheduledTask, "task" | "originalDueDate"> {
+  task: TaskRecord;
+  originalDueDate: string | null;
+}
+
+/** HTTP API changes as serialized by the Rust backend */
+interface ApiChangesRecord extends Omit<ApiChanges, "tasks"> {
+  tasks: TaskRecord[];
+}
+
+/** Recorded operation as serialized by the Rust backend */
+interface OperationRecord extends Omit<Operation, "createdAt"> {
+  createdAt: string;
+}
+
+/** Sidebar date filter as serialized by the Rust backend */
+interface DateFilterRecord extends Omit<DateFilter, "dueDate"> {
+  dueDate: string | null;
+}
 
 export class TaskService {
-  private static convertDatabaseTask(dbTask: any): Task {
+  private static convertTaskRecord(record: TaskRecord): Task {
     return {
-      id: dbTask.id,
-      name: dbTask.name,
-      parentId: dbTask.parent_id || undefined,
-      completed: Boolean(dbTask.completed),
-      completedAt: dbTask.completed_at
-        ? new Date(dbTask.completed_at)
+      id: record.id,
+      name: record.name,
+      parentId: record.parentId ?? undefined,
+      completed: record.completed,
+      completedAt: record.completedAt
+        ? new Date(record.completedAt)
         : undefined,
-      createdAt: new Date(dbTask.created_at),
-      dueDate: new Date(dbTask.due_date),
-      order: dbTask.task_order,
-      completedSubtasks: dbTask.completed_subtasks || 0,
-      totalSubtasks: dbTask.total_subtasks || 0,
+      createdAt: new Date(record.createdAt),
+      dueDate: record.dueDate ?? undefined,
+      dueTime: record.dueTime ?? undefined,
+      order: record.order,
+      priority: record.priority,
+      recurrence: record.recurrence ?? undefined,
+      repeatFrom: record.repeatFrom,
+      occurrence: record.occurrence,
+      completedSubtasks: record.completedSubtasks,
+      totalSubtasks: record.totalSubtasks,
+      overdue: record.overdue,
     };
   }
 
-  private static async getAllTasks(): Promise<DatabaseTask[]> {
-    const database = await DatabaseService.getConnection();
-    const today = new Date().toISOString().split("T")[0];
-    const startOfToday = `${today}T00:00:00.000Z`;
-
-    return await database.select(
-      `
-      WITH RECURSIVE subtask_counts AS (
-        SELECT 
-          t.id,
-          COUNT(s.id) as total_subtasks,
-          SUM(CASE WHEN s.completed = 1 THEN 1 ELSE 0 END) as completed_subtasks
-        FROM tasks t
-        LEFT JOIN tasks s ON s.parent_id = t.id
-        GROUP BY t.id
-      )
-      SELECT 
-        t.id, 
-        t.name, 
-        t.parent_id, 
-        t.completed, 
-        t.completed_at, 
-        t.created_at, 
-        t.due_date,
-        t.task_order,
-        COALESCE(sc.completed_subtasks, 0) as completed_subtasks,
-        COALESCE(sc.total_subtasks, 0) as total_subtasks
-      FROM tasks t
-      LEFT JOIN subtask_counts sc ON t.id = sc.id
-      ORDER BY 
-        DATE(t.due_date) ASC,
-        t.completed ASC,
-        t.parent_id, 
-        t.task_order ASC,
-        t.created_at ASC
-    `,
-      [startOfToday]
-    );
+  static async listProfiles(): Promise<Profile[]> {
+    return await invoke<Profile[]>("list_profiles");
   }
 
-  private static async getTasksByDate(
-    startDate: string,
-    endDate: string
-  ): Promise<DatabaseTask[]> {
-    const database = await DatabaseService.getConnection();
-    const today = new Date().toISOString().split("T")[0];
-    const startOfToday = `${today}T00:00:00.000Z`;
-
-    return await database.select(
-      `
-      WITH RECURSIVE 
-      -- Find all tasks that match the date criteria
-      matching_tasks AS (
-        SELECT id, parent_id FROM tasks 
-        WHERE due_date >= $1 AND due_date <= $2
-      ),
-      -- Recursively find all parent tasks
-      parent_hierarchy AS (
-        SELECT id, parent_id FROM matching_tasks
-        UNION ALL
-        SELECT t.id, t.parent_id FROM tasks t
-        INNER JOIN parent_hierarchy ph ON t.id = ph.parent_id
-      ),
-      -- Calculate subtask counts for all relevant tasks, only counting subtasks that match date criteria
-      subtask_counts AS (
-        SELECT 
-          t.id,
-          COUNT(s.id) as total_subtasks,
-          SUM(CASE WHEN s.completed = 1 THEN 1 ELSE 0 END) as completed_subtasks
-        FROM tasks t
-        LEFT JOIN tasks s ON s.parent_id = t.id AND s.due_date >= $1 AND s.due_date <= $2
-        GROUP BY t.id
-      )
-      SELECT 
-        t.id, 
-        t.name, 
-        t.parent_id, 
-        t.completed, 
-        t.completed_at, 
-        t.created_at, 
-        t.due_date,
-        t.task_order,
-        COALESCE(sc.completed_subtasks, 0) as completed_subtasks,
-        COALESCE(sc.total_subtasks, 0) as total_subtasks
-      FROM tasks t
-      LEFT JOIN subtask_counts sc ON t.id = sc.id
-      WHERE t.id IN (SELECT id FROM parent_hierarchy)
-      ORDER BY 
-        CASE WHEN t.due_date < $3 AND t.completed = 0 THEN 0 ELSE 1 END,
-        t.parent_id, 
-        t.task_order ASC,
-        CASE WHEN t.due_date < $3 AND t.completed = 0 THEN t.due_date END DESC,
-        t.created_at ASC
-    `,
-      [startDate, endDate, startOfToday]
-    );
+  static async activeProfile(): Promise<Profile> {
+    return await invoke<Profile>("active_profile");
   }
 
-  private static async getTodaysTasks(): Promise<DatabaseTask[]> {
-    const today = new Date().toISOString().split("T")[0];
-    const startOfDay = `${today}T00:00:00.000Z`;
-    const endOfDay = `${today}T23:59:59.999Z`;
-
-    // Get today's tasks and incomplete overdue tasks (due before today)
-    const database = await DatabaseService.getConnection();
-    return await database.select(
-      `
-      WITH RECURSIVE 
-      -- Find all tasks that match the date criteria (today's tasks + overdue incomplete)
-      matching_tasks AS (
-        SELECT id, parent_id FROM tasks 
-        WHERE (due_date >= $1 AND due_date <= $2) OR (due_date < $1 AND completed = 0)
-      ),
-      -- Recursively find all parent tasks
-      parent_hierarchy AS (
-        SELECT id, parent_id FROM matching_tasks
-        UNION ALL
-        SELECT t.id, t.parent_id FROM tasks t
-        INNER JOIN parent_hierarchy ph ON t.id = ph.parent_id
-      ),
-      -- Calculate subtask counts for all relevant tasks, only counting subtasks that match date criteria
-      subtask_counts AS (
-        SELECT 
-          t.id,
-          COUNT(s.id) as total_subtasks,
-          SUM(CASE WHEN s.completed = 1 THEN 1 ELSE 0 END) as completed_subtasks
-        FROM tasks t
-        LEFT JOIN tasks s ON s.parent_id = t.id AND ((s.due_date >= $1 AND s.due_date <= $2) OR (s.due_date < $1 AND s.completed = 0))
-        GROUP BY t.id
-      )
-      SELECT 
-        t.id, 
-        t.name, 
-        t.parent_id, 
-        t.completed, 
-        t.completed_at, 
-        t.created_at, 
-        t.due_date,
-        t.task_order,
-        COALESCE(sc.completed_subtasks, 0) as completed_subtasks,
-        COALESCE(sc.total_subtasks, 0) as total_subtasks
-      FROM tasks t
-      LEFT JOIN subtask_counts sc ON t.id = sc.id
-      WHERE t.id IN (SELECT id FROM parent_hierarchy)
-      ORDER BY 
-        CASE WHEN t.due_date < $1 AND t.completed = 0 THEN 0 ELSE 1 END,
-        t.parent_id, 
-        t.task_order ASC,
-        CASE WHEN t.due_date < $1 AND t.completed = 0 THEN t.due_date END DESC,
-        t.created_at ASC
-    `,
-      [startOfDay, endOfDay]
-    );
+  /**
+   * Switch to another profile's database, creating the profile if needed.
+   * Everything loaded so far belongs to the previous profile and should be
+   * reloaded
+   */
+  static async switchProfile(name: string): Promise<Profile> {
+    return await invoke<Profile>("switch_profile", { name });
   }
 
-  private static async getTasksForDate(date: Date): Promise<DatabaseTask[]> {
-    const dateStr = date.toISOString().split("T")[0];
-    const startOfDay = `${dateStr}T00:00:00.000Z`;
-    const endOfDay = `${dateStr}T23:59:59.999Z`;
-    return await this.getTasksByDate(startOfDay, endOfDay);
-  }
-
-  private static async getTomorrowsTasks(): Promise<DatabaseTask[]> {
-    const tomorrow = new Date();
-    tomorrow.setDate(tomorrow.getDate() + 1);
-    const tomorrowStr = tomorrow.toISOString().split("T")[0];
-    const startOfDay = `${tomorrowStr}T00:00:00.000Z`;
-    const endOfDay = `${tomorrowStr}T23:59:59.999Z`;
-
-    // Get today's date for overdue cutoff
-    const today = new Date().toISOString().split("T")[0];
-    const startOfToday = `${today}T00:00:00.000Z`;
-
-    // Get tomorrow's tasks and incomplete overdue tasks (due before today)
-    const database = await DatabaseService.getConnection();
-    return await database.select(
-      `
-      WITH RECURSIVE 
-      -- Find all tasks that match the date criteria (tomorrow's tasks + overdue incomplete)
-      matching_tasks AS (
-        SELECT id, parent_id FROM tasks 
-        WHERE (due_date >= $1 AND due_date <= $2) OR (due_date < $3 AND completed = 0)
-      ),
-      -- Recursively find all parent tasks
-      parent_hierarchy AS (
-        SELECT id, parent_id FROM matching_tasks
-        UNION ALL
-        SELECT t.id, t.parent_id FROM tasks t
-        INNER JOIN parent_hierarchy ph ON t.id = ph.parent_id
-      ),
-      -- Calculate subtask counts for all relevant tasks
-      subtask_counts AS (
-        SELECT 
-          t.id,
-          COUNT(s.id) as total_subtasks,
-          SUM(CASE WHEN s.completed = 1 THEN 1 ELSE 0 END) as completed_subtasks
-        FROM tasks t
-        LEFT JOIN tasks s ON s.parent_id = t.id AND ((s.due_date >= $1 AND s.due_date <= $2) OR (s.due_date < $3 AND s.completed = 0))
-        GROUP BY t.id
-      )
-      SELECT 
-        t.id, 
-        t.name, 
-        t.parent_id, 
-        t.completed, 
-        t.completed_at, 
-        t.created_at, 
-        t.due_date,
-        t.task_order,
-        COALESCE(sc.completed_subtasks, 0) as completed_subtasks,
-        COALESCE(sc.total_subtasks, 0) as total_subtasks
-      FROM tasks t
-      LEFT JOIN subtask_counts sc ON t.id = sc.id
-      WHERE t.id IN (SELECT id FROM parent_hierarchy)
-      ORDER BY 
-        CASE WHEN t.due_date < $3 AND t.completed = 0 THEN 0 ELSE 1 END,
-        t.parent_id, 
-        t.task_order ASC,
-        CASE WHEN t.due_date < $3 AND t.completed = 0 THEN t.due_date END DESC,
-        t.created_at ASC
-    `,
-      [startOfDay, endOfDay, startOfToday]
-    );
+  /** Backups of the active profile, newest first */
+  static async listBackups(): Promise<Backup[]> {
+    const records = await invoke<BackupRecord[]>("list_backups");
+    return records.map((record) => ({
+      ...record,
+      createdAt: new Date(record.createdAt),
+    }));
   }
 
-  private static async getSubtasks(parentId: string): Promise<DatabaseTask[]> {
-    const database = await DatabaseService.getConnection();
-    return await database.select(
-      `
-      WITH RECURSIVE subtask_counts AS (
-        SELECT 
-          t.id,
-          COUNT(s.id) as total_subtasks,
-          SUM(CASE WHEN s.completed = 1 THEN 1 ELSE 0 END) as completed_subtasks
-        FROM tasks t
-        LEFT JOIN tasks s ON s.parent_id = t.id
-        GROUP BY t.id
-      )
-      SELECT 
-        t.id, 
-        t.name, 
-        t.parent_id, 
-        t.completed, 
-        t.completed_at, 
-        t.created_at, 
-        t.due_date,
-        t.task_order,
-        COALESCE(sc.completed_subtasks, 0) as completed_subtasks,
-        COALESCE(sc.total_subtasks, 0) as total_subtasks
-      FROM tasks t
-      LEFT JOIN subtask_counts sc ON t.id = sc.id
-      WHERE t.parent_id = $1
-      ORDER BY t.task_order ASC
-    `,
-      [parentId]
-    );
+  /**
+   * Replace the active profile's tasks with a backup, after the backend
+   * verified its integrity. Returns the backup taken of the replaced state;
+   * everything loaded so far should be reloaded
+   */
+  static async restoreBackup(id: string): Promise<Backup> {
+    const record = await invoke<BackupRecord>("restore_backup", { id });
+    return { ...record, createdAt: new Date(record.createdAt) };
   }
 
-  private static async handleParentChildCompletion(
-    taskId: string
-  ): Promise<void> {
-    const database = await DatabaseService.getConnection();
+  static async getBackupSettings(): Promise<BackupSettings> {
+    return await invoke<BackupSettings>("get_backup_settings");
+  }
 
-    // Get the task that was just toggled
-    const [task] = (await database.select(
-      "SELECT id, parent_id, completed FROM tasks WHERE id = $1",
-      [taskId]
-    )) as DatabaseTask[];
+  static async setBackupSettings(settings: BackupSettings): Promise<void> {
+    await invoke("set_backup_settings", { settings });
+  }
 
-    if (!task) return;
+  static async exportTasks(filter?: ExportFilter): Promise<ExportDocument> {
+    return await invoke<ExportDocument>("export_tasks", { filter });
+  }
 
-    // If task was completed, check if parent should be auto-completed
-    if (Boolean(task.completed) && task.parent_id) {
-      const siblings = await this.getSubtasks(task.parent_id);
-      const allSiblingsCompleted = siblings.every((sibling) =>
-        Boolean(sibling.completed)
-      );
+  /**
+   * Import a document from `exportTasks`, e.g. read from a file. Runs in one
+   * transaction; with `dryRun` only the report is produced
+   */
+  static async importTasks(
+    document: unknown,
+    options?: ImportOptions
+  ): Promise<ImportReport> {
+    return await invoke<ImportReport>("import_tasks", { document, options });
+  }
 
-      if (allSiblingsCompleted) {
-        // Auto-complete parent
-        const now = new Date().toISOString();
-        await database.execute(
-          "UPDATE tasks SET completed = TRUE, completed_at = $1 WHERE id = $2",
-          [now, task.parent_id]
-        );
-        // Recursively check parent's parent
-        await this.handleParentChildCompletion(task.parent_id);
-      }
-    }
-    // If task was uncompleted, auto-uncomplete parent if it was completed
-    else if (!Boolean(task.completed) && task.parent_id) {
-      const [parent] = (await database.select(
-        "SELECT id, completed, parent_id FROM tasks WHERE id = $1",
-        [task.parent_id]
-      )) as DatabaseTask[];
-
-      if (parent && Boolean(parent.completed)) {
-        // Auto-uncomplete parent
-        await database.execute(
-          "UPDATE tasks SET completed = FALSE, completed_at = NULL WHERE id = $1",
-          [task.parent_id]
-        );
-        // Recursively check parent's parent
-        if (parent.parent_id) {
-          await this.handleParentChildCompletion(task.parent_id);
-        }
-      }
-    }
+  /** Tasks as a todo.txt file, hierarchy kept in `id:`/`parent:` keys */
+  static async exportTodoTxt(filter?: ExportFilter): Promise<string> {
+    return await invoke<string>("export_todo_txt", { filter });
   }
 
-  static async loadTasks(dateFilter?: DateFilter): Promise<Task[]> {
-    let dbTasks;
-
-    if (!dateFilter) {
-      dbTasks = await this.getAllTasks();
-    } else {
-      switch (dateFilter.type) {
-        case "all":
-          dbTasks = await this.getAllTasks();
-          break;
-        case "today":
-          dbTasks = await this.getTodaysTasks();
-          break;
-        case "tomorrow":
-          dbTasks = await this.getTomorrowsTasks();
-          break;
-        case "yesterday":
-          const yesterday = new Date();
-          yesterday.setDate(yesterday.getDate() - 1);
-          dbTasks = await this.getTasksForDate(yesterday);
-          break;
-        case "date":
-          if (dateFilter.date) {
-            dbTasks = await this.getTasksForDate(dateFilter.date);
-          } else {
-            dbTasks = await this.getAllTasks();
-          }
-          break;
-        case "range":
-          if (dateFilter.startDate && dateFilter.endDate) {
-            const startDateStr = dateFilter.startDate.toISOString();
-            const endDateStr = dateFilter.endDate.toISOString();
-            dbTasks = await this.getTasksByDate(startDateStr, endDateStr);
-          } else {
-            dbTasks = await this.getAllTasks();
-          }
-          break;
-        default:
-          dbTasks = await this.getAllTasks();
-      }
-    }
+  static async importTodoTxt(
+    text: string,
+    options?: ImportOptions
+  ): Promise<ImportReport> {
+    return await invoke<ImportReport>("import_todo_txt", { text, options });
+  }
+
+  /** Tasks as an iCalendar (.ics) file of VTODOs */
+  static async exportIcal(filter?: ExportFilter): Promise<string> {
+    return await invoke<string>("export_ical", { filter });
+  }
+
+  static async importIcal(
+    text: string,
+    options?: ImportOptions
+  ): Promise<ImportReport> {
+    return await invoke<ImportReport>("import_ical", { text, options });
+  }
 
-    return dbTasks.map(this.convertDatabaseTask);
+  /** Tasks as Markdown checklists, one section per due day */
+  static async exportMarkdown(filter?: ExportFilter): Promise<string> {
+    return await invoke<string>("export_markdown", { filter });
   }
 
+  /** Add a pasted checklist as new tasks, nested by indentation */
+  static async importMarkdown(
+    text: string,
+    parentId?: string | null,
+    options?: ImportOptions
+  ): Promise<ImportReport> {
+    return await invoke<ImportReport>("import_markdown", {
+      text,
+      parentId,
+      options,
+    });
+  }
+
+  /**
+   * Load tasks matching the date filter and optional tag filter, along with
+   * their ancestors, in the given sort order (manual by default)
+   */
+  static async loadTasks(
+    dateFilter?: DateFilter,
+    tagFilter?: TagFilter,
+    sort?: TaskSort
+  ): Promise<Task[]> {
+    const records = await invoke<TaskRecord[]>("load_tasks", {
+      filter: dateFilter,
+      tags: tagFilter,
+      sort,
+    });
+    return records.map(this.convertTaskRecord);
+  }
+
+  /**
+   * Full-text search over task names and notes, best matches first. The
+   * last word matches as a prefix so results can follow typing
+   */
+  static async searchTasks(
+    query: string,
+    dateFilter?: DateFilter
+  ): Promise<SearchHit[]> {
+    const records = await invoke<SearchHitRecord[]>("search_tasks", {
+      query,
+      filter: dateFilter,
+    });
+    return records.map((record) => ({
+      ...record,
+      task: this.convertTaskRecord(record.task),
+      notesHtml: record.notesHtml ?? undefined,
+    }));
+  }
+
+  /**
+   * Create a task due on `dueDate` (YYYY-MM-DD), optionally at `dueTime`,
+   * or a someday task when no date is given
+   */
   static async createTask(
     name: string,
     parentId?: string,
-    date?: Date
+    dueDate?: string,
+    dueTime?: string
   ): Promise<Task> {
-    const database = await DatabaseService.getConnection();
-    const taskDate = date ? date.toISOString() : new Date().toISOString();
-    const id = uuidv4();
-
-    // Get the next order number for this parent
-    const [{ max_order }] = (await database.select(
-      "SELECT COALESCE(MAX(task_order), -1) as max_order FROM tasks WHERE parent_id IS $1",
-      [parentId || null]
-    )) as [{ max_order: number }];
-
-    const task: DatabaseTask = {
-      id,
+    const record = await invoke<TaskRecord>("create_task", {
       name,
-      parent_id: parentId,
-      completed: 0,
-      completed_at: undefined,
-      created_at: new Date().toISOString(),
-      due_date: taskDate,
-      task_order: max_order + 1,
+      parentId,
+      dueDate,
+      dueTime,
+    });
+    return this.convertTaskRecord(record);
+  }
+
+  static async updateTask(id: string, name: string): Promise<void> {
+    await invoke("rename_task", { id, name });
+  }
+
+  static async setPriority(
+    taskIds: string | string[],
+    priority: Priority
+  ): Promise<void> {
+    const ids = Array.isArray(taskIds) ? taskIds : [taskIds];
+    await invoke("set_priority", { ids, priority });
+  }
+
+  /**
+   * Make a task recur by an RFC 5545 RRULE (e.g. "FREQ=WEEKLY;BYDAY=MO"), or
+   * a one-off task again when `rule` is omitted. FREQ, INTERVAL, BYDAY,
+   * BYMONTHDAY, COUNT and UNTIL are understood, other parts are refused with
+   * an invalidRecurrence error. Yearly rules stay in the due date's month
+   */
+  static async setRecurrence(
+    id: string,
+    rule?: string,
+    repeatFrom: RepeatFrom = "due"
+  ): Promise<void> {
+    await invoke("set_recurrence", { id, rule, repeatFrom });
+  }
+
+  private static convertReminderRecord(record: ReminderRecord): Reminder {
+    return {
+      id: record.id,
+      taskId: record.taskId,
+      remindAt: record.remindAt ? new Date(record.remindAt) : undefined,
+      offsetMinutes: record.offsetMinutes ?? undefined,
+      deliveredAt: record.deliveredAt ? new Date(record.deliveredAt) : undefined,
+      createdAt: new Date(record.createdAt),
     };
+  }
 
-    await database.execute(
-      "INSERT INTO tasks (id, name, parent_id, completed, completed_at, created_at, due_date, task_order) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
-      [
-        task.id,
-        task.name,
-        task.parent_id,
-        task.completed,
-        task.completed_at,
-        task.created_at,
-        task.due_date,
-        task.task_order,
-      ]
-    );
+  static async listReminders(taskId: string): Promise<Reminder[]> {
+    const records = await invoke<ReminderRecord[]>("list_reminders", {
+      taskId,
+    });
+    return records.map(this.convertReminderRecord);
+  }
 
-    // Handle parent-child completion logic
-    // New task is uncompleted, so if parent was completed, it should become uncompleted
-    if (parentId) {
-      await this.handleParentChildCompletion(id);
-    }
+  /**
+   * Add a reminder, delivered as a desktop notification by the backend
+   * scheduler
+   */
+  static async addReminder(
+    taskId: string,
+    trigger: ReminderTrigger
+  ): Promise<Reminder> {
+    const record = await invoke<ReminderRecord>("add_reminder", {
+      taskId,
+      trigger,
+    });
+    return this.convertReminderRecord(record);
+  }
 
-    return this.convertDatabaseTask(task);
+  static async deleteReminder(id: string): Promise<void> {
+    await invoke("delete_reminder", { id });
   }
 
-  static async updateTask(id: string, name: string): Promise<void> {
-    const database = await DatabaseService.getConnection();
-    await database.execute("UPDATE tasks SET name = $1 WHERE id = $2", [
-      name,
-      id,
-    ]);
+  /** Markdown notes of a task, empty when it has none */
+  static async getNotes(id: string): Promise<string> {
+    return await invoke<string>("get_notes", { id });
   }
 
-  static async deleteTasks(taskIds: string | string[]): Promise<void> {
-    const database = await DatabaseService.getConnection();
+  static async updateNotes(id: string, notes: string): Promise<void> {
+    await invoke("set_notes", { id, notes });
+  }
 
-    // Normalize to array
-    const idsArray = Array.isArray(taskIds) ? taskIds : [taskIds];
+  /**
+   * Notes rendered to sanitized HTML. Links should be opened with `openUrl`
+   * from the opener plugin rather than followed inside the webview.
+   */
+  static async renderNotes(id: string): Promise<string> {
+    return await invoke<string>("render_notes", { id });
+  }
 
-    if (idsArray.length === 0) return;
+  /**
+   * Move tasks along with all of their descendants to the trash, returning
+   * how many rows were trashed
+   */
+  static async deleteTasks(taskIds: string | string[]): Promise<number> {
+    const ids = Array.isArray(taskIds) ? taskIds : [taskIds];
+    return await invoke<number>("delete_subtree", { ids });
+  }
 
-    // Get all tasks being deleted to check their parents
-    const tasksToDelete = (await database.select(
-      `SELECT id, parent_id, completed FROM tasks WHERE id IN (${idsArray
-        .map(() => "?")
-        .join(", ")})`,
-      idsArray
-    )) as DatabaseTask[];
+  /** Deleted tasks, most recently deleted first */
+  static async listTrash(): Promise<TrashEntry[]> {
+    const records = await invoke<TrashEntryRecord[]>("list_trash");
+    return records.map((record) => ({
+      ...record,
+      task: this.convertTaskRecord(record.task),
+      deletedAt: new Date(record.deletedAt),
+    }));
+  }
 
-    if (tasksToDelete.length === 0) return;
+  /**
+   * Put a trash entry back at its old place with its subtasks, or at the
+   * end of the top level when its parent was deleted too
+   */
+  static async restoreFromTrash(id: string): Promise<Task> {
+    const record = await invoke<TaskRecord>("restore_from_trash", { id });
+    return this.convertTaskRecord(record);
+  }
 
-    // Delete all tasks and their subtasks in one query
-    await database.execute(
-      `DELETE FROM tasks WHERE id IN (${idsArray
-        .map(() => "?")
-        .join(", ")}) OR parent_id IN (${idsArray.map(() => "?").join(", ")})`,
-      [...idsArray, ...idsArray]
-    );
+  /**
+   * Permanently delete tasks trashed more than `olderThanDays` ago, or the
+   * whole trash, returning how many rows were removed
+   */
+  static async purgeTrash(olderThanDays?: number): Promise<number> {
+    return await invoke<number>("purge_trash", { olderThanDays });
+  }
 
-    // Handle parent-child completion logic for affected parents
-    const uniqueParents = new Set(
-      tasksToDelete
-        .map((task) => task.parent_id)
-        .filter((id): id is string => Boolean(id))
-    );
+  static async getTrashSettings(): Promise<TrashSettings> {
+    return await invoke<TrashSettings>("get_trash_settings");
+  }
 
-    for (const parentId of uniqueParents) {
-      // Check remaining siblings after deletion
-      const siblings = await this.getSubtasks(parentId);
-
-      if (siblings.length > 0) {
-        const allSiblingsCompleted = siblings.every((sibling) =>
-          Boolean(sibling.completed)
-        );
-
-        if (allSiblingsCompleted) {
-          // Auto-complete parent
-          const now = new Date().toISOString();
-          await database.execute(
-            "UPDATE tasks SET completed = TRUE, completed_at = $1 WHERE id = $2",
-            [now, parentId]
-          );
-          // Recursively check parent's parent
-          await this.handleParentChildCompletion(parentId);
-        }
-      }
-    }
+  static async setTrashSettings(settings: TrashSettings): Promise<void> {
+    await invoke("set_trash_settings", { settings });
   }
 
-  static async toggleTask(id: string): Promise<void> {
-    const database = await DatabaseService.getConnection();
-    const now = new Date().toISOString();
+  /**
+   * Toggle a task, returning it along with every ancestor whose completion
+   * was changed by the toggle, and the next occurrence of any recurring task
+   * that got completed
+   */
+  static async toggleTask(id: string): Promise<Task[]> {
+    const records = await invoke<TaskRecord[]>("toggle_task", { id });
+    return records.map(this.convertTaskRecord);
+  }
 
-    await database.execute(
-      "UPDATE tasks SET completed = NOT completed, completed_at = CASE WHEN completed THEN NULL ELSE $1 END WHERE id = $2",
-      [now, id]
-    );
+  /**
+   * Patch loaded tasks with rows whose completion changed, keeping the
+   * subtask counts of their loaded parents in step
+   */
+  static applyCompletionChanges(tasks: Task[], changed: Task[]): Task[] {
+    const changedById = new Map(changed.map((task) => [task.id, task]));
+    const countDeltas = new Map<string, number>();
+
+    for (const task of tasks) {
+      const update = changedById.get(task.id);
+      if (!update || !task.parentId || update.completed === task.completed) {
+        continue;
+      }
+      const delta = update.completed ? 1 : -1;
+      countDeltas.set(
+        task.parentId,
+        (countDeltas.get(task.parentId) || 0) + delta
+      );
+    }
 
-    // Handle parent-child completion logic
-    await this.handleParentChildCompletion(id);
+    return tasks.map((task) => {
+      const update = changedById.get(task.id);
+      const delta = countDeltas.get(task.id) || 0;
+      if (!update && delta === 0) return task;
+
+      return {
+        ...task,
+        completed: update ? update.completed : task.completed,
+        completedAt: update ? update.completedAt : task.completedAt,
+        completedSubtasks: Math.min(
+          task.totalSubtasks,
+          Math.max(0, task.completedSubtasks + delta)
+        ),
+      };
+    });
   }
 
   static getTasksByParentId(
@@ -532,228 +488,162 @@ export class TaskService {
     return allSubtasks;
   }
 
-  static async reorderTasks(
-    taskIds: string[],
-    parentId?: string
-  ): Promise<void> {
-    const database = await DatabaseService.getConnection();
+  /**
+   * Revert the latest change to the task tree, returning it, or null when
+   * there is nothing to undo. Everything loaded should be reloaded
+   */
+  static async undo(): Promise<Operation | null> {
+    const record = await invoke<OperationRecord | null>("undo");
+    return record && { ...record, createdAt: new Date(record.createdAt) };
+  }
 
-    if (taskIds.length === 0) return;
+  /**
+   * Apply the change undone last again, returning it, or null when nothing
+   * was undone since the last change
+   */
+  static async redo(): Promise<Operation | null> {
+    const record = await invoke<OperationRecord | null>("redo");
+    return record && { ...record, createdAt: new Date(record.createdAt) };
+  }
 
-    // Build CASE statement for updating order in a single query
-    const caseClauses = taskIds
-      .map((_, index) => `WHEN id = ? THEN ${index}`)
-      .join(" ");
-    const placeholders = taskIds.map(() => "?").join(", ");
+  /** Every recorded change to a task, oldest first */
+  static async taskHistory(id: string): Promise<TaskHistory> {
+    const record = await invoke<TaskHistoryRecord>("task_history", { id });
+    return {
+      ...record,
+      events: record.events.map((event) => ({
+        ...event,
+        createdAt: new Date(event.createdAt),
+      })),
+    };
+  }
 
-    await database.execute(
-      `UPDATE tasks SET task_order = CASE ${caseClauses} END WHERE id IN (${placeholders}) AND parent_id IS ?`,
-      [...taskIds, ...taskIds, parentId || null]
+  /**
+   * Tasks whose due date was pushed back at least `minTimes` times (once by
+   * default), the most rescheduled first. Undo and redo do not count
+   */
+  static async rescheduledTasks(minTimes?: number): Promise<RescheduledTask[]> {
+    const records = await invoke<RescheduledTaskRecord[]>(
+      "rescheduled_tasks",
+      { minTimes }
     );
+    return records.map((record) => ({
+      ...record,
+      task: this.convertTaskRecord(record.task),
+      originalDueDate: record.originalDueDate ?? undefined,
+    }));
   }
 
-  private static async updateParentCompletionStatus(
-    parentId: string
-  ): Promise<void> {
-    const database = await DatabaseService.getConnection();
+  static async getApiSettings(): Promise<ApiSettings> {
+    return await invoke<ApiSettings>("get_api_settings");
+  }
+
+  /** Save the settings, restarting the HTTP API to apply them */
+  static async setApiSettings(settings: ApiSettings): Promise<void> {
+    await invoke("set_api_settings", { settings });
+  }
 
-    // Get all children of this parent
-    const children = await this.getSubtasks(parentId);
+  /** Bearer token clients of the HTTP API authenticate with */
+  static async apiToken(): Promise<string> {
+    return await invoke<string>("api_token");
+  }
 
-    if (children.length === 0) return;
+  /** Replace the token, refusing clients that hold the old one */
+  static async rotateApiToken(): Promise<string> {
+    return await invoke<string>("rotate_api_token");
+  }
 
-    const allChildrenCompleted = children.every((child) =>
-      Boolean(child.completed)
+  /** Call `handler` whenever a request to the HTTP API changes tasks */
+  static async onApiChanges(
+    handler: (changes: ApiChanges) => void
+  ): Promise<UnlistenFn> {
+    return await listen<ApiChangesRecord>("tasks-changed", (event) =>
+      handler({
+        ...event.payload,
+        tasks: event.payload.tasks.map((record) =>
+          this.convertTaskRecord(record)
+        ),
+      })
     );
+  }
 
-    // Get current parent status
-    const [parent] = (await database.select(
-      "SELECT id, completed, parent_id FROM tasks WHERE id = $1",
-      [parentId]
-    )) as DatabaseTask[];
-
-    if (!parent) return;
-
-    // Update parent completion status if needed
-    if (allChildrenCompleted && !Boolean(parent.completed)) {
-      // Auto-complete parent
-      const now = new Date().toISOString();
-      await database.execute(
-        "UPDATE tasks SET completed = TRUE, completed_at = $1 WHERE id = $2",
-        [now, parentId]
-      );
-      // Recursively check parent's parent
-      if (parent.parent_id) {
-        await this.updateParentCompletionStatus(parent.parent_id);
-      }
-    } else if (!allChildrenCompleted && Boolean(parent.completed)) {
-      // Auto-uncomplete parent
-      await database.execute(
-        "UPDATE tasks SET completed = FALSE, completed_at = NULL WHERE id = $1",
-        [parentId]
-      );
-      // Recursively check parent's parent
-      if (parent.parent_id) {
-        await this.updateParentCompletionStatus(parent.parent_id);
-      }
-    }
+  static async reorderTasks(
+    taskIds: string[],
+    parentId?: string
+  ): Promise<void> {
+    await invoke("reorder_tasks", { ids: taskIds, parentId });
   }
 
+  /**
+   * Move tasks under a new parent at `index` among its children (appended
+   * when omitted). Rejects with a `cycle` error if a task would end up
+   * inside its own subtree.
+   */
   static async moveTasksToParent(
     taskIds: string | string[],
-    newParentId?: string
+    newParentId?: string,
+    index?: number
   ): Promise<void> {
-    const database = await DatabaseService.getConnection();
-
-    // Normalize to array
-    const idsArray = Array.isArray(taskIds) ? taskIds : [taskIds];
-
-    if (idsArray.length === 0) return;
-
-    // Get the tasks being moved to check their current parents
-    const tasksToMove = (await database.select(
-      `SELECT id, parent_id, completed FROM tasks WHERE id IN (${idsArray
-        .map(() => "?")
-        .join(", ")})`,
-      idsArray
-    )) as DatabaseTask[];
-
-    if (tasksToMove.length === 0) return;
-
-    // Get the next order number for the new parent
-    const [{ max_order }] = (await database.select(
-      "SELECT COALESCE(MAX(task_order), -1) as max_order FROM tasks WHERE parent_id IS $1",
-      [newParentId || null]
-    )) as [{ max_order: number }];
-
-    // Build CASE statement for updating task_order in a single query
-    const orderCaseClauses = tasksToMove
-      .map((_, index) => `WHEN id = ? THEN ${max_order + 1 + index}`)
-      .join(" ");
-    const placeholders = idsArray.map(() => "?").join(", ");
-
-    // Update all tasks' parent_id and task_order in one query
-    await database.execute(
-      `UPDATE tasks SET parent_id = ?, task_order = CASE ${orderCaseClauses} END WHERE id IN (${placeholders})`,
-      [newParentId || null, ...idsArray, ...idsArray]
-    );
-
-    // Handle parent-child completion logic for both old and new parents
-    const uniqueOldParents = new Set(
-      tasksToMove
-        .map((task) => task.parent_id)
-        .filter((id): id is string => Boolean(id))
-    );
-
-    for (const oldParentId of uniqueOldParents) {
-      // Check if old parent should be auto-completed/uncompleted after losing children
-      await this.updateParentCompletionStatus(oldParentId);
-    }
-
-    if (newParentId) {
-      // Check if new parent should be auto-completed/uncompleted after gaining children
-      await this.updateParentCompletionStatus(newParentId);
-    }
+    const ids = Array.isArray(taskIds) ? taskIds : [taskIds];
+    await invoke("move_tasks", { ids, newParentId, index });
   }
 
+  /**
+   * Sidebar filters with labels and per-day counts, computed by the backend
+   * in its configured timezone
+   */
   static async generateDateFiltersFromDatabase(): Promise<DateFilter[]> {
-    const distinctDates = await this.getAllDistinctDates();
-    const taskCounts = await this.getTaskCountsByDate();
-    return generateDateFiltersFromDates(distinctDates, taskCounts);
+    const records = await invoke<DateFilterRecord[]>("date_filters");
+    return records.map((record) => ({
+      ...record,
+      dueDate: record.dueDate ?? undefined,
+    }));
   }
 
+  /**
+   * Reschedule tasks along with their incomplete subtasks; omitting the date
+   * turns them into someday tasks
+   */
   static async updateTasksDates(
     taskIds: string | string[],
-    newDate: Date
+    dueDate?: string,
+    dueTime?: string
   ): Promise<void> {
-    const database = await DatabaseService.getConnection();
-    const dateStr = newDate.toISOString();
-
-    // Normalize to array
-    const idsArray = Array.isArray(taskIds) ? taskIds : [taskIds];
-
-    if (idsArray.length === 0) return;
-
-    // Update all specified tasks in one query
-    const placeholders = idsArray.map(() => "?").join(", ");
-    await database.execute(
-      `UPDATE tasks SET due_date = ? WHERE id IN (${placeholders})`,
-      [dateStr, ...idsArray]
-    );
+    const ids = Array.isArray(taskIds) ? taskIds : [taskIds];
+    await invoke("set_due_date", { ids, dueDate, dueTime });
+  }
 
-    // Get all subtasks recursively for all tasks and update their dates too
-    const allSubtaskIds: string[] = [];
-    for (const taskId of idsArray) {
-      const subtasks = await this.getAllSubtasksRecursive(taskId);
-      allSubtaskIds.push(...subtasks.map((task) => task.id));
-    }
+  static async listTags(): Promise<Tag[]> {
+    const records = await invoke<TagRecord[]>("list_tags");
+    return records.map((record) => ({
+      ...record,
+      createdAt: new Date(record.createdAt),
+    }));
+  }
 
-    // Update only non-completed subtasks in one query if there are any
-    if (allSubtaskIds.length > 0) {
-      const subtaskPlaceholders = allSubtaskIds.map(() => "?").join(", ");
-      await database.execute(
-        `UPDATE tasks SET due_date = ? WHERE id IN (${subtaskPlaceholders}) AND completed = 0`,
-        [dateStr, ...allSubtaskIds]
-      );
-    }
+  static async createTag(name: string): Promise<Tag> {
+    const record = await invoke<TagRecord>("create_tag", { name });
+    return { ...record, createdAt: new Date(record.createdAt) };
   }
 
-  private static async getAllSubtasksRecursive(
-    taskId: string
-  ): Promise<DatabaseTask[]> {
-    const database = await DatabaseService.getConnection();
-    const directSubtasks = (await database.select(
-      "SELECT id, name, parent_id, completed, completed_at, created_at, due_date, task_order FROM tasks WHERE parent_id = $1",
-      [taskId]
-    )) as DatabaseTask[];
+  static async renameTag(id: string, name: string): Promise<void> {
+    await invoke("rename_tag", { id, name });
+  }
 
-    const allSubtasks = [...directSubtasks];
+  /** Move every task of the source tags to the target and delete them */
+  static async mergeTags(sourceIds: string[], targetId: string): Promise<void> {
+    await invoke("merge_tags", { sourceIds, targetId });
+  }
 
-    // Recursively get subtasks of subtasks
-    for (const subtask of directSubtasks) {
-      const nestedSubtasks = await this.getAllSubtasksRecursive(subtask.id);
-      allSubtasks.push(...nestedSubtasks);
-    }
+  static async deleteTag(id: string): Promise<void> {
+    await invoke("delete_tag", { id });
+  }
 
-    return allSubtasks;
+  static async attachTags(taskIds: string[], tagIds: string[]): Promise<void> {
+    await invoke("attach_tags", { taskIds, tagIds });
   }
 
-  static async getAllDistinctDates(): Promise<Date[]> {
-    const database = await DatabaseService.getConnection();
-
-    // Get all distinct due dates
-    const dateRows = (await database.select(
-      `SELECT DISTINCT DATE(due_date) as date_str FROM tasks
-       ORDER BY date_str DESC`
-    )) as Array<{ date_str: string }>;
-
-    return dateRows.map((row) => new Date(row.date_str + "T00:00:00.000Z"));
-  }
-
-  static async getTaskCountsByDate(): Promise<
-    Map<string, { total: number; completed: number }>
-  > {
-    const database = await DatabaseService.getConnection();
-    const rows = (await database.select(
-      `SELECT 
-        DATE(due_date) as date_str, 
-        COUNT(id) as total_count,
-        SUM(CASE WHEN completed = 1 THEN 1 ELSE 0 END) as completed_count
-       FROM tasks 
-       GROUP BY date_str`
-    )) as Array<{
-      date_str: string;
-      total_count: number;
-      completed_count: number;
-    }>;
-
-    const counts = new Map<string, { total: number; completed: number }>();
-    for (const row of rows) {
-      counts.set(row.date_str, {
-        total: row.total_count,
-        completed: row.completed_count,
-      });
-    }
-    return counts;
+  static async detachTags(taskIds: string[], tagIds: string[]): Promise<void> {
+    await invoke("detach_tags", { taskIds, tagIds });
   }
 }
@@ -1,3 +1,12 @@
+export type Priority = "none" | "low" | "medium" | "high" | "urgent";
+
+// Manual keeps overdue tasks first then the manual order; priority puts the
+// highest priority first, then overdue, then the manual order
+export type TaskSort = "manual" | "priority";
+
+// Schedule the next occurrence from the due date or the completion date
+export type RepeatFrom = "due" | "completion";
+
 export interface Task {
   id: string;
   name: string;
@@ -5,8 +14,18 @@ export interface Task {
   completed: boolean;
   completedAt?: Date;
   createdAt: Date;
-  dueDate: Date;
+  // Calendar day (YYYY-MM-DD), absent for someday tasks
+  dueDate?: string;
+  // Wall-clock deadline (HH:MM:SS) on dueDate, absent for all-day tasks
+  dueTime?: string;
+  overdue: boolean;
   order: number;
+  priority: Priority;
+  // RFC 5545 RRULE, absent for one-off tasks
+  recurrence?: string;
+  repeatFrom: RepeatFrom;
+  // 1-based position in the recurring series
+  occurrence: number;
   completedSubtasks: number;
   totalSubtasks: number;
 }
@@ -17,11 +36,217 @@ export interface Column {
 }
 
 export interface DateFilter {
-  type: "all" | "today" | "tomorrow" | "yesterday" | "date" | "range";
-  date?: Date;
-  startDate?: Date;
-  endDate?: Date;
+  type:
+    | "all"
+    | "today"
+    | "tomorrow"
+    | "yesterday"
+    | "date"
+    | "range"
+    | "someday";
+  // Calendar day (YYYY-MM-DD) in the backend's configured timezone
+  day?: string;
+  // The filter's day, used as the due date for new tasks
+  dueDate?: string;
+  startDay?: string;
+  endDay?: string;
   label: string;
   totalTaskCount?: number;
   completedTaskCount?: number;
 }
+
+export interface Profile {
+  name: string;
+  // Absolute path of the profile's database file
+  database: string;
+  active: boolean;
+}
+
+export type BackupReason = "startup" | "migration" | "scheduled" | "restore";
+
+export interface Backup {
+  // File name, passed back to restore it
+  id: string;
+  createdAt: Date;
+  reason: BackupReason;
+  size: number;
+}
+
+export interface BackupSettings {
+  // 0 to only back up at startup and before migrations
+  intervalMinutes: number;
+  keepHourly: number;
+  keepDaily: number;
+  keepWeekly: number;
+}
+
+// Versioned JSON snapshot of the task tree, kept as-is for saving to a file
+export interface ExportDocument {
+  format: "act";
+  version: number;
+  exportedAt: string;
+  tasks: unknown[];
+}
+
+// Tasks due within from..to (YYYY-MM-DD, either end open) and their
+// ancestors, limited to the subtree of rootId
+export interface ExportFilter {
+  from?: string;
+  to?: string;
+  rootId?: string;
+}
+
+export interface ImportOptions {
+  // Update tasks with the same id (default), or import everything as new
+  ids?: "merge" | "remap";
+  dryRun?: boolean;
+}
+
+export interface ImportReport {
+  created: number;
+  updated: number;
+  tagsCreated: number;
+  warnings: string[];
+  dryRun: boolean;
+}
+
+export interface Tag {
+  id: string;
+  name: string;
+  createdAt: Date;
+  taskCount: number;
+}
+
+export interface TagFilter {
+  tagIds: string[];
+  // Match tasks carrying any (default) or all of the tags
+  mode?: "any" | "all";
+}
+
+// Fire at a fixed instant, or at an offset from the task's due time
+// (negative for before; all-day tasks count from the start of the day)
+export type ReminderTrigger =
+  | { type: "absolute"; at: Date }
+  | { type: "relative"; offsetMinutes: number };
+
+export interface Reminder {
+  id: string;
+  taskId: string;
+  remindAt?: Date;
+  offsetMinutes?: number;
+  deliveredAt?: Date;
+  createdAt: Date;
+}
+
+export interface SearchHit {
+  task: Task;
+  // Task name as HTML, matched terms wrapped in <mark>
+  nameHtml: string;
+  // Excerpt of the notes around the matches, when the notes matched
+  notesHtml?: string;
+  // Lower is a better match
+  rank: number;
+  // Ancestors from the root down, one per column to open
+  path: { id: string; name: string }[];
+}
+
+// Change to the task tree recorded for undo; "reschedule" changed due dates,
+// "restore" brought tasks back from the trash and "edit" changed several
+// fields of a task at once through the API
+export type OperationKind =
+  | "create"
+  | "rename"
+  | "toggle"
+  | "delete"
+  | "restore"
+  | "move"
+  | "reorder"
+  | "reschedule"
+  | "edit";
+
+export interface Operation {
+  id: number;
+  kind: OperationKind;
+  // Name of the task acted on, the first one when there were several
+  label: string;
+  taskCount: number;
+  createdAt: Date;
+}
+
+// A deleted task with the subtasks deleted along with it
+export interface TrashEntry {
+  // Subtask counts cover the subtasks deleted along with it
+  task: Task;
+  deletedAt: Date;
+  // Tasks a restore brings back, this one included
+  taskCount: number;
+  // Where a restore puts it, from the root down; empty for the top level
+  path: { id: string; name: string }[];
+}
+
+export interface TrashSettings {
+  // Days deleted tasks are kept, 0 to keep them until purged
+  retentionDays: number;
+}
+
+// Where a change to the tasks was made
+export type ChangeOrigin = "gui" | "cli" | "api" | "sync";
+
+// Part of a task a history event is about; "created" has the name as its
+// new value and "removed" as its old one
+export type TaskField =
+  | "created"
+  | "removed"
+  | "name"
+  | "parent"
+  | "completed"
+  | "dueDate"
+  | "dueTime"
+  | "notes"
+  | "priority"
+  | "recurrence"
+  | "repeatFrom"
+  | "tags"
+  | "trashed";
+
+export interface TaskEvent {
+  id: number;
+  taskId: string;
+  field: TaskField;
+  // JSON values, null where there was none
+  oldValue: unknown;
+  newValue: unknown;
+  origin: ChangeOrigin;
+  // Set when an undo or redo made the change rather than an edit
+  step: "undo" | "redo" | null;
+  createdAt: Date;
+}
+
+export interface TaskHistory {
+  // Oldest first
+  events: TaskEvent[];
+  // How often an edit pushed a due date the task already had to a later day
+  timesRescheduled: number;
+}
+
+export interface RescheduledTask {
+  task: Task;
+  timesRescheduled: number;
+  // The first due date it was pushed back from, YYYY-MM-DD
+  originalDueDate?: string;
+}
+
+export interface ApiSettings {
+  // The local HTTP API is off until enabled
+  enabled: boolean;
+  // Listened on at 127.0.0.1 only
+  port: number;
+}
+
+// What a request to the HTTP API changed, pushed to the window
+export interface ApiChanges {
+  // Tasks created or changed, as they are now
+  tasks: Task[];
+  // Ids of tasks moved to the trash along with their subtasks
+  trashed: string[];
+}
@@ -1,5 +1,11 @@
 import { DateFilter, Task } from "../types";
 
+// Parse a calendar day (YYYY-MM-DD) as local midnight
+export const parseDay = (day: string): Date => {
+  const [year, month, date] = day.split("-").map(Number);
+  return new Date(year, month - 1, date);
+};
+
 // Check if a date is today
 export const isToday = (date: Date): boolean => {
   const today = new Date();
@@ -59,7 +65,9 @@ export const getTaskDates = (tasks: Task[]): Date[] => {
 
   tasks.forEach((task) => {
     // Add due date (this is what we filter by now)
-    dates.add(task.dueDate.toDateString());
+    if (task.dueDate) {
+      dates.add(parseDay(task.dueDate).toDateString());
+    }
 
     // Add completion date if exists
     if (task.completedAt) {
@@ -72,109 +80,21 @@ export const getTaskDates = (tasks: Task[]): Date[] => {
     .sort((a, b) => b.getTime() - a.getTime()); // Sort newest first
 };
 
-// Generate date filter options from distinct dates
-export const generateDateFiltersFromDates = (
-  distinctDates: Date[],
-  taskCounts: Map<string, { total: number; completed: number }>
-): DateFilter[] => {
-  const filters: DateFilter[] = [];
-  const today = new Date();
-  const tomorrow = new Date();
-  tomorrow.setDate(tomorrow.getDate() + 1);
-  const yesterday = new Date();
-  yesterday.setDate(yesterday.getDate() - 1);
-
-  const toYYYYMMDD = (d: Date) => d.toISOString().split("T")[0];
-
-  const getCounts = (date: Date) => {
-    const counts = taskCounts.get(toYYYYMMDD(date));
-    return {
-      totalTaskCount: counts?.total || 0,
-      completedTaskCount: counts?.completed || 0,
-    };
-  };
-
-  // Calculate total counts across all dates
-  let totalAllTasks = 0;
-  let completedAllTasks = 0;
-  taskCounts.forEach((counts) => {
-    totalAllTasks += counts.total;
-    completedAllTasks += counts.completed;
-  });
-
-  // Add "All" filter at the very top
-  filters.push({
-    type: "all",
-    label: "All",
-    totalTaskCount: totalAllTasks,
-    completedTaskCount: completedAllTasks,
-  });
-
-  // Always add Today filter
-  filters.push({
-    type: "today",
-    date: today,
-    label: "Today",
-    ...getCounts(today),
-  });
-
-  // Always add Tomorrow filter
-  filters.push({
-    type: "tomorrow",
-    date: tomorrow,
-    label: "Tomorrow",
-    ...getCounts(tomorrow),
-  });
-
-  // Add Yesterday filter if there are tasks for yesterday
-  const hasYesterdayTasks = distinctDates.some((date) => isYesterday(date));
-  if (hasYesterdayTasks) {
-    filters.push({
-      type: "yesterday",
-      date: yesterday,
-      label: "Yesterday",
-      ...getCounts(yesterday),
-    });
-  }
-
-  // Add historical filters for other dates
-  distinctDates.forEach((date) => {
-    if (!isToday(date) && !isTomorrow(date) && !isYesterday(date)) {
-      filters.push({
-        type: "date",
-        date,
-        label: formatDateLabel(date),
-        ...getCounts(date),
-      });
-    }
-  });
-
-  return filters;
-};
-
-// Get a Date object from a DateFilter
-export const getDateFromFilter = (filter?: DateFilter): Date | undefined => {
+// Get the due day (YYYY-MM-DD) new tasks get under a DateFilter, undefined
+// for someday tasks. The sidebar entries are the backend's, whose days are
+// in its configured timezone rather than the browser's
+export const getDateFromFilter = (
+  filter: DateFilter | undefined,
+  dateFilters: DateFilter[],
+): string | undefined => {
   if (!filter) return undefined;
 
-  const today = new Date();
   switch (filter.type) {
     case "all":
-      return undefined; // All filter doesn't correspond to a specific date
-    case "today":
-      return today;
-    case "tomorrow":
-      const tomorrow = new Date(today);
-      tomorrow.setDate(tomorrow.getDate() + 1);
-      return tomorrow;
-    case "yesterday":
-      const yesterday = new Date(today);
-      yesterday.setDate(yesterday.getDate() - 1);
-      return yesterday;
-    case "date":
-      return filter.date;
-    case "range":
-      return filter.startDate; // Use start date for range filters
+      // All filter doesn't correspond to a specific date, default to today
+      return dateFilters.find((entry) => entry.type === "today")?.dueDate;
     default:
-      return undefined;
+      // The filter's day, as computed by the backend
+      return filter.dueDate;
   }
 };